@@ -0,0 +1,16 @@
+-- Full-text and fuzzy search over market questions and slugs
+
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Weighted document: question matches rank above slug matches
+ALTER TABLE markets ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', COALESCE(question, '')), 'A') ||
+    setweight(to_tsvector('english', COALESCE(replace(slug, '-', ' '), '')), 'B')
+) STORED;
+
+-- Index for full-text queries (@@)
+CREATE INDEX idx_markets_search_vector ON markets USING GIN (search_vector);
+
+-- Indexes for fuzzy matching (typos, partial words)
+CREATE INDEX idx_markets_question_trgm ON markets USING GIN (question gin_trgm_ops);
+CREATE INDEX idx_markets_slug_trgm ON markets USING GIN (slug gin_trgm_ops);
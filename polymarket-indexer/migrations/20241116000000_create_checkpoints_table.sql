@@ -0,0 +1,7 @@
+-- Last fully indexed block per indexer, so runs can resume where they stopped
+
+CREATE TABLE checkpoints (
+    name TEXT PRIMARY KEY,
+    block_number BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
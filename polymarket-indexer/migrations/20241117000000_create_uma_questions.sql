@@ -0,0 +1,56 @@
+-- UMA CTF Adapter questions and their lifecycle events
+--
+-- A question's condition ID is derived from the adapter address and question
+-- ID, so questions link to markets without waiting for TokenRegistered (no
+-- foreign key: a question is usually initialized before its market exists).
+
+CREATE TABLE uma_questions (
+    question_id TEXT PRIMARY KEY,
+    condition_id TEXT NOT NULL,
+    adapter TEXT NOT NULL,
+
+    -- From QuestionInitialized (NULL when only later events were indexed)
+    creator TEXT,
+    request_timestamp BIGINT,
+    ancillary_data TEXT,  -- raw UTF-8 ancillary data
+    title TEXT,
+    description TEXT,
+    resolution_data TEXT,  -- res_data: price to outcome mapping
+
+    -- 'initialized', 'reset' (disputed and re-requested) or 'resolved'
+    status TEXT NOT NULL,
+    paused BOOLEAN NOT NULL DEFAULT FALSE,
+    reset_count INTEGER NOT NULL DEFAULT 0,
+
+    -- From QuestionResolved
+    settled_price TEXT,  -- int256 as decimal text
+    payouts JSONB,  -- ["1", "0"]
+
+    initialized_block BIGINT,
+    resolved_block BIGINT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_uma_questions_condition_id ON uma_questions(condition_id);
+
+CREATE TRIGGER update_uma_questions_updated_at
+    BEFORE UPDATE ON uma_questions
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Every lifecycle event, for dispute and pause history
+CREATE TABLE uma_question_events (
+    tx_hash TEXT NOT NULL,
+    log_index BIGINT NOT NULL,
+    question_id TEXT NOT NULL,
+    event TEXT NOT NULL,  -- initialized, resolved, reset, paused, unpaused
+    block_number BIGINT NOT NULL,
+    settled_price TEXT,
+    payouts JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX idx_uma_question_events_question_id ON uma_question_events(question_id, block_number);
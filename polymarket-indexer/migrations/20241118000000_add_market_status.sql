@@ -0,0 +1,38 @@
+-- Explicit market lifecycle status
+--
+-- registered -> trading -> closed -> disputed -> resolved, moved by Gamma
+-- flags and UMA question events (see polymarket::lifecycle). Every applied
+-- transition is kept; transitions the state machine rejects are recorded in
+-- data_anomalies instead.
+
+ALTER TABLE markets
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'registered',
+    ADD COLUMN status_updated_at TIMESTAMPTZ;
+
+CREATE INDEX idx_markets_status ON markets(status);
+
+CREATE TABLE market_status_transitions (
+    id BIGSERIAL PRIMARY KEY,
+    condition_id TEXT NOT NULL REFERENCES markets(condition_id) ON DELETE CASCADE,
+    from_status TEXT NOT NULL,
+    to_status TEXT NOT NULL,
+    source TEXT NOT NULL,  -- 'gamma' or 'uma'
+    block_number BIGINT,  -- block of the on-chain event (NULL for Gamma)
+    transitioned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_market_status_transitions_condition_id
+    ON market_status_transitions(condition_id, id);
+
+-- Data that contradicts what was already indexed, kept for review
+CREATE TABLE data_anomalies (
+    id BIGSERIAL PRIMARY KEY,
+    condition_id TEXT,
+    kind TEXT NOT NULL,  -- e.g. 'invalid_status_transition'
+    detail TEXT NOT NULL,
+    block_number BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_data_anomalies_kind ON data_anomalies(kind, created_at);
+CREATE INDEX idx_data_anomalies_condition_id ON data_anomalies(condition_id);
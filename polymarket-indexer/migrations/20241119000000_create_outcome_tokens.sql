@@ -0,0 +1,23 @@
+-- Outcome tokens: ERC-1155 position IDs of each market outcome
+--
+-- Trades and balances reference token IDs, not condition IDs, so each token
+-- maps back to its market, outcome and the other token of the pair. Token
+-- IDs are uint256 and stored as NUMERIC (bind and select them as text).
+-- Rows are written when a market is upserted; outcome labels are filled in
+-- once Gamma metadata arrives.
+
+CREATE TABLE outcome_tokens (
+    token_id NUMERIC(78, 0) PRIMARY KEY,
+    condition_id TEXT NOT NULL REFERENCES markets(condition_id) ON DELETE CASCADE,
+    outcome_index SMALLINT NOT NULL,  -- position in `markets.outcomes`
+    outcome_label TEXT,  -- e.g. 'Yes' (NULL until metadata is fetched)
+    complement_token_id NUMERIC(78, 0) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (condition_id, outcome_index)
+);
+
+CREATE TRIGGER update_outcome_tokens_updated_at
+    BEFORE UPDATE ON outcome_tokens
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
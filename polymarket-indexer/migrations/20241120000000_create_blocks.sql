@@ -0,0 +1,20 @@
+-- Headers of blocks that contain indexed events
+--
+-- Fetched once per block (see blocks::BlockRecorder) so event times don't
+-- need an RPC round trip per query. The hash and parent hash allow checking
+-- stored blocks against the chain after a reorg.
+
+CREATE TABLE blocks (
+    number BIGINT PRIMARY KEY,
+    hash TEXT NOT NULL,
+    parent_hash TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_blocks_timestamp ON blocks(timestamp);
+
+-- Time of the registration block (NULL until its header is recorded)
+ALTER TABLE markets ADD COLUMN registered_at TIMESTAMPTZ;
+
+CREATE INDEX idx_markets_registered_at ON markets(registered_at);
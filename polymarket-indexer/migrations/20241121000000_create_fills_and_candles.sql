@@ -0,0 +1,55 @@
+-- Exchange fills and the OHLCV candles aggregated from them
+--
+-- Every OrderFilled event from both exchanges is kept. The trade columns
+-- (token_id .. notional) are NULL for taker-order summaries and fills that
+-- don't swap an outcome token for collateral (see polymarket::trades).
+-- Amounts are raw 6-decimal units.
+
+CREATE TABLE fills (
+    tx_hash TEXT NOT NULL,
+    log_index BIGINT NOT NULL,
+    exchange TEXT NOT NULL,
+    order_hash TEXT NOT NULL,
+    maker TEXT NOT NULL,
+    taker TEXT NOT NULL,
+    maker_asset_id NUMERIC(78, 0) NOT NULL,
+    taker_asset_id NUMERIC(78, 0) NOT NULL,
+    maker_amount_filled NUMERIC(78, 0) NOT NULL,
+    taker_amount_filled NUMERIC(78, 0) NOT NULL,
+    fee NUMERIC(78, 0) NOT NULL,
+    token_id NUMERIC(78, 0),
+    side TEXT,  -- maker's side: 'buy' or 'sell'
+    price DOUBLE PRECISION,
+    size BIGINT,
+    notional BIGINT,
+    block_number BIGINT NOT NULL,
+    block_time TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX idx_fills_block_number ON fills(block_number);
+CREATE INDEX idx_fills_block_time ON fills(block_time);
+CREATE INDEX idx_fills_token_id ON fills(token_id, block_time) WHERE token_id IS NOT NULL;
+
+-- One row per outcome token, resolution ('1m', '1h', '1d') and bucket.
+-- first_*/last_* locate the opening and closing fills so candles built from
+-- fills seen out of order merge correctly.
+CREATE TABLE candles (
+    token_id NUMERIC(78, 0) NOT NULL,
+    resolution TEXT NOT NULL,
+    bucket_start TIMESTAMPTZ NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume BIGINT NOT NULL,
+    notional BIGINT NOT NULL,
+    trades BIGINT NOT NULL,
+    first_block BIGINT NOT NULL,
+    first_log_index BIGINT NOT NULL,
+    last_block BIGINT NOT NULL,
+    last_log_index BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (token_id, resolution, bucket_start)
+);
@@ -0,0 +1,41 @@
+-- Conditional Tokens collateral flows and the daily market series built
+-- from them
+--
+-- position_events keeps the split, merge and redeem events that move
+-- collateral in or out of a market (see polymarket::positions). Amounts are
+-- raw 6-decimal collateral units.
+
+CREATE TABLE position_events (
+    tx_hash TEXT NOT NULL,
+    log_index BIGINT NOT NULL,
+    contract TEXT NOT NULL,
+    kind TEXT NOT NULL,  -- 'split', 'merge' or 'redeem'
+    condition_id TEXT NOT NULL,
+    stakeholder TEXT NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_time TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX idx_position_events_block_number ON position_events(block_number);
+CREATE INDEX idx_position_events_block_time ON position_events(block_time);
+CREATE INDEX idx_position_events_condition_id ON position_events(condition_id, block_time);
+
+-- One row per market and UTC day with activity. Columns are sums over the
+-- day, so rows merge by addition; open interest is the running total of
+-- split_amount - merge_amount - redeem_amount (see db::market_stats).
+CREATE TABLE market_daily_stats (
+    condition_id TEXT NOT NULL,
+    day TIMESTAMPTZ NOT NULL,
+    split_amount BIGINT NOT NULL DEFAULT 0,
+    merge_amount BIGINT NOT NULL DEFAULT 0,
+    redeem_amount BIGINT NOT NULL DEFAULT 0,
+    volume BIGINT NOT NULL DEFAULT 0,
+    trades BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (condition_id, day)
+);
+
+CREATE INDEX idx_market_daily_stats_day ON market_daily_stats(day);
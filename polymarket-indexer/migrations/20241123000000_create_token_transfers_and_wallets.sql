@@ -0,0 +1,60 @@
+-- Conditional Tokens ERC-1155 transfers and the per-wallet analytics
+-- replayed from them, the fills and the position events
+--
+-- A TransferBatch is stored as one row per token, numbered by batch_index
+-- (always 0 for TransferSingle). Amounts are raw 6-decimal units.
+
+CREATE TABLE token_transfers (
+    tx_hash TEXT NOT NULL,
+    log_index BIGINT NOT NULL,
+    batch_index INTEGER NOT NULL,
+    operator TEXT NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    token_id NUMERIC(78, 0) NOT NULL,
+    amount NUMERIC(78, 0) NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_time TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tx_hash, log_index, batch_index)
+);
+
+CREATE INDEX idx_token_transfers_block_number ON token_transfers(block_number);
+CREATE INDEX idx_token_transfers_block_time ON token_transfers(block_time);
+
+-- Replaced as a whole by `wallets` rebuilds (see the wallets module).
+-- Collateral columns are raw 6-decimal units; avg_price is collateral per
+-- share of the shares still held.
+CREATE TABLE wallet_positions (
+    wallet TEXT NOT NULL,
+    token_id NUMERIC(78, 0) NOT NULL,
+    condition_id TEXT,  -- NULL when the token's market isn't indexed
+    shares BIGINT NOT NULL,
+    cost_basis BIGINT NOT NULL,
+    avg_price DOUBLE PRECISION,
+    realized_pnl BIGINT NOT NULL,
+    settled BOOLEAN NOT NULL,  -- realized_pnl counts the held shares at the payout
+    first_block BIGINT NOT NULL,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet, token_id)
+);
+
+CREATE INDEX idx_wallet_positions_condition_id ON wallet_positions(condition_id);
+
+CREATE TABLE wallet_stats (
+    wallet TEXT PRIMARY KEY,
+    markets BIGINT NOT NULL,
+    open_positions BIGINT NOT NULL,
+    resolved_markets BIGINT NOT NULL,
+    winning_markets BIGINT NOT NULL,
+    win_rate DOUBLE PRECISION,  -- NULL until a market the wallet held resolves
+    realized_pnl BIGINT NOT NULL,
+    volume BIGINT NOT NULL,
+    trades BIGINT NOT NULL,
+    first_block BIGINT NOT NULL,
+    last_block BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_wallet_stats_realized_pnl ON wallet_stats(realized_pnl);
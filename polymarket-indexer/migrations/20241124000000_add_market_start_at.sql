@@ -0,0 +1,21 @@
+-- Parsed market start time for the `GET /markets` date filters
+--
+-- `start_date` is Gamma's free-form text and is kept as received; casting it
+-- per row fails the whole query on one malformed value and can't use an
+-- index. `start_at` holds the parsed value (NULL when it doesn't parse) and
+-- is written alongside `start_date` (see MarketMetadata::start_at).
+
+ALTER TABLE markets ADD COLUMN start_at TIMESTAMPTZ;
+
+CREATE FUNCTION pg_temp.try_timestamptz(value TEXT) RETURNS TIMESTAMPTZ AS $$
+BEGIN
+    RETURN value::TIMESTAMPTZ;
+EXCEPTION WHEN others THEN
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+UPDATE markets SET start_at = pg_temp.try_timestamptz(start_date)
+WHERE start_date IS NOT NULL;
+
+CREATE INDEX idx_markets_start_at ON markets(start_at);
@@ -0,0 +1,47 @@
+-- SQLite schema, equivalent to the PostgreSQL migrations in ../migrations
+--
+-- Differences: outcomes is JSON text, timestamps are RFC 3339 text set by the
+-- application (there is no updated_at trigger) and there is no search index.
+
+CREATE TABLE markets (
+    condition_id TEXT PRIMARY KEY,
+    token0 TEXT NOT NULL,
+    token1 TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    tx_hash TEXT NOT NULL,
+    question TEXT,
+    slug TEXT,
+    pm_market_id TEXT,
+    outcomes TEXT,  -- JSON: ["Yes", "No"]
+    start_date TEXT,
+    end_date TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    metadata_fetched_at TEXT  -- NULL if not yet fetched
+);
+
+CREATE INDEX idx_markets_block_number ON markets(block_number);
+CREATE INDEX idx_markets_created_at ON markets(created_at);
+CREATE INDEX idx_markets_slug ON markets(slug) WHERE slug IS NOT NULL;
+
+CREATE TABLE tags (
+    pm_tag_id TEXT PRIMARY KEY,
+    label TEXT,
+    slug TEXT
+);
+
+CREATE INDEX idx_tags_slug ON tags(slug);
+
+CREATE TABLE market_tags (
+    condition_id TEXT NOT NULL REFERENCES markets(condition_id) ON DELETE CASCADE,
+    pm_tag_id TEXT NOT NULL REFERENCES tags(pm_tag_id) ON DELETE CASCADE,
+    PRIMARY KEY (condition_id, pm_tag_id)
+);
+
+CREATE INDEX idx_market_tags_pm_tag_id ON market_tags(pm_tag_id);
+
+CREATE TABLE checkpoints (
+    name TEXT PRIMARY KEY,
+    block_number INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
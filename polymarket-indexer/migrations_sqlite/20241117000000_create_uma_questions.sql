@@ -0,0 +1,39 @@
+-- UMA CTF Adapter questions and their lifecycle events, as in
+-- ../migrations/20241117000000_create_uma_questions.sql
+
+CREATE TABLE uma_questions (
+    question_id TEXT PRIMARY KEY,
+    condition_id TEXT NOT NULL,
+    adapter TEXT NOT NULL,
+    creator TEXT,
+    request_timestamp INTEGER,
+    ancillary_data TEXT,
+    title TEXT,
+    description TEXT,
+    resolution_data TEXT,
+    status TEXT NOT NULL,
+    paused INTEGER NOT NULL DEFAULT 0,
+    reset_count INTEGER NOT NULL DEFAULT 0,
+    settled_price TEXT,
+    payouts TEXT,  -- JSON: ["1", "0"]
+    initialized_block INTEGER,
+    resolved_block INTEGER,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_uma_questions_condition_id ON uma_questions(condition_id);
+
+CREATE TABLE uma_question_events (
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    question_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    settled_price TEXT,
+    payouts TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX idx_uma_question_events_question_id ON uma_question_events(question_id, block_number);
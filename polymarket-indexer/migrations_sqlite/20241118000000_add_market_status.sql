@@ -0,0 +1,32 @@
+-- Explicit market lifecycle status, as in
+-- ../migrations/20241118000000_add_market_status.sql
+
+ALTER TABLE markets ADD COLUMN status TEXT NOT NULL DEFAULT 'registered';
+ALTER TABLE markets ADD COLUMN status_updated_at TEXT;
+
+CREATE INDEX idx_markets_status ON markets(status);
+
+CREATE TABLE market_status_transitions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    condition_id TEXT NOT NULL REFERENCES markets(condition_id) ON DELETE CASCADE,
+    from_status TEXT NOT NULL,
+    to_status TEXT NOT NULL,
+    source TEXT NOT NULL,
+    block_number INTEGER,
+    transitioned_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_market_status_transitions_condition_id
+    ON market_status_transitions(condition_id, id);
+
+CREATE TABLE data_anomalies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    condition_id TEXT,
+    kind TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    block_number INTEGER,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_data_anomalies_kind ON data_anomalies(kind, created_at);
+CREATE INDEX idx_data_anomalies_condition_id ON data_anomalies(condition_id);
@@ -0,0 +1,15 @@
+-- Outcome tokens, as in ../migrations/20241119000000_create_outcome_tokens.sql
+--
+-- Token IDs are TEXT here: SQLite's NUMERIC affinity would round uint256
+-- values to REAL.
+
+CREATE TABLE outcome_tokens (
+    token_id TEXT PRIMARY KEY,
+    condition_id TEXT NOT NULL REFERENCES markets(condition_id) ON DELETE CASCADE,
+    outcome_index INTEGER NOT NULL,
+    outcome_label TEXT,
+    complement_token_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (condition_id, outcome_index)
+);
@@ -0,0 +1,16 @@
+-- Headers of blocks that contain indexed events, as in
+-- ../migrations/20241120000000_create_blocks.sql
+
+CREATE TABLE blocks (
+    number INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL,
+    parent_hash TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_blocks_timestamp ON blocks(timestamp);
+
+ALTER TABLE markets ADD COLUMN registered_at TEXT;
+
+CREATE INDEX idx_markets_registered_at ON markets(registered_at);
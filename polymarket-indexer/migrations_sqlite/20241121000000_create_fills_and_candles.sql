@@ -0,0 +1,48 @@
+-- Exchange fills and the OHLCV candles aggregated from them, as in
+-- ../migrations/20241121000000_create_fills_and_candles.sql
+
+CREATE TABLE fills (
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    exchange TEXT NOT NULL,
+    order_hash TEXT NOT NULL,
+    maker TEXT NOT NULL,
+    taker TEXT NOT NULL,
+    maker_asset_id TEXT NOT NULL,
+    taker_asset_id TEXT NOT NULL,
+    maker_amount_filled TEXT NOT NULL,
+    taker_amount_filled TEXT NOT NULL,
+    fee TEXT NOT NULL,
+    token_id TEXT,
+    side TEXT,
+    price REAL,
+    size INTEGER,
+    notional INTEGER,
+    block_number INTEGER NOT NULL,
+    block_time TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX idx_fills_block_number ON fills(block_number);
+CREATE INDEX idx_fills_block_time ON fills(block_time);
+CREATE INDEX idx_fills_token_id ON fills(token_id, block_time);
+
+CREATE TABLE candles (
+    token_id TEXT NOT NULL,
+    resolution TEXT NOT NULL,
+    bucket_start TEXT NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume INTEGER NOT NULL,
+    notional INTEGER NOT NULL,
+    trades INTEGER NOT NULL,
+    first_block INTEGER NOT NULL,
+    first_log_index INTEGER NOT NULL,
+    last_block INTEGER NOT NULL,
+    last_log_index INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (token_id, resolution, bucket_start)
+);
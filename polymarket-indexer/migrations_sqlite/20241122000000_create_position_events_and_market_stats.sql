@@ -0,0 +1,35 @@
+-- Conditional Tokens collateral flows and the daily market series built
+-- from them, as in
+-- ../migrations/20241122000000_create_position_events_and_market_stats.sql
+
+CREATE TABLE position_events (
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    contract TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    condition_id TEXT NOT NULL,
+    stakeholder TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    block_time TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (tx_hash, log_index)
+);
+
+CREATE INDEX idx_position_events_block_number ON position_events(block_number);
+CREATE INDEX idx_position_events_block_time ON position_events(block_time);
+CREATE INDEX idx_position_events_condition_id ON position_events(condition_id, block_time);
+
+CREATE TABLE market_daily_stats (
+    condition_id TEXT NOT NULL,
+    day TEXT NOT NULL,
+    split_amount INTEGER NOT NULL DEFAULT 0,
+    merge_amount INTEGER NOT NULL DEFAULT 0,
+    redeem_amount INTEGER NOT NULL DEFAULT 0,
+    volume INTEGER NOT NULL DEFAULT 0,
+    trades INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (condition_id, day)
+);
+
+CREATE INDEX idx_market_daily_stats_day ON market_daily_stats(day);
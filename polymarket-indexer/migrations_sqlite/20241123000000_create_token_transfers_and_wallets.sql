@@ -0,0 +1,54 @@
+-- Conditional Tokens ERC-1155 transfers and per-wallet analytics, as in
+-- ../migrations/20241123000000_create_token_transfers_and_wallets.sql
+
+CREATE TABLE token_transfers (
+    tx_hash TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    batch_index INTEGER NOT NULL,
+    operator TEXT NOT NULL,
+    from_address TEXT NOT NULL,
+    to_address TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    block_number INTEGER NOT NULL,
+    block_time TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (tx_hash, log_index, batch_index)
+);
+
+CREATE INDEX idx_token_transfers_block_number ON token_transfers(block_number);
+CREATE INDEX idx_token_transfers_block_time ON token_transfers(block_time);
+
+CREATE TABLE wallet_positions (
+    wallet TEXT NOT NULL,
+    token_id TEXT NOT NULL,
+    condition_id TEXT,
+    shares INTEGER NOT NULL,
+    cost_basis INTEGER NOT NULL,
+    avg_price REAL,
+    realized_pnl INTEGER NOT NULL,
+    settled INTEGER NOT NULL,
+    first_block INTEGER NOT NULL,
+    last_block INTEGER NOT NULL,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (wallet, token_id)
+);
+
+CREATE INDEX idx_wallet_positions_condition_id ON wallet_positions(condition_id);
+
+CREATE TABLE wallet_stats (
+    wallet TEXT PRIMARY KEY,
+    markets INTEGER NOT NULL,
+    open_positions INTEGER NOT NULL,
+    resolved_markets INTEGER NOT NULL,
+    winning_markets INTEGER NOT NULL,
+    win_rate REAL,
+    realized_pnl INTEGER NOT NULL,
+    volume INTEGER NOT NULL,
+    trades INTEGER NOT NULL,
+    first_block INTEGER NOT NULL,
+    last_block INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_wallet_stats_realized_pnl ON wallet_stats(realized_pnl);
@@ -0,0 +1,419 @@
+// Request handlers for the REST API
+
+use crate::api::{ApiError, AppState};
+use crate::candles::Resolution;
+use crate::db::markets::{self, MarketFilter};
+use crate::db::models::{
+    Candle, Market, MarketSearchResult, MarketSeriesPoint, MarketStatusTransition, Tag, TagSummary,
+    UmaQuestion,
+};
+use crate::db::{candles, market_stats, market_status, market_tags, tokens, uma_questions};
+use crate::polymarket::lifecycle::MarketStatus;
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Page size when `limit` is not given
+const DEFAULT_PAGE_SIZE: i64 = 50;
+
+/// Largest page a client may request
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// Query parameters for `GET /markets`
+#[derive(Debug, Deserialize)]
+pub struct ListMarketsQuery {
+    pub tag: Option<String>,
+    pub from_block: Option<i64>,
+    pub to_block: Option<i64>,
+    pub start_after: Option<DateTime<Utc>>,
+    pub start_before: Option<DateTime<Utc>>,
+    pub has_metadata: Option<bool>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Query parameters for `GET /markets/search`
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for `GET /tokens/:token_id/candles`
+#[derive(Debug, Deserialize)]
+pub struct CandlesQuery {
+    /// `1m`, `1h` (default) or `1d`
+    pub resolution: Option<String>,
+    /// Earliest bucket start (inclusive)
+    pub start: Option<DateTime<Utc>>,
+    /// Latest bucket start (exclusive)
+    pub end: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for `GET /markets/:condition_id/series`
+#[derive(Debug, Deserialize)]
+pub struct SeriesQuery {
+    /// Earliest day (inclusive)
+    pub start: Option<DateTime<Utc>>,
+    /// Latest day (exclusive)
+    pub end: Option<DateTime<Utc>>,
+}
+
+/// One page of results
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Market with its tags, on-chain question and status history
+#[derive(Debug, Serialize)]
+pub struct MarketResponse {
+    #[serde(flatten)]
+    pub market: Market,
+    pub tags: Vec<Tag>,
+    /// UMA CTF Adapter question, when its events were indexed
+    pub uma_question: Option<UmaQuestion>,
+    /// Status transitions, oldest first
+    pub status_history: Vec<MarketStatusTransition>,
+}
+
+/// Market of an outcome token, plus which outcome the token is
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    #[serde(flatten)]
+    pub market: MarketResponse,
+    /// Position in the market's outcome list
+    pub outcome_index: i16,
+    /// Outcome label, e.g. "Yes", once Gamma metadata is fetched
+    pub outcome_label: Option<String>,
+    /// The other token of the pair
+    pub complement_token_id: String,
+}
+
+/// `GET /markets`
+pub async fn list_markets(
+    State(state): State<AppState>,
+    Query(query): Query<ListMarketsQuery>,
+) -> Result<Json<Page<Market>>, ApiError> {
+    let limit = page_size(query.limit)?;
+    let offset = query.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(ApiError::BadRequest(
+            "offset must not be negative".to_string(),
+        ));
+    }
+    if let (Some(from), Some(to)) = (query.from_block, query.to_block) {
+        if from > to {
+            return Err(ApiError::BadRequest(
+                "from_block must not be greater than to_block".to_string(),
+            ));
+        }
+    }
+
+    let status = query
+        .status
+        .as_deref()
+        .map(str::parse::<MarketStatus>)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+
+    let filter = MarketFilter {
+        tag_slug: query.tag,
+        from_block: query.from_block,
+        to_block: query.to_block,
+        start_after: query.start_after,
+        start_before: query.start_before,
+        has_metadata: query.has_metadata,
+        status,
+    };
+    let data = markets::list_markets(&state.pool, &filter, limit, offset).await?;
+
+    Ok(Json(Page {
+        data,
+        limit,
+        offset,
+    }))
+}
+
+/// `GET /markets/search`
+pub async fn search_markets(
+    State(state): State<AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Vec<MarketSearchResult>>, ApiError> {
+    if query.q.trim().is_empty() {
+        return Err(ApiError::BadRequest("q must not be empty".to_string()));
+    }
+    let limit = page_size(query.limit)?;
+
+    let results = markets::search_markets(&state.pool, &query.q, limit).await?;
+    Ok(Json(results))
+}
+
+/// `GET /markets/:condition_id`
+pub async fn get_market(
+    State(state): State<AppState>,
+    Path(condition_id): Path<String>,
+) -> Result<Json<MarketResponse>, ApiError> {
+    let market = markets::get_market_by_condition_id(&state.pool, &condition_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("market {} not found", condition_id)))?;
+
+    with_details(&state, market).await.map(Json)
+}
+
+/// `GET /markets/:condition_id/series`
+///
+/// One point per day with activity, oldest first. Amounts are raw 6-decimal
+/// collateral units.
+pub async fn get_market_series(
+    State(state): State<AppState>,
+    Path(condition_id): Path<String>,
+    Query(query): Query<SeriesQuery>,
+) -> Result<Json<Vec<MarketSeriesPoint>>, ApiError> {
+    if let (Some(start), Some(end)) = (query.start, query.end) {
+        if start > end {
+            return Err(ApiError::BadRequest(
+                "start must not be after end".to_string(),
+            ));
+        }
+    }
+    if markets::get_market_by_condition_id(&state.pool, &condition_id)
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::NotFound(format!(
+            "market {} not found",
+            condition_id
+        )));
+    }
+
+    let series =
+        market_stats::get_market_series(&state.pool, &condition_id, query.start, query.end).await?;
+    Ok(Json(series))
+}
+
+/// `GET /tags`
+pub async fn list_tags(State(state): State<AppState>) -> Result<Json<Vec<TagSummary>>, ApiError> {
+    let tags = market_tags::list_tags(&state.pool).await?;
+    Ok(Json(tags))
+}
+
+/// `GET /tokens/:token_id`
+pub async fn get_token(
+    State(state): State<AppState>,
+    Path(token_id): Path<String>,
+) -> Result<Json<TokenResponse>, ApiError> {
+    validate_token_id(&token_id)?;
+
+    let token = tokens::get_by_token_id(&state.pool, &token_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("token {} not found", token_id)))?;
+    let market = markets::get_market_by_condition_id(&state.pool, &token.condition_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("token {} not found", token_id)))?;
+
+    Ok(Json(TokenResponse {
+        market: with_details(&state, market).await?,
+        outcome_index: token.outcome_index,
+        outcome_label: token.outcome_label,
+        complement_token_id: token.complement_token_id,
+    }))
+}
+
+/// `GET /tokens/:token_id/candles`
+///
+/// Candles come oldest first; with more than `limit` in range, the most
+/// recent ones are returned.
+pub async fn get_candles(
+    State(state): State<AppState>,
+    Path(token_id): Path<String>,
+    Query(query): Query<CandlesQuery>,
+) -> Result<Json<Vec<Candle>>, ApiError> {
+    validate_token_id(&token_id)?;
+    let resolution = query
+        .resolution
+        .as_deref()
+        .map(str::parse::<Resolution>)
+        .transpose()
+        .map_err(ApiError::BadRequest)?
+        .unwrap_or(Resolution::Hour);
+    let limit = page_size(query.limit)?;
+    if let (Some(start), Some(end)) = (query.start, query.end) {
+        if start > end {
+            return Err(ApiError::BadRequest(
+                "start must not be after end".to_string(),
+            ));
+        }
+    }
+
+    let candles = candles::get_candles(
+        &state.pool,
+        &token_id,
+        resolution,
+        query.start,
+        query.end,
+        limit,
+    )
+    .await?;
+    Ok(Json(candles))
+}
+
+/// Reject token IDs that are not decimal numbers
+fn validate_token_id(token_id: &str) -> Result<(), ApiError> {
+    if token_id.is_empty() || !token_id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ApiError::BadRequest(
+            "token_id must be a decimal number".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate the requested page size, applying the default
+fn page_size(limit: Option<i64>) -> Result<i64, ApiError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    Ok(limit)
+}
+
+async fn with_details(state: &AppState, market: Market) -> Result<MarketResponse, ApiError> {
+    let tags = market_tags::get_tags_for_market(&state.pool, &market.condition_id).await?;
+    let uma_question =
+        uma_questions::get_question_by_condition_id(&state.pool, &market.condition_id).await?;
+    let status_history =
+        market_status::get_status_transitions(&state.pool, &market.condition_id).await?;
+    Ok(MarketResponse {
+        market,
+        tags,
+        uma_question,
+        status_history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{router, AppState};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use axum::Router;
+    use serde_json::Value;
+    use sqlx::postgres::PgPoolOptions;
+    use tower::ServiceExt;
+
+    /// Router over a pool that never connects: invalid requests are
+    /// rejected before any query
+    fn offline_router() -> Router {
+        let pool = PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        router(AppState { pool })
+    }
+
+    /// Router over the database the crate is built against (DATABASE_URL)
+    fn database_router() -> Router {
+        dotenv::dotenv().ok();
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let pool = PgPoolOptions::new()
+            .max_connections(1)
+            .connect_lazy(&url)
+            .unwrap();
+        router(AppState { pool })
+    }
+
+    async fn get(router: Router, uri: &str) -> (StatusCode, Value) {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_requests_are_rejected() {
+        let reversed = "start=2024-01-02T00:00:00Z&end=2024-01-01T00:00:00Z";
+        for (uri, message) in [
+            (
+                "/markets?limit=0".to_string(),
+                "limit must be between 1 and 500",
+            ),
+            (
+                "/markets?limit=501".to_string(),
+                "limit must be between 1 and 500",
+            ),
+            (
+                "/markets?offset=-1".to_string(),
+                "offset must not be negative",
+            ),
+            (
+                "/markets?from_block=9&to_block=5".to_string(),
+                "from_block must not be greater than to_block",
+            ),
+            (
+                "/markets?status=open".to_string(),
+                "unknown market status 'open'",
+            ),
+            ("/markets/search?q=%20".to_string(), "q must not be empty"),
+            (
+                format!("/markets/0xaa/series?{}", reversed),
+                "start must not be after end",
+            ),
+            (
+                "/tokens/0x12".to_string(),
+                "token_id must be a decimal number",
+            ),
+            (
+                "/tokens/12a/candles".to_string(),
+                "token_id must be a decimal number",
+            ),
+            (
+                "/tokens/7/candles?resolution=5m".to_string(),
+                "unknown candle resolution '5m'",
+            ),
+            (
+                "/tokens/7/candles?limit=501".to_string(),
+                "limit must be between 1 and 500",
+            ),
+            (
+                format!("/tokens/7/candles?{}", reversed),
+                "start must not be after end",
+            ),
+        ] {
+            let (status, body) = get(offline_router(), &uri).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+            assert_eq!(body["error"], message, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_markets_and_tokens_are_not_found() {
+        let condition_id = format!("0x{}", "ee".repeat(32));
+        let token_id = "9".repeat(70);
+        for (uri, message) in [
+            (
+                format!("/markets/{}", condition_id),
+                format!("market {} not found", condition_id),
+            ),
+            (
+                format!("/markets/{}/series", condition_id),
+                format!("market {} not found", condition_id),
+            ),
+            (
+                format!("/tokens/{}", token_id),
+                format!("token {} not found", token_id),
+            ),
+        ] {
+            let (status, body) = get(database_router(), &uri).await;
+            assert_eq!(status, StatusCode::NOT_FOUND, "{}", uri);
+            assert_eq!(body["error"], message, "{}", uri);
+        }
+    }
+}
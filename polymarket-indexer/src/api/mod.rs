@@ -0,0 +1,104 @@
+// Read-only REST API over indexed markets and tags
+//
+// Endpoints:
+//   GET /markets                  - paginated, filterable market list
+//   GET /markets/search?q=...     - full-text and fuzzy search over questions
+//   GET /markets/:condition_id    - single market with its tags, UMA question and
+//                                   status history
+//   GET /markets/:condition_id/series
+//                                 - daily open interest and volume (?start=&end=)
+//   GET /tags                     - all tags with market counts
+//   GET /tokens/:token_id         - market of an outcome token, with the token's
+//                                   outcome index and label
+//   GET /tokens/:token_id/candles - OHLCV candles of an outcome token
+//                                   (?resolution=1m|1h|1d&start=&end=&limit=)
+
+pub mod handlers;
+
+use crate::error::IndexerError;
+use crate::shutdown::Shutdown;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use eyre::Result;
+use serde_json::json;
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use tracing::{error, info};
+
+/// Shared state for all handlers
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+}
+
+/// Build the API router
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/markets", get(handlers::list_markets))
+        .route("/markets/search", get(handlers::search_markets))
+        .route("/markets/:condition_id", get(handlers::get_market))
+        .route(
+            "/markets/:condition_id/series",
+            get(handlers::get_market_series),
+        )
+        .route("/tags", get(handlers::list_tags))
+        .route("/tokens/:token_id", get(handlers::get_token))
+        .route("/tokens/:token_id/candles", get(handlers::get_candles))
+        .with_state(state)
+}
+
+/// Serve the API on the given address until shutdown is requested, then
+/// finish the requests already in progress
+pub async fn serve(addr: SocketAddr, state: AppState, shutdown: Shutdown) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Serving API on http://{}", listener.local_addr()?);
+
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(async move { shutdown.requested().await })
+        .await?;
+    Ok(())
+}
+
+/// Error returned by handlers, rendered as `{"error": "..."}`
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Internal(eyre::Report),
+}
+
+impl From<eyre::Report> for ApiError {
+    fn from(e: eyre::Report) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl From<IndexerError> for ApiError {
+    fn from(e: IndexerError) -> Self {
+        match e {
+            IndexerError::NotFound(message) => ApiError::NotFound(message),
+            e => ApiError::Internal(e.into()),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Internal(e) => {
+                // Details go to the log, not to the client
+                error!("API request failed: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal server error".to_string(),
+                )
+            }
+        };
+
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
@@ -0,0 +1,50 @@
+// API - read-only REST API over the indexed markets and tags
+//
+// Usage:
+//   cargo run --bin api
+//   cargo run --bin api -- --listen 0.0.0.0:8080 --config indexer.toml
+//
+// Example requests:
+//   curl 'localhost:8080/markets?tag=politics&has_metadata=true&limit=10'
+//   curl localhost:8080/markets/0x1b2ca1f8...
+//   curl localhost:8080/tags
+//   curl localhost:8080/tokens/7132...
+
+use clap::Parser;
+use eyre::Result;
+use polymarket_indexer::api::{self, AppState};
+use polymarket_indexer::cli::{self, ConfigArgs};
+use polymarket_indexer::db::create_pool;
+use polymarket_indexer::shutdown::Shutdown;
+use std::net::SocketAddr;
+use std::process::ExitCode;
+
+/// Serve indexed markets and tags as JSON
+#[derive(Debug, Parser)]
+#[command(name = "api", version)]
+struct ApiArgs {
+    #[command(flatten)]
+    config: ConfigArgs,
+
+    /// Listen address (overrides api.listen_addr)
+    #[arg(long, value_name = "ADDR")]
+    listen: Option<SocketAddr>,
+}
+
+async fn run(args: ApiArgs) -> Result<()> {
+    let config = args.config.load()?;
+    let db_pool = create_pool(&config.database).await?;
+    let addr = args.listen.unwrap_or(config.api.listen_addr);
+
+    let shutdown = Shutdown::from_config(&config.indexer);
+
+    api::serve(addr, AppState { pool: db_pool }, shutdown).await
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = ApiArgs::parse();
+    cli::init();
+
+    cli::exit(run(args).await)
+}
@@ -0,0 +1,26 @@
+// Polymarket Indexer - unified CLI
+//
+// Usage:
+//   polymarket-indexer backfill --days 7
+//   polymarket-indexer backfill --from-block 50000000 --to-block 50001000
+//   polymarket-indexer stream
+//   polymarket-indexer enrich --limit 500
+//   polymarket-indexer migrate
+//   polymarket-indexer lookup 0x1b2ca1f8...
+//   polymarket-indexer search Fed rate cut
+//   polymarket-indexer export --output markets.parquet --state-file export.state
+//   polymarket-indexer audit --strict
+//
+// Run `polymarket-indexer help <command>` for the flags of each subcommand.
+
+use clap::Parser;
+use polymarket_indexer::cli::{self, Cli};
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    cli::init();
+
+    cli::exit(cli.run().await)
+}
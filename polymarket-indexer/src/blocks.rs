@@ -0,0 +1,233 @@
+// Block headers - timestamps and hashes for blocks holding indexed events
+//
+// Logs only carry a block number, so before a batch of logs is dispatched
+// the headers of the blocks it touches are fetched (concurrently, skipping
+// blocks recorded recently) and stored in the `blocks` table. Handlers can
+// then rely on a block's timestamp being stored, e.g. for a market's
+// `registered_at`. A stored hash that differs from the fetched one means the
+// block was reorganized since it was recorded, which is reported as a data
+// anomaly.
+
+use crate::error::{IndexerError, Result};
+use crate::ingest::IngestStats;
+use crate::metrics;
+use crate::store::MarketStore;
+use async_trait::async_trait;
+use ethers::types::{Block, Log, H256};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// Data anomaly kind recorded when a stored block hash changes
+pub const BLOCK_HASH_MISMATCH: &str = "block_hash_mismatch";
+
+/// Block numbers remembered as recorded, to skip refetching their headers
+const CACHE_CAPACITY: usize = 10_000;
+
+/// The parts of a block header the indexer stores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    /// Unix timestamp (seconds)
+    pub timestamp: u64,
+}
+
+impl BlockHeader {
+    /// Header of a mined block (`None` for a pending one)
+    pub fn from_block<T>(block: &Block<T>) -> Option<Self> {
+        Some(Self {
+            number: block.number?.as_u64(),
+            hash: block.hash?,
+            parent_hash: block.parent_hash,
+            timestamp: block.timestamp.as_u64(),
+        })
+    }
+
+    /// Block hash as a 0x-prefixed hex string
+    pub fn hash_hex(&self) -> String {
+        format!("{:#x}", self.hash)
+    }
+
+    /// Parent hash as a 0x-prefixed hex string
+    pub fn parent_hash_hex(&self) -> String {
+        format!("{:#x}", self.parent_hash)
+    }
+
+    /// Block timestamp as a UTC time
+    pub fn time(&self) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(self.timestamp as i64, 0).unwrap_or_default()
+    }
+}
+
+/// Anything that can fetch block headers by number
+#[async_trait]
+pub trait BlockSource: Send + Sync {
+    /// Get the header of block `number`, if the node has it
+    async fn get_block_header(&self, number: u64) -> Result<Option<BlockHeader>>;
+}
+
+/// Fetches and stores the headers of blocks containing indexed events
+pub struct BlockRecorder {
+    store: Arc<dyn MarketStore>,
+    concurrency: usize,
+    recorded: Mutex<BTreeSet<u64>>,
+}
+
+impl BlockRecorder {
+    /// Create a recorder fetching up to `concurrency` headers at once
+    pub fn new(store: Arc<dyn MarketStore>, concurrency: usize) -> Self {
+        Self {
+            store,
+            concurrency: concurrency.max(1),
+            recorded: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Record the header of every block holding one of `logs`
+    ///
+    /// Call before dispatching the logs. Blocks recorded earlier by this
+    /// recorder are not fetched again.
+    pub async fn record(
+        &self,
+        source: &dyn BlockSource,
+        logs: &[Log],
+    ) -> eyre::Result<IngestStats> {
+        let mut stats = IngestStats::default();
+        let numbers: BTreeSet<u64> = logs
+            .iter()
+            .filter_map(|log| log.block_number)
+            .map(|number| number.as_u64())
+            .collect();
+
+        let missing: Vec<u64> = {
+            let recorded = self.recorded.lock().expect("block cache poisoned");
+            numbers
+                .iter()
+                .copied()
+                .filter(|number| !recorded.contains(number))
+                .collect()
+        };
+        metrics::BLOCK_HEADERS
+            .with_label_values(&["cached"])
+            .inc_by((numbers.len() - missing.len()) as u64);
+        if missing.is_empty() {
+            return Ok(stats);
+        }
+
+        let headers: Vec<BlockHeader> = stream::iter(missing)
+            .map(|number| async move {
+                source
+                    .get_block_header(number)
+                    .await?
+                    .ok_or_else(|| IndexerError::NotFound(format!("block {}", number)))
+            })
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+        metrics::BLOCK_HEADERS
+            .with_label_values(&["fetched"])
+            .inc_by(headers.len() as u64);
+        debug!("Fetched {} block headers", headers.len());
+
+        for stale in self.store.upsert_blocks(&headers).await? {
+            let detail = format!(
+                "block {} hash changed from {} to {}",
+                stale.number, stale.hash, stale.new_hash
+            );
+            warn!("Data anomaly ({}): {}", BLOCK_HASH_MISMATCH, detail);
+            self.store
+                .record_anomaly(None, BLOCK_HASH_MISMATCH, &detail, Some(stale.number))
+                .await?;
+            metrics::DATA_ANOMALIES
+                .with_label_values(&[BLOCK_HASH_MISMATCH])
+                .inc();
+            stats.anomalies += 1;
+        }
+        stats.blocks = headers.len();
+
+        let mut recorded = self.recorded.lock().expect("block cache poisoned");
+        recorded.extend(headers.iter().map(|header| header.number));
+        while recorded.len() > CACHE_CAPACITY {
+            recorded.pop_first();
+        }
+
+        Ok(stats)
+    }
+}
+
+/// A stored block whose hash differs from the one just fetched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplacedBlock {
+    pub number: u64,
+    /// Hash that was stored
+    pub hash: String,
+    /// Hash stored in its place
+    pub new_hash: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use crate::testing;
+    use ethers::types::U64;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct FakeChain {
+        fetches: AtomicUsize,
+        salt: u8,
+    }
+
+    #[async_trait]
+    impl BlockSource for FakeChain {
+        async fn get_block_header(&self, number: u64) -> Result<Option<BlockHeader>> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(BlockHeader {
+                hash: H256::from_low_u64_be(number * 256 + self.salt as u64),
+                ..testing::block(number)
+            }))
+        }
+    }
+
+    fn log_at(block: u64) -> Log {
+        Log {
+            block_number: Some(U64::from(block)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_each_block_once() {
+        let store = Arc::new(MemoryStore::new());
+        let recorder = BlockRecorder::new(store.clone(), 4);
+        let chain = FakeChain {
+            fetches: AtomicUsize::new(0),
+            salt: 0,
+        };
+
+        let logs = [log_at(10), log_at(10), log_at(12)];
+        let stats = recorder.record(&chain, &logs).await.unwrap();
+        assert_eq!(stats.blocks, 2);
+        recorder.record(&chain, &logs).await.unwrap();
+        assert_eq!(chain.fetches.load(Ordering::SeqCst), 2);
+
+        let block = store.get_block(12).await.unwrap().unwrap();
+        assert_eq!(block.timestamp.timestamp(), 1_700_000_024);
+
+        // A fresh recorder refetches; a different hash is a reorg
+        let reorged = FakeChain {
+            fetches: AtomicUsize::new(0),
+            salt: 1,
+        };
+        let recorder = BlockRecorder::new(store.clone(), 4);
+        let stats = recorder.record(&reorged, &logs[2..]).await.unwrap();
+        assert_eq!(stats.anomalies, 1);
+        assert_eq!(
+            store.get_block(12).await.unwrap().unwrap().hash,
+            format!("{:#x}", H256::from_low_u64_be(12 * 256 + 1))
+        );
+    }
+}
@@ -0,0 +1,297 @@
+// Candles - OHLCV aggregation of exchange trades per outcome token
+//
+// Every trade (see `polymarket::trades`) falls into one 1m, 1h and 1d bucket
+// by its block time. Stores merge the candles built from newly recorded
+// fills into the stored ones as fills arrive; `rebuild` recomputes the
+// candles of a historical range from the stored fills.
+//
+// Open and close are the prices of the earliest and latest fill by (block,
+// log index), which makes merging order-independent: a candle built from
+// fills of an earlier range can be merged after a later one.
+
+use crate::db::models::{Candle, Fill as FillRow};
+use crate::polymarket::trades::Fill;
+use crate::store::MarketStore;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use tracing::info;
+
+/// Candle width
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Resolution {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 3] = [Resolution::Minute, Resolution::Hour, Resolution::Day];
+
+    /// Name stored in the `resolution` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Minute => "1m",
+            Resolution::Hour => "1h",
+            Resolution::Day => "1d",
+        }
+    }
+
+    pub fn duration(self) -> Duration {
+        match self {
+            Resolution::Minute => Duration::minutes(1),
+            Resolution::Hour => Duration::hours(1),
+            Resolution::Day => Duration::days(1),
+        }
+    }
+
+    /// Start of the bucket holding `time`
+    pub fn bucket_start(self, time: DateTime<Utc>) -> DateTime<Utc> {
+        time.duration_trunc(self.duration())
+            .expect("bucket widths divide a day")
+    }
+}
+
+impl fmt::Display for Resolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Resolution::ALL
+            .into_iter()
+            .find(|resolution| resolution.as_str() == s)
+            .ok_or_else(|| format!("unknown candle resolution '{}'", s))
+    }
+}
+
+/// A priced trade at a point in the chain, the input to a candle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    pub token_id: String,
+    pub time: DateTime<Utc>,
+    pub block_number: i64,
+    pub log_index: i64,
+    pub price: f64,
+    pub size: i64,
+    pub notional: i64,
+}
+
+impl Tick {
+    /// Tick of a decoded fill, if it is a trade
+    pub fn from_fill(fill: &Fill) -> Option<Self> {
+        let trade = fill.trade()?;
+        Some(Self {
+            token_id: trade.token_id.to_string(),
+            time: fill.block_time,
+            block_number: fill.block_number as i64,
+            log_index: fill.log_index as i64,
+            price: trade.price,
+            size: trade.size,
+            notional: trade.notional,
+        })
+    }
+
+    /// Tick of a stored fill, if it is a trade
+    pub fn from_row(fill: &FillRow) -> Option<Self> {
+        Some(Self {
+            token_id: fill.token_id.clone()?,
+            time: fill.block_time,
+            block_number: fill.block_number,
+            log_index: fill.log_index,
+            price: fill.price?,
+            size: fill.size?,
+            notional: fill.notional?,
+        })
+    }
+}
+
+impl Candle {
+    /// Candle of a single tick
+    pub fn from_tick(tick: &Tick, resolution: Resolution) -> Self {
+        Self {
+            token_id: tick.token_id.clone(),
+            resolution: resolution.as_str().to_string(),
+            bucket_start: resolution.bucket_start(tick.time),
+            open: tick.price,
+            high: tick.price,
+            low: tick.price,
+            close: tick.price,
+            volume: tick.size,
+            notional: tick.notional,
+            trades: 1,
+            first_block: tick.block_number,
+            first_log_index: tick.log_index,
+            last_block: tick.block_number,
+            last_log_index: tick.log_index,
+        }
+    }
+
+    /// Fold `other`, a candle for the same token and bucket, into this one
+    pub fn merge(&mut self, other: &Candle) {
+        if other.first_position() < self.first_position() {
+            self.open = other.open;
+            (self.first_block, self.first_log_index) = other.first_position();
+        }
+        if other.last_position() > self.last_position() {
+            self.close = other.close;
+            (self.last_block, self.last_log_index) = other.last_position();
+        }
+        self.high = self.high.max(other.high);
+        self.low = self.low.min(other.low);
+        self.volume += other.volume;
+        self.notional += other.notional;
+        self.trades += other.trades;
+    }
+
+    /// Store key: (token ID, resolution, bucket start)
+    pub fn key(&self) -> (String, String, DateTime<Utc>) {
+        (
+            self.token_id.clone(),
+            self.resolution.clone(),
+            self.bucket_start,
+        )
+    }
+
+    fn first_position(&self) -> (i64, i64) {
+        (self.first_block, self.first_log_index)
+    }
+
+    fn last_position(&self) -> (i64, i64) {
+        (self.last_block, self.last_log_index)
+    }
+}
+
+/// Build the 1m, 1h and 1d candles of `ticks`, in key order
+pub fn aggregate<'a>(ticks: impl IntoIterator<Item = &'a Tick>) -> Vec<Candle> {
+    let mut candles: BTreeMap<(String, String, DateTime<Utc>), Candle> = BTreeMap::new();
+    for tick in ticks {
+        for resolution in Resolution::ALL {
+            let candle = Candle::from_tick(tick, resolution);
+            candles
+                .entry(candle.key())
+                .and_modify(|existing| existing.merge(&candle))
+                .or_insert(candle);
+        }
+    }
+    candles.into_values().collect()
+}
+
+/// Recompute the candles of every day holding a stored fill between
+/// `from_block` and `to_block` (inclusive)
+///
+/// Whole days are rebuilt from all of their stored fills, so buckets that
+/// straddle the range edges come out complete. Returns the candles written.
+pub async fn rebuild(
+    store: &dyn MarketStore,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<usize> {
+    let Some((first, last)) = store.get_fill_time_range(from_block, to_block).await? else {
+        return Ok(0);
+    };
+
+    let mut written = 0;
+    let mut day = Resolution::Day.bucket_start(first);
+    while day <= last {
+        let next = day + Resolution::Day.duration();
+        let fills = store.get_fills_between(day, next).await?;
+        let ticks: Vec<Tick> = fills.iter().filter_map(Tick::from_row).collect();
+        let candles = aggregate(&ticks);
+        store.replace_candles(&candles).await?;
+        info!(
+            "Rebuilt {} candles from {} fills on {}",
+            candles.len(),
+            fills.len(),
+            day.date_naive()
+        );
+        written += candles.len();
+        day = next;
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::contracts::ctf_exchange::CTFExchangeEvents;
+    use crate::polymarket::events::PolymarketEvent;
+    use crate::testing;
+    use chrono::TimeZone;
+    use ethers::types::Address;
+
+    fn tick(seconds: i64, block: i64, price: f64) -> Tick {
+        Tick {
+            token_id: "7".to_string(),
+            time: testing::time(seconds),
+            block_number: block,
+            log_index: 0,
+            price,
+            size: 10,
+            notional: (price * 10.0) as i64,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_buckets() {
+        // 1_700_000_000 is 22:13:20 UTC; +40s crosses into the next minute
+        let ticks = [tick(0, 1, 0.5), tick(10, 2, 0.7), tick(40, 3, 0.4)];
+        let candles = aggregate(&ticks);
+
+        let minutes: Vec<_> = candles.iter().filter(|c| c.resolution == "1m").collect();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(minutes[0].trades, 2);
+        assert_eq!((minutes[0].open, minutes[0].close), (0.5, 0.7));
+
+        let hour = candles.iter().find(|c| c.resolution == "1h").unwrap();
+        assert_eq!(
+            hour.bucket_start,
+            Utc.with_ymd_and_hms(2023, 11, 14, 22, 0, 0).unwrap()
+        );
+        assert_eq!(
+            (hour.open, hour.high, hour.low, hour.close),
+            (0.5, 0.7, 0.4, 0.4)
+        );
+        assert_eq!(hour.volume, 30);
+    }
+
+    #[test]
+    fn test_merge_is_order_independent() {
+        let early = aggregate(&[tick(0, 1, 0.5)]);
+        let late = aggregate(&[tick(5, 2, 0.6), tick(6, 3, 0.3)]);
+
+        let mut forward = early[0].clone();
+        forward.merge(&late[0]);
+        let mut backward = late[0].clone();
+        backward.merge(&early[0]);
+
+        assert_eq!(forward, backward);
+        assert_eq!((forward.open, forward.close, forward.trades), (0.5, 0.3, 3));
+        assert_eq!("1h".parse(), Ok(Resolution::Hour));
+    }
+
+    #[test]
+    fn test_candles_of_a_decoded_fill() {
+        let log = testing::order_filled(Address::repeat_byte(0xee), 3);
+        let PolymarketEvent::Exchange(CTFExchangeEvents::OrderFilledFilter(event)) =
+            PolymarketEvent::decode(&log).unwrap()
+        else {
+            panic!("expected OrderFilled");
+        };
+        let fill = Fill::from_event(event, &log, testing::time(0)).unwrap();
+
+        let candles = aggregate(&[Tick::from_fill(&fill).unwrap()]);
+        assert_eq!(candles.len(), 3);
+        let day = candles.iter().find(|c| c.resolution == "1d").unwrap();
+        assert_eq!(day.token_id, "7");
+        assert!((day.close - 0.3).abs() < 1e-12);
+        assert_eq!((day.volume, day.notional), (10_000_000, 3_000_000));
+        assert_eq!((day.first_block, day.first_log_index), (5, 3));
+    }
+}
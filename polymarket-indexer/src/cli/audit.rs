@@ -0,0 +1,55 @@
+// Audit - report data quality issues in the indexed markets
+
+use crate::config::Config;
+use crate::db::{anomalies, create_pool, markets};
+use clap::Args;
+use eyre::{eyre, Result};
+use tracing::{info, warn};
+
+#[derive(Debug, Args)]
+pub struct AuditArgs {
+    /// Exit with a failure code if any issue is found
+    #[arg(long)]
+    pub strict: bool,
+}
+
+pub async fn run(args: AuditArgs, config: &Config) -> Result<()> {
+    let db_pool = create_pool(&config.database).await?;
+
+    let total = markets::count_markets(&db_pool).await?;
+    let without_metadata = markets::count_markets_without_metadata(&db_pool).await?;
+    let without_tags = markets::count_markets_without_tags(&db_pool).await?;
+    let anomaly_counts = anomalies::count_anomalies_by_kind(&db_pool).await?;
+
+    info!("Audit complete!");
+    info!("  Markets: {}", total);
+    info!("  Markets without metadata: {}", without_metadata);
+    info!("  Markets with metadata but no tags: {}", without_tags);
+    for (kind, count) in &anomaly_counts {
+        info!("  Data anomalies ({}): {}", kind, count);
+    }
+
+    let issues = without_metadata + without_tags;
+    if issues > 0 {
+        warn!(
+            "Found {} markets with issues (run `enrich` to retry metadata)",
+            issues
+        );
+        if args.strict {
+            return Err(eyre!("Audit found {} markets with issues", issues));
+        }
+    }
+
+    let anomaly_total: i64 = anomaly_counts.iter().map(|(_, count)| count).sum();
+    if anomaly_total > 0 {
+        warn!(
+            "Found {} recorded data anomalies (see the data_anomalies table)",
+            anomaly_total
+        );
+        if args.strict {
+            return Err(eyre!("Audit found {} data anomalies", anomaly_total));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+// Backfill - index historical market events and enrich with metadata
+
+use crate::blocks::BlockRecorder;
+use crate::cli::{StoreArgs, UsageError};
+use crate::client::evm::HttpClient;
+use crate::config::Config;
+use crate::ingest::IngestStats;
+use crate::metrics;
+use crate::registry::EventRegistry;
+use crate::shutdown::Shutdown;
+use crate::store::MarketStore;
+use clap::{ArgGroup, Args};
+use eyre::Result;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Polygon block time: ~2 seconds
+const POLYGON_BLOCK_TIME_SECS: u64 = 2;
+
+/// Checkpoint recording the end of the last completed backfill
+pub const CHECKPOINT: &str = "backfill";
+
+#[derive(Debug, Args)]
+#[command(group(
+    ArgGroup::new("range")
+        .required(true)
+        .args(["days", "hours", "minutes", "from_block"])
+))]
+pub struct BackfillArgs {
+    /// Index the last N days of blocks
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub days: Option<u64>,
+
+    /// Index the last N hours of blocks
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub hours: Option<u64>,
+
+    /// Index the last N minutes of blocks
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub minutes: Option<u64>,
+
+    /// First block to index (inclusive)
+    #[arg(long, value_name = "BLOCK")]
+    pub from_block: Option<u64>,
+
+    /// Last block to index (inclusive, defaults to the chain head)
+    #[arg(long, value_name = "BLOCK", requires = "from_block")]
+    pub to_block: Option<u64>,
+
+    /// Start after the `backfill` checkpoint when it falls inside the range,
+    /// to continue an interrupted run over the same range
+    #[arg(long)]
+    pub resume: bool,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+impl BackfillArgs {
+    /// Length of the time-based lookback window, if one was given
+    fn lookback_secs(&self) -> Result<Option<u64>, UsageError> {
+        let lookback = [
+            ("--days", self.days, 86_400),
+            ("--hours", self.hours, 3_600),
+            ("--minutes", self.minutes, 60),
+        ]
+        .into_iter()
+        .find_map(|(flag, n, unit)| Some((flag, n?, unit)));
+        let Some((flag, n, unit)) = lookback else {
+            return Ok(None);
+        };
+
+        n.checked_mul(unit)
+            .map(Some)
+            .ok_or_else(|| UsageError(format!("{} {} is too large", flag, n)))
+    }
+
+    /// Resolve the arguments to an inclusive block range
+    ///
+    /// Ranges ending at the chain head stop `confirmations` blocks short of it.
+    async fn block_range(&self, client: &HttpClient, confirmations: u64) -> Result<(u64, u64)> {
+        let confirmed_head = || async {
+            let head = client.get_block_number().await?;
+            Ok::<_, eyre::Report>(head.saturating_sub(confirmations))
+        };
+
+        if let Some(seconds) = self.lookback_secs()? {
+            let current_block = confirmed_head().await?;
+
+            // Estimate blocks based on block time
+            let blocks_to_go_back = seconds / POLYGON_BLOCK_TIME_SECS;
+            let from_block = current_block.saturating_sub(blocks_to_go_back);
+
+            return Ok((from_block, current_block));
+        }
+
+        // The arg group guarantees --from-block when no lookback is given
+        let from_block = self.from_block.unwrap_or_default();
+        let to_block = match self.to_block {
+            Some(block) => block,
+            None => confirmed_head().await?,
+        };
+
+        if from_block > to_block {
+            return Err(UsageError(format!(
+                "--from-block ({}) must not be greater than --to-block ({})",
+                from_block, to_block
+            ))
+            .into());
+        }
+
+        Ok((from_block, to_block))
+    }
+}
+
+pub async fn run(args: BackfillArgs, config: &Config) -> Result<()> {
+    info!("Market Backfill starting...");
+
+    metrics::spawn_from_config(&config.metrics);
+    let shutdown = Shutdown::from_config(&config.indexer);
+    let store = args.store.open(config).await?;
+    let stats = backfill(&args, config, store, &shutdown).await?;
+
+    // Summary
+    if shutdown.is_requested() {
+        info!("Backfill stopped early; rerun with --resume to continue from the checkpoint");
+    } else {
+        info!("Backfill complete!");
+    }
+    stats.log_summary();
+
+    Ok(())
+}
+
+/// Index the requested range into `store`
+///
+/// The range is fetched `indexer.chunk_blocks` at a time, checkpointing after
+/// each chunk; with `--resume` blocks up to the checkpoint are skipped. Once
+/// `shutdown` is requested no further chunks are started.
+pub async fn backfill(
+    args: &BackfillArgs,
+    config: &Config,
+    store: Arc<dyn MarketStore>,
+    shutdown: &Shutdown,
+) -> Result<IngestStats> {
+    // Initialize clients
+    let evm_client = HttpClient::from_config(&config.rpc, &config.fixtures).await?;
+    let (mut from_block, to_block) = args
+        .block_range(&evm_client, config.indexer.confirmations)
+        .await?;
+    if args.resume {
+        match store.get_checkpoint(CHECKPOINT).await? {
+            Some(checkpoint) if checkpoint >= from_block => {
+                info!("Resuming after checkpoint at block {}", checkpoint);
+                from_block = checkpoint + 1;
+            }
+            _ => info!("No checkpoint inside the range; starting from its first block"),
+        }
+    }
+
+    info!("Backfill range: blocks {} to {}", from_block, to_block);
+
+    let registry = EventRegistry::from_config(config, store.clone());
+    let blocks = BlockRecorder::new(store.clone(), config.indexer.block_concurrency);
+    info!("Fetching {} events...", registry.event_names().join(", "));
+
+    let mut stats = IngestStats::default();
+    let mut last_indexed = None;
+    let mut chunk_start = from_block;
+    while chunk_start <= to_block && !shutdown.is_requested() {
+        let chunk_end = chunk_start
+            .saturating_add(config.indexer.chunk_blocks - 1)
+            .min(to_block);
+
+        // Fetch every registered event in the chunk in one sweep
+        let filter = registry
+            .filter()
+            .from_block(chunk_start)
+            .to_block(chunk_end);
+        let chunk = async {
+            let logs = evm_client.get_logs(&filter).await?;
+            metrics::LOGS_FETCHED.inc_by(logs.len() as u64);
+            info!(
+                "Found {} logs in blocks {} to {}",
+                logs.len(),
+                chunk_start,
+                chunk_end
+            );
+            let mut stats = blocks.record(&evm_client, &logs).await?;
+            stats += registry.dispatch(&logs).await?;
+            Ok::<_, eyre::Report>(stats)
+        };
+
+        let Some(chunk_stats) = shutdown.drain(chunk).await else {
+            warn!(
+                "Shutdown grace period elapsed; blocks {} to {} will be redone on the next run",
+                chunk_start, chunk_end
+            );
+            break;
+        };
+        stats += chunk_stats?;
+
+        store.set_checkpoint(CHECKPOINT, chunk_end).await?;
+        last_indexed = Some(chunk_end);
+        chunk_start = chunk_end + 1;
+    }
+
+    if let Some(block) = last_indexed {
+        let head = evm_client.get_block_number().await?;
+        metrics::record_progress(head, block);
+    }
+
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{Cli, Command};
+    use clap::Parser;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(["polymarket-indexer", "backfill"].iter().chain(args))
+    }
+
+    #[test]
+    fn test_lookback_secs() {
+        let Command::Backfill(args) = parse(&["--hours", "6"]).unwrap().command else {
+            panic!("expected backfill");
+        };
+        assert_eq!(args.lookback_secs().unwrap(), Some(6 * 3600));
+
+        let Command::Backfill(args) = parse(&["--days", "300000000000000"]).unwrap().command else {
+            panic!("expected backfill");
+        };
+        assert!(args.lookback_secs().is_err());
+    }
+
+    #[test]
+    fn test_range_is_required_and_exclusive() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["--days", "1", "--from-block", "5"]).is_err());
+        assert!(parse(&["--to-block", "5"]).is_err());
+        assert!(parse(&["--days", "0"]).is_err());
+        assert!(parse(&["--from-block", "5", "--to-block", "10"]).is_ok());
+        assert!(parse(&["--days", "1", "--resume"]).is_ok());
+    }
+}
@@ -0,0 +1,40 @@
+// Candles - rebuild OHLCV candles from the stored exchange fills
+
+use crate::candles;
+use crate::cli::UsageError;
+use crate::config::Config;
+use crate::store;
+use clap::Args;
+use eyre::Result;
+use tracing::info;
+
+#[derive(Debug, Args)]
+pub struct CandlesArgs {
+    /// First block whose fills are rebuilt
+    #[arg(long, value_name = "BLOCK")]
+    pub from_block: u64,
+
+    /// Last block whose fills are rebuilt (defaults to the latest stored fill)
+    #[arg(long, value_name = "BLOCK")]
+    pub to_block: Option<u64>,
+}
+
+pub async fn run(args: CandlesArgs, config: &Config) -> Result<()> {
+    // Block numbers are stored as BIGINT
+    let to_block = args.to_block.unwrap_or(i64::MAX as u64);
+    if args.from_block > to_block {
+        return Err(UsageError(format!(
+            "--from-block ({}) must not be greater than --to-block ({})",
+            args.from_block, to_block
+        ))
+        .into());
+    }
+
+    let store = store::open(&config.database).await?;
+    let written = candles::rebuild(&*store, args.from_block, to_block).await?;
+
+    info!("Candle rebuild complete!");
+    info!("  Candles written: {}", written);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+// Enrich - fetch Gamma metadata for markets indexed without it
+
+use crate::config::Config;
+use crate::ingest::{IngestStats, MarketIngester};
+use crate::shutdown::Shutdown;
+use crate::store;
+use clap::Args;
+use eyre::Result;
+use tracing::{info, warn};
+
+#[derive(Debug, Args)]
+pub struct EnrichArgs {
+    /// Maximum number of markets to enrich
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(i64).range(1..))]
+    pub limit: i64,
+}
+
+pub async fn run(args: EnrichArgs, config: &Config) -> Result<()> {
+    let shutdown = Shutdown::from_config(&config.indexer);
+    let store = store::open(&config.database).await?;
+    let pending = store.get_markets_without_metadata(args.limit).await?;
+    info!("Enriching {} markets without metadata", pending.len());
+
+    let ingester = MarketIngester::from_config(config, store);
+    let mut stats = IngestStats::default();
+    for market in &pending {
+        if shutdown.is_requested() {
+            break;
+        }
+        let Some(market_stats) = shutdown.drain(ingester.enrich(&market.condition_id)).await else {
+            warn!(
+                "Shutdown grace period elapsed while enriching {}",
+                market.condition_id
+            );
+            break;
+        };
+        stats += market_stats?;
+    }
+
+    if shutdown.is_requested() {
+        info!("Enrich stopped early");
+    } else {
+        info!("Enrich complete!");
+    }
+    stats.log_summary();
+
+    Ok(())
+}
@@ -0,0 +1,256 @@
+// Export - write markets joined with tags as NDJSON, CSV or Parquet
+//
+// Incremental exports: with --state-file, only markets updated since the
+// previous successful run are written. The state file holds the newest
+// `updated_at` exported, then the markets exported within `OVERLAP_SECS` of
+// it. `updated_at` is when the writing transaction started, not when it
+// committed, so each run re-reads that window and skips the markets already
+// exported with the same `updated_at`.
+
+use crate::cli::UsageError;
+use crate::config::Config;
+use crate::db::create_pool;
+use crate::db::markets::{self, ExportFilter};
+use crate::export::{self, ExportFormat};
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use eyre::{eyre, Result, WrapErr};
+use futures::TryStreamExt;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// How far before the watermark an incremental export re-reads, to catch
+/// markets whose transaction committed after the previous run read
+const OVERLAP_SECS: i64 = 600;
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Output file (defaults to stdout; required for parquet)
+    #[arg(long, short, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Output format (defaults to the --output extension, else ndjson)
+    #[arg(long, value_enum)]
+    pub format: Option<ExportFormat>,
+
+    /// Only markets registered at or after this block
+    #[arg(long, value_name = "BLOCK")]
+    pub from_block: Option<i64>,
+
+    /// Only markets registered at or before this block
+    #[arg(long, value_name = "BLOCK")]
+    pub to_block: Option<i64>,
+
+    /// Only markets with this tag slug
+    #[arg(long, value_name = "SLUG")]
+    pub tag: Option<String>,
+
+    /// Only markets updated at or after this time (RFC 3339)
+    #[arg(long, value_name = "TIME", conflicts_with = "state_file")]
+    pub updated_since: Option<DateTime<Utc>>,
+
+    /// Watermark file for incremental exports (read before, written after)
+    #[arg(long, value_name = "PATH")]
+    pub state_file: Option<PathBuf>,
+}
+
+impl ExportArgs {
+    fn resolve_format(&self) -> Result<ExportFormat> {
+        let format = self
+            .format
+            .or_else(|| self.output.as_deref().and_then(ExportFormat::from_path))
+            .unwrap_or(ExportFormat::Ndjson);
+
+        if format == ExportFormat::Parquet && self.output.is_none() {
+            return Err(UsageError("parquet export requires --output".to_string()).into());
+        }
+        if let (Some(from), Some(to)) = (self.from_block, self.to_block) {
+            if from > to {
+                return Err(UsageError(format!(
+                    "--from-block ({}) must not be greater than --to-block ({})",
+                    from, to
+                ))
+                .into());
+            }
+        }
+
+        Ok(format)
+    }
+}
+
+pub async fn run(args: ExportArgs, config: &Config) -> Result<()> {
+    let format = args.resolve_format()?;
+
+    let state = match args.state_file {
+        Some(ref path) => ExportState::read(path)?,
+        None => None,
+    };
+    let updated_since = match state {
+        Some(ref state) => Some(state.watermark - Duration::seconds(OVERLAP_SECS)),
+        None => args.updated_since,
+    };
+    if let Some(since) = updated_since {
+        info!("Exporting markets updated since {}", since);
+    }
+
+    let filter = ExportFilter {
+        from_block: args.from_block,
+        to_block: args.to_block,
+        tag_slug: args.tag.clone(),
+        updated_since,
+    };
+
+    // Write to a temporary file so a failed export never leaves a truncated file
+    let partial_path = args.output.as_deref().map(partial_path);
+    let output: Box<dyn Write + Send> = match partial_path {
+        Some(ref path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+    let mut writer = export::writer_for(format, output)?;
+
+    let db_pool = create_pool(&config.database).await?;
+    let mut rows = markets::stream_export_rows(&db_pool, &filter);
+    let mut exported = 0;
+    let mut next_state = state.clone().unwrap_or_default();
+
+    while let Some(row) = rows.try_next().await? {
+        let key = (row.condition_id.clone(), row.updated_at);
+        if state.as_ref().is_some_and(|s| s.exported.contains(&key)) {
+            continue;
+        }
+        writer.write_row(&row)?;
+        next_state.record(key);
+        exported += 1;
+    }
+    writer.finish()?;
+
+    if let (Some(partial), Some(output)) = (partial_path, args.output.as_deref()) {
+        fs::rename(&partial, output)?;
+    }
+    if let Some(path) = args.state_file.as_deref() {
+        if !next_state.exported.is_empty() {
+            next_state.prune();
+            next_state.write(path)?;
+        }
+    }
+
+    info!("✓ Exported {} markets as {:?}", exported, format);
+
+    Ok(())
+}
+
+fn partial_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+/// What previous incremental exports wrote
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ExportState {
+    /// Newest `updated_at` exported
+    watermark: DateTime<Utc>,
+    /// (condition ID, `updated_at`) of the markets exported within
+    /// `OVERLAP_SECS` of the watermark
+    exported: BTreeSet<(String, DateTime<Utc>)>,
+}
+
+impl ExportState {
+    /// Note a market as exported, moving the watermark up to it
+    fn record(&mut self, key: (String, DateTime<Utc>)) {
+        self.watermark = self.watermark.max(key.1);
+        self.exported.insert(key);
+    }
+
+    /// Forget the markets the next run no longer re-reads
+    fn prune(&mut self) {
+        let since = self.watermark - Duration::seconds(OVERLAP_SECS);
+        self.exported.retain(|(_, updated_at)| *updated_at >= since);
+    }
+
+    /// Read the state a previous run saved, if any
+    fn read(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path)?;
+        Self::parse(&contents)
+            .map(Some)
+            .wrap_err_with(|| format!("invalid export state in {}", path.display()))
+    }
+
+    /// The watermark line, then one `<condition ID> <updated_at>` line per
+    /// exported market (a watermark alone is a state with no markets)
+    fn parse(contents: &str) -> Result<Self> {
+        let mut lines = contents.lines().map(str::trim).filter(|l| !l.is_empty());
+        let watermark = parse_time(lines.next().ok_or_else(|| eyre!("missing watermark"))?)?;
+        let exported = lines
+            .map(|line| {
+                let (condition_id, updated_at) = line
+                    .split_once(' ')
+                    .ok_or_else(|| eyre!("invalid exported market '{}'", line))?;
+                Ok((condition_id.to_string(), parse_time(updated_at)?))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            watermark,
+            exported,
+        })
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        let mut contents = format!("{}\n", self.watermark.to_rfc3339());
+        for (condition_id, updated_at) in &self.exported {
+            contents.push_str(&format!("{} {}\n", condition_id, updated_at.to_rfc3339()));
+        }
+
+        let tmp = partial_path(path);
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+fn parse_time(text: &str) -> Result<DateTime<Utc>> {
+    Ok(DateTime::parse_from_rfc3339(text)?.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn test_state_keeps_the_overlap_window() {
+        let mut state = ExportState::default();
+        state.record(("0xaa".to_string(), time(0)));
+        state.record(("0xbb".to_string(), time(OVERLAP_SECS + 5)));
+        state.record(("0xcc".to_string(), time(10)));
+        state.prune();
+
+        assert_eq!(state.watermark, time(OVERLAP_SECS + 5));
+        let kept: Vec<&str> = state.exported.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(kept, ["0xbb", "0xcc"]);
+
+        let path = std::env::temp_dir().join(format!("export-state-{}", std::process::id()));
+        state.write(&path).unwrap();
+        assert_eq!(ExportState::read(&path).unwrap(), Some(state));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_watermark_only_state_parses() {
+        let state = ExportState::parse("2023-11-14T22:13:20+00:00\n").unwrap();
+        assert_eq!(state.watermark, time(0));
+        assert!(state.exported.is_empty());
+        assert!(ExportState::parse("0xaa 2023-11-14T22:13:20+00:00\n").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+// Lookup - print a single market, its outcome tokens, tags, UMA question and
+// status history
+
+use crate::cli::UsageError;
+use crate::config::Config;
+use crate::db::{
+    anomalies, create_pool, market_status, market_tags, markets, tokens, uma_questions,
+};
+use clap::{ArgGroup, Args};
+use eyre::{eyre, Result};
+
+#[derive(Debug, Args)]
+#[command(group(ArgGroup::new("key").required(true).args(["condition_id", "token_id"])))]
+pub struct LookupArgs {
+    /// Condition ID (hex string with 0x prefix)
+    pub condition_id: Option<String>,
+
+    /// Outcome token ID (decimal)
+    #[arg(long, value_name = "ID")]
+    pub token_id: Option<String>,
+}
+
+pub async fn run(args: LookupArgs, config: &Config) -> Result<()> {
+    if let Some(ref condition_id) = args.condition_id {
+        validate_condition_id(condition_id)?;
+    }
+    if let Some(ref token_id) = args.token_id {
+        if token_id.is_empty() || !token_id.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(UsageError(format!(
+                "invalid token ID '{}': expected a decimal number",
+                token_id
+            ))
+            .into());
+        }
+    }
+
+    let db_pool = create_pool(&config.database).await?;
+    let market = match (&args.condition_id, &args.token_id) {
+        (Some(condition_id), _) => {
+            markets::get_market_by_condition_id(&db_pool, condition_id).await?
+        }
+        (None, Some(token_id)) => match tokens::get_by_token_id(&db_pool, token_id).await? {
+            Some(token) => {
+                markets::get_market_by_condition_id(&db_pool, &token.condition_id).await?
+            }
+            None => None,
+        },
+        (None, None) => None,
+    };
+
+    let market = market.ok_or_else(|| eyre!("Market not found"))?;
+    market.display();
+
+    for token in tokens::get_tokens_for_market(&db_pool, &market.condition_id).await? {
+        println!(
+            "  Outcome {} ({}): {}",
+            token.outcome_index,
+            token.outcome_label.as_deref().unwrap_or("?"),
+            token.token_id
+        );
+    }
+
+    let tags = market_tags::get_tags_for_market(&db_pool, &market.condition_id).await?;
+    if !tags.is_empty() {
+        let labels: Vec<&str> = tags
+            .iter()
+            .map(|t| t.label.as_deref().unwrap_or(&t.pm_tag_id))
+            .collect();
+        println!("  Tags: {}", labels.join(", "));
+    }
+
+    if let Some(question) =
+        uma_questions::get_question_by_condition_id(&db_pool, &market.condition_id).await?
+    {
+        question.display();
+    }
+
+    let transitions = market_status::get_status_transitions(&db_pool, &market.condition_id).await?;
+    for t in &transitions {
+        println!(
+            "  {} -> {} ({}, {})",
+            t.from_status, t.to_status, t.source, t.transitioned_at
+        );
+    }
+    for anomaly in anomalies::get_anomalies_for_market(&db_pool, &market.condition_id).await? {
+        println!("  Anomaly [{}]: {}", anomaly.kind, anomaly.detail);
+    }
+
+    Ok(())
+}
+
+/// Check that a condition ID is a 0x-prefixed 32-byte hex string
+fn validate_condition_id(condition_id: &str) -> Result<()> {
+    let valid = condition_id
+        .strip_prefix("0x")
+        .is_some_and(|hex| hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit()));
+
+    if !valid {
+        return Err(UsageError(format!(
+            "invalid condition ID '{}': expected 0x followed by 64 hex characters",
+            condition_id
+        ))
+        .into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+// Migrate - apply the SQL migrations bundled with the crate
+//
+// Markets stored before the outcome_tokens table existed get their outcome
+// tokens filled in afterwards; the token order needs the Conditional Tokens
+// math, so it cannot be done in SQL.
+
+use crate::config::{Config, DatabaseBackend};
+use crate::db::{create_pool, tokens};
+use clap::Args;
+use eyre::Result;
+use tracing::info;
+
+#[derive(Debug, Args)]
+pub struct MigrateArgs {}
+
+pub async fn run(_args: MigrateArgs, config: &Config) -> Result<()> {
+    let filled = match config.database.backend()? {
+        DatabaseBackend::Postgres => {
+            let db_pool = create_pool(&config.database).await?;
+            sqlx::migrate!("./migrations").run(&db_pool).await?;
+            tokens::backfill_outcome_tokens(&db_pool).await?
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => {
+            let store = crate::store::SqliteStore::connect(&config.database).await?;
+            store.migrate().await?;
+            store.backfill_outcome_tokens().await?
+        }
+    };
+    info!("✓ Migrations applied");
+    if filled > 0 {
+        info!("✓ Filled in outcome tokens for {} markets", filled);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,190 @@
+// Command-line interface for the polymarket-indexer binary
+//
+// Each subcommand lives in its own module with an `Args` struct and a `run`
+// function, so thin alias binaries (e.g. market_backfill) can reuse them.
+
+pub mod audit;
+pub mod backfill;
+pub mod candles;
+pub mod enrich;
+pub mod export;
+pub mod lookup;
+pub mod market_stats;
+pub mod migrate;
+pub mod search;
+pub mod stream;
+pub mod wallets;
+
+use crate::config::{Config, ConfigError};
+use crate::error::IndexerError;
+use crate::store::{self, MarketStore, MemoryStore};
+use clap::{Parser, Subcommand};
+use eyre::Result;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use tracing::{error, info, Level};
+
+/// Process exit codes shared by all indexer binaries
+pub mod exit_code {
+    /// Command completed successfully
+    pub const SUCCESS: u8 = 0;
+    /// Command failed at runtime (RPC, database, API errors)
+    pub const FAILURE: u8 = 1;
+    /// Invalid arguments (same code clap uses for parse errors)
+    pub const USAGE: u8 = 2;
+    /// Invalid or missing configuration (sysexits.h EX_CONFIG)
+    pub const CONFIG: u8 = 78;
+}
+
+/// Index Polymarket markets from Polygon into PostgreSQL
+#[derive(Debug, Parser)]
+#[command(name = "polymarket-indexer", version, about)]
+pub struct Cli {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Flags shared by every binary for locating and overriding the config file
+#[derive(Debug, clap::Args)]
+pub struct ConfigArgs {
+    /// TOML config file (defaults to ./indexer.toml when present)
+    #[arg(
+        long = "config",
+        global = true,
+        value_name = "PATH",
+        env = "INDEXER_CONFIG"
+    )]
+    pub path: Option<PathBuf>,
+
+    /// Bypass the Gamma response cache for this run
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+}
+
+impl ConfigArgs {
+    /// Load and validate the layered configuration
+    pub fn load(&self) -> Result<Config> {
+        let mut config = Config::load(self.path.as_deref())?;
+        if self.no_cache {
+            config.cache.dir = None;
+        }
+        Ok(config)
+    }
+}
+
+/// Flags shared by the indexing commands for choosing where markets are written
+#[derive(Debug, clap::Args)]
+pub struct StoreArgs {
+    /// Index into memory instead of the database (nothing is persisted)
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl StoreArgs {
+    /// Open the configured store, or an empty in-memory store for dry runs
+    pub async fn open(&self, config: &Config) -> Result<Arc<dyn MarketStore>> {
+        if self.dry_run {
+            info!("Dry run: markets will not be persisted");
+            return Ok(Arc::new(MemoryStore::new()));
+        }
+        store::open(&config.database).await
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Index historical TokenRegistered events over a block range
+    Backfill(backfill::BackfillArgs),
+    /// Follow new TokenRegistered events from the chain head
+    Stream(stream::StreamArgs),
+    /// Fetch Gamma metadata for markets indexed without it
+    Enrich(enrich::EnrichArgs),
+    /// Apply pending database migrations and fill in missing outcome tokens
+    Migrate(migrate::MigrateArgs),
+    /// Look up a single market by condition ID or token ID
+    Lookup(lookup::LookupArgs),
+    /// Search market questions and slugs
+    Search(search::SearchArgs),
+    /// Export markets with tags as NDJSON, CSV or Parquet
+    Export(export::ExportArgs),
+    /// Report data quality issues in the indexed markets
+    Audit(audit::AuditArgs),
+    /// Rebuild OHLCV candles from the stored exchange fills
+    Candles(candles::CandlesArgs),
+    /// Rebuild daily open interest and volume per market
+    MarketStats(market_stats::MarketStatsArgs),
+    /// Rebuild per-wallet positions, realized PnL and win rate
+    Wallets(wallets::WalletsArgs),
+}
+
+impl Cli {
+    /// Run the selected subcommand
+    pub async fn run(self) -> Result<()> {
+        let config = self.config.load()?;
+
+        match self.command {
+            Command::Backfill(args) => backfill::run(args, &config).await,
+            Command::Stream(args) => stream::run(args, &config).await,
+            Command::Enrich(args) => enrich::run(args, &config).await,
+            Command::Migrate(args) => migrate::run(args, &config).await,
+            Command::Lookup(args) => lookup::run(args, &config).await,
+            Command::Search(args) => search::run(args, &config).await,
+            Command::Export(args) => export::run(args, &config).await,
+            Command::Audit(args) => audit::run(args, &config).await,
+            Command::Candles(args) => candles::run(args, &config).await,
+            Command::MarketStats(args) => market_stats::run(args, &config).await,
+            Command::Wallets(args) => wallets::run(args, &config).await,
+        }
+    }
+}
+
+/// Invalid argument combination that clap cannot express declaratively
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// Load .env and install the tracing subscriber
+pub fn init() {
+    dotenv::dotenv().ok();
+    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+}
+
+/// Whether a failure is a configuration problem, directly or via `IndexerError`
+fn is_config_error(e: &eyre::Report) -> bool {
+    e.downcast_ref::<ConfigError>().is_some()
+        || matches!(
+            e.downcast_ref::<IndexerError>(),
+            Some(IndexerError::Config(_))
+        )
+}
+
+/// Log a command failure and map it to a process exit code
+pub fn exit(result: Result<()>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::from(exit_code::SUCCESS),
+        Err(e) if e.downcast_ref::<UsageError>().is_some() => {
+            eprintln!("error: {}", e);
+            ExitCode::from(exit_code::USAGE)
+        }
+        Err(e) if is_config_error(&e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(exit_code::CONFIG)
+        }
+        Err(e) => {
+            error!("{:?}", e);
+            ExitCode::from(exit_code::FAILURE)
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// Search - find markets by question text
+
+use crate::config::Config;
+use crate::db::{create_pool, markets};
+use clap::Args;
+use eyre::Result;
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Search terms, e.g. "Fed rate cut" (supports "quoted phrases" and -exclusions)
+    #[arg(required = true, num_args = 1..)]
+    pub query: Vec<String>,
+
+    /// Maximum number of results
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(i64).range(1..=500))]
+    pub limit: i64,
+}
+
+pub async fn run(args: SearchArgs, config: &Config) -> Result<()> {
+    let db_pool = create_pool(&config.database).await?;
+    let query = args.query.join(" ");
+
+    let results = markets::search_markets(&db_pool, &query, args.limit).await?;
+    if results.is_empty() {
+        println!("No markets match {:?}", query);
+    }
+
+    for result in results {
+        println!(
+            "{:.3}  {}  {}",
+            result.rank,
+            result.condition_id,
+            result.question.as_deref().unwrap_or("(no question)")
+        );
+    }
+
+    Ok(())
+}
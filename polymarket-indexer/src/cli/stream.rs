@@ -0,0 +1,116 @@
+// Stream - follow new market events as blocks are produced
+//
+// Subscribes to new block headers and, for each head, fetches the logs of
+// every block that has reached the configured confirmation depth. Progress is
+// checkpointed after each range so a restarted stream resumes where it stopped.
+// SIGINT/SIGTERM ends the stream once the range in flight is done.
+
+use crate::blocks::BlockRecorder;
+use crate::cli::StoreArgs;
+use crate::client::evm::WsClient;
+use crate::config::Config;
+use crate::ingest::IngestStats;
+use crate::metrics;
+use crate::registry::EventRegistry;
+use crate::shutdown::Shutdown;
+use clap::Args;
+use eyre::{eyre, Result};
+use futures::StreamExt;
+use tracing::{info, warn};
+
+/// Checkpoint recording the last block the stream has indexed
+pub const CHECKPOINT: &str = "stream";
+
+#[derive(Debug, Args)]
+pub struct StreamArgs {
+    /// First block to index (defaults to the block after the last checkpoint,
+    /// else the confirmed chain head)
+    #[arg(long, value_name = "BLOCK")]
+    pub from_block: Option<u64>,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+pub async fn run(args: StreamArgs, config: &Config) -> Result<()> {
+    info!("Market Stream starting...");
+    metrics::spawn_from_config(&config.metrics);
+    let shutdown = Shutdown::from_config(&config.indexer);
+
+    let ws_client = WsClient::from_config(&config.rpc).await?;
+    let store = args.store.open(config).await?;
+    let registry = EventRegistry::from_config(config, store.clone());
+    let blocks = BlockRecorder::new(store.clone(), config.indexer.block_concurrency);
+    let confirmations = config.indexer.confirmations;
+
+    let checkpoint = store.get_checkpoint(CHECKPOINT).await?;
+    let mut next_block = match (args.from_block, checkpoint) {
+        (Some(block), _) => block,
+        (None, Some(block)) => {
+            info!("Resuming from checkpoint at block {}", block);
+            block + 1
+        }
+        (None, None) => {
+            ws_client
+                .get_block_number()
+                .await?
+                .saturating_sub(confirmations)
+                + 1
+        }
+    };
+
+    let mut heads = ws_client.subscribe_blocks().await?;
+    info!(
+        "Streaming {} events from block {} ({} confirmations)",
+        registry.event_names().join(", "),
+        next_block,
+        confirmations
+    );
+
+    let mut stats = IngestStats::default();
+    loop {
+        let head = tokio::select! {
+            head = heads.next() => head,
+            _ = shutdown.requested() => break,
+        };
+        let Some(head) = head else {
+            stats.log_summary();
+            return Err(eyre!("WebSocket subscription closed"));
+        };
+        let Some(head_number) = head.number else {
+            continue;
+        };
+        let head_number = head_number.as_u64();
+        let to_block = head_number.saturating_sub(confirmations);
+        if to_block < next_block {
+            metrics::record_progress(head_number, next_block.saturating_sub(1));
+            continue;
+        }
+
+        let filter = registry.filter().from_block(next_block).to_block(to_block);
+
+        let range = async {
+            let logs = ws_client.get_logs(&filter).await?;
+            metrics::LOGS_FETCHED.inc_by(logs.len() as u64);
+            let mut stats = blocks.record(&ws_client, &logs).await?;
+            stats += registry.dispatch(&logs).await?;
+            Ok::<_, eyre::Report>(stats)
+        };
+        let Some(range_stats) = shutdown.drain(range).await else {
+            warn!(
+                "Shutdown grace period elapsed; blocks {} to {} will be redone on the next run",
+                next_block, to_block
+            );
+            break;
+        };
+        stats += range_stats?;
+
+        store.set_checkpoint(CHECKPOINT, to_block).await?;
+        next_block = to_block + 1;
+        metrics::record_progress(head_number, to_block);
+    }
+
+    info!("Stream stopped at block {}", next_block.saturating_sub(1));
+    stats.log_summary();
+    Ok(())
+}
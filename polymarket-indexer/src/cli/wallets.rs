@@ -0,0 +1,84 @@
+// Wallets - rebuild per-wallet positions, realized PnL and win rate
+//
+// Every stored fill, position event and transfer is replayed. A block range
+// limits the rewrite to the wallets active in it, recomputed from their whole
+// history; the other stored wallets are kept.
+
+use crate::cli::UsageError;
+use crate::config::Config;
+use crate::store;
+use crate::wallets;
+use clap::Args;
+use eyre::Result;
+use tracing::info;
+
+#[derive(Debug, Args)]
+pub struct WalletsArgs {
+    /// Only rewrite the wallets active from this block (defaults to 0)
+    #[arg(long, value_name = "BLOCK")]
+    pub from_block: Option<u64>,
+
+    /// Only rewrite the wallets active up to this block (defaults to the
+    /// latest stored one)
+    #[arg(long, value_name = "BLOCK")]
+    pub to_block: Option<u64>,
+}
+
+impl WalletsArgs {
+    /// Block range whose active wallets are rewritten, `None` for all of them
+    fn active_in(&self) -> Result<Option<(u64, u64)>, UsageError> {
+        if self.from_block.is_none() && self.to_block.is_none() {
+            return Ok(None);
+        }
+
+        let from_block = self.from_block.unwrap_or(0);
+        // Block numbers are stored as BIGINT
+        let to_block = self.to_block.unwrap_or(i64::MAX as u64);
+        if from_block > to_block {
+            return Err(UsageError(format!(
+                "--from-block ({}) must not be greater than --to-block ({})",
+                from_block, to_block
+            )));
+        }
+        Ok(Some((from_block, to_block)))
+    }
+}
+
+pub async fn run(args: WalletsArgs, config: &Config) -> Result<()> {
+    let active_in = args.active_in()?;
+
+    let store = store::open(&config.database).await?;
+    let (positions, wallets) = wallets::rebuild(&*store, &config.contracts, active_in).await?;
+
+    info!("Wallet rebuild complete!");
+    info!("  Positions written: {}", positions);
+    info!("  Wallets written: {}", wallets);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{Cli, Command};
+    use clap::Parser;
+
+    fn active_in(args: &[&str]) -> Result<Option<(u64, u64)>, String> {
+        let cli = Cli::try_parse_from(["polymarket-indexer", "wallets"].iter().chain(args))
+            .map_err(|e| e.to_string())?;
+        let Command::Wallets(args) = cli.command else {
+            panic!("expected wallets");
+        };
+        args.active_in().map_err(|e| e.0)
+    }
+
+    #[test]
+    fn test_block_range_limits_the_rewrite() {
+        assert_eq!(active_in(&[]), Ok(None));
+        assert_eq!(
+            active_in(&["--from-block", "5"]),
+            Ok(Some((5, i64::MAX as u64)))
+        );
+        assert_eq!(active_in(&["--to-block", "5"]), Ok(Some((0, 5))));
+        assert!(active_in(&["--from-block", "9", "--to-block", "5"]).is_err());
+    }
+}
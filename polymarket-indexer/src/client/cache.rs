@@ -0,0 +1,164 @@
+// Response cache - keep API responses on disk between runs
+//
+// Entries are stored like fixtures (`<dir>/<label>-<hash>.json`, hash of the
+// request key) along with the time they were written and whether the
+// response carried data. Responses with data stay fresh for `found_ttl`;
+// empty and not-found responses for the (usually shorter) `not_found_ttl`,
+// since a market missing from the API now may be listed later.
+//
+// The cache is best effort: unreadable, corrupt or unwritable entries are
+// logged and treated as misses.
+
+use crate::client::fixtures;
+use crate::config::CacheConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    /// Unix time the response was stored, in seconds
+    stored_at: u64,
+    found: bool,
+    response: Value,
+}
+
+/// On-disk cache of API responses with separate found/not-found TTLs
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    found_ttl: Duration,
+    not_found_ttl: Duration,
+}
+
+impl ResponseCache {
+    pub fn new(dir: impl Into<PathBuf>, found_ttl: Duration, not_found_ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            found_ttl,
+            not_found_ttl,
+        }
+    }
+
+    /// Cache from the `[cache]` config section (`None` when no directory is set)
+    pub fn from_config(config: &CacheConfig) -> Option<Self> {
+        config.dir.as_ref().map(|dir| {
+            Self::new(
+                dir,
+                Duration::from_secs(config.found_ttl_secs),
+                Duration::from_secs(config.not_found_ttl_secs),
+            )
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, label: &str, key: &str) -> PathBuf {
+        self.dir.join(fixtures::file_name(label, key))
+    }
+
+    /// Fresh cached response for `key`, if any
+    pub fn get(&self, label: &str, key: &str) -> Option<Value> {
+        self.get_at(label, key, SystemTime::now())
+    }
+
+    fn get_at(&self, label: &str, key: &str, now: SystemTime) -> Option<Value> {
+        let path = self.path(label, key);
+        let contents = fs::read_to_string(&path).ok()?;
+        let entry: Entry = match serde_json::from_str(&contents) {
+            Ok(entry) => entry,
+            Err(e) => {
+                warn!("Ignoring corrupt cache entry {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        // Two keys can only share a file on a hash collision
+        if entry.key != key {
+            return None;
+        }
+
+        let ttl = if entry.found {
+            self.found_ttl
+        } else {
+            self.not_found_ttl
+        };
+        let age = unix_secs(now).saturating_sub(entry.stored_at);
+        (age < ttl.as_secs()).then_some(entry.response)
+    }
+
+    /// Store `response` for `key`; `found` is false for empty or 404 answers
+    pub fn put(&self, label: &str, key: &str, found: bool, response: &Value) {
+        self.put_at(label, key, found, response, SystemTime::now())
+    }
+
+    fn put_at(&self, label: &str, key: &str, found: bool, response: &Value, now: SystemTime) {
+        let path = self.path(label, key);
+        let entry = Entry {
+            key: key.to_string(),
+            stored_at: unix_secs(now),
+            found,
+            response: response.clone(),
+        };
+        if let Err(e) = write_atomic(&path, &entry) {
+            warn!("Cannot write cache entry {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Write through a temporary file so concurrent readers never see a partial entry
+fn write_atomic(path: &Path, entry: &Entry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    fs::write(&tmp, serde_json::to_vec(entry)?)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_found_and_not_found_ttls() {
+        let dir = std::env::temp_dir().join(format!("cache-test-{}", std::process::id()));
+        let cache = ResponseCache::new(&dir, Duration::from_secs(100), Duration::from_secs(10));
+        let now = SystemTime::now();
+        let later = |secs| now + Duration::from_secs(secs);
+
+        let found = json!({"status": 200, "body": [{"id": "1"}]});
+        let missing = json!({"status": 404, "body": null});
+        cache.put_at("markets", "/markets?id=1", true, &found, now);
+        cache.put_at("markets", "/markets?id=2", false, &missing, now);
+
+        assert_eq!(
+            cache.get_at("markets", "/markets?id=1", now),
+            Some(found.clone())
+        );
+        assert_eq!(cache.get_at("markets", "/markets?id=2", now), Some(missing));
+        assert_eq!(cache.get_at("markets", "/markets?id=3", now), None);
+
+        // The not-found entry expires first
+        assert_eq!(cache.get_at("markets", "/markets?id=2", later(10)), None);
+        assert_eq!(
+            cache.get_at("markets", "/markets?id=1", later(99)),
+            Some(found)
+        );
+        assert_eq!(cache.get_at("markets", "/markets?id=1", later(100)), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,25 +1,64 @@
 // EVM RPC Clients for HTTP and WebSocket
 
+use crate::blocks::{BlockHeader, BlockSource};
+use crate::client::fixtures::Fixtures;
+use crate::client::transport::Transport;
 use crate::client::{Chain, Provider};
-use ethers::providers::{Http, Middleware, Provider as EthersProvider, Ws};
-use ethers::types::{Filter, Log};
-use eyre::Result;
+use crate::config::{ConfigError, FixturesConfig, RpcConfig};
+use crate::error::Result;
+use async_trait::async_trait;
+use ethers::providers::{Http, Middleware, Provider as EthersProvider, SubscriptionStream, Ws};
+use ethers::types::{Block, Filter, Log, H256};
+use std::str::FromStr;
 use std::sync::Arc;
+use tracing::info;
 
 /// HTTP client for historical queries (eth_getLogs)
+///
+/// Can record its responses to a fixtures directory or replay them offline.
 pub struct HttpClient {
-    provider: Arc<EthersProvider<Http>>,
+    provider: Arc<EthersProvider<Transport>>,
 }
 
 impl HttpClient {
     /// Create a new HTTP client for the given provider and chain
-    pub async fn new(provider: Provider, chain: Chain, api_key: Option<&str>) -> Result<Self> {
-        let url = provider.http_url(chain, api_key);
-        let http_provider = EthersProvider::<Http>::try_from(url)?;
+    pub async fn new(provider: &Provider, chain: Chain, api_key: Option<&str>) -> Result<Self> {
+        let http = http_transport(&provider.http_url(chain, api_key)?)?;
+        Ok(Self::with_transport(Transport::Http(http)))
+    }
 
-        Ok(Self {
-            provider: Arc::new(http_provider),
-        })
+    /// Create an HTTP client that records responses, or replays them without
+    /// connecting to the provider
+    pub async fn with_fixtures(
+        provider: &Provider,
+        chain: Chain,
+        api_key: Option<&str>,
+        fixtures: Fixtures,
+    ) -> Result<Self> {
+        if fixtures.is_replay() {
+            info!("Replaying RPC responses from {}", fixtures.dir().display());
+            return Ok(Self::with_transport(Transport::Replay(fixtures)));
+        }
+
+        info!("Recording RPC responses to {}", fixtures.dir().display());
+        let http = http_transport(&provider.http_url(chain, api_key)?)?;
+        Ok(Self::with_transport(Transport::Record(http, fixtures)))
+    }
+
+    /// Create a new HTTP client from the `[rpc]` and `[fixtures]` config sections
+    pub async fn from_config(config: &RpcConfig, fixtures: &FixturesConfig) -> Result<Self> {
+        let provider = config.provider();
+        let api_key = config.api_key.as_deref();
+        match Fixtures::from_config(fixtures) {
+            Some(fixtures) => Self::with_fixtures(&provider, config.chain, api_key, fixtures).await,
+            None => Self::new(&provider, config.chain, api_key).await,
+        }
+    }
+
+    fn with_transport(transport: Transport) -> Self {
+        Self {
+            provider: Arc::new(EthersProvider::new(transport)),
+        }
     }
 
     /// Get the current block number
@@ -33,6 +72,24 @@ impl HttpClient {
         let logs = self.provider.get_logs(filter).await?;
         Ok(logs)
     }
+
+    /// Get the header of block `number` (eth_getBlockByNumber)
+    pub async fn get_block_header(&self, number: u64) -> Result<Option<BlockHeader>> {
+        let block = self.provider.get_block(number).await?;
+        Ok(block.as_ref().and_then(BlockHeader::from_block))
+    }
+}
+
+#[async_trait]
+impl BlockSource for HttpClient {
+    async fn get_block_header(&self, number: u64) -> Result<Option<BlockHeader>> {
+        HttpClient::get_block_header(self, number).await
+    }
+}
+
+fn http_transport(url: &str) -> Result<Http> {
+    Http::from_str(url)
+        .map_err(|e| ConfigError::new(format!("invalid RPC URL {:?}: {}", url, e)).into())
 }
 
 /// WebSocket client for live event streaming (eth_subscribe)
@@ -42,8 +99,8 @@ pub struct WsClient {
 
 impl WsClient {
     /// Create a new WebSocket client for the given provider and chain
-    pub async fn new(provider: Provider, chain: Chain, api_key: Option<&str>) -> Result<Self> {
-        let url = provider.ws_url(chain, api_key);
+    pub async fn new(provider: &Provider, chain: Chain, api_key: Option<&str>) -> Result<Self> {
+        let url = provider.ws_url(chain, api_key)?;
         let ws_provider = EthersProvider::<Ws>::connect(url).await?;
 
         Ok(Self {
@@ -51,12 +108,39 @@ impl WsClient {
         })
     }
 
+    /// Create a new WebSocket client from the `[rpc]` config section
+    pub async fn from_config(config: &RpcConfig) -> Result<Self> {
+        Self::new(&config.provider(), config.chain, config.api_key.as_deref()).await
+    }
+
     /// Get the current block number
     pub async fn get_block_number(&self) -> Result<u64> {
         let block_number = self.provider.get_block_number().await?;
         Ok(block_number.as_u64())
     }
 
-    // TODO: Add method to subscribe to logs
-    // pub async fn subscribe_logs(&self, filter: Filter) -> Result<impl Stream<Item = Log>>
+    /// Fetch logs matching the given filter
+    pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let logs = self.provider.get_logs(filter).await?;
+        Ok(logs)
+    }
+
+    /// Get the header of block `number` (eth_getBlockByNumber)
+    pub async fn get_block_header(&self, number: u64) -> Result<Option<BlockHeader>> {
+        let block = self.provider.get_block(number).await?;
+        Ok(block.as_ref().and_then(BlockHeader::from_block))
+    }
+
+    /// Subscribe to new block headers (eth_subscribe "newHeads")
+    pub async fn subscribe_blocks(&self) -> Result<SubscriptionStream<'_, Ws, Block<H256>>> {
+        let stream = self.provider.subscribe_blocks().await?;
+        Ok(stream)
+    }
+}
+
+#[async_trait]
+impl BlockSource for WsClient {
+    async fn get_block_header(&self, number: u64) -> Result<Option<BlockHeader>> {
+        WsClient::get_block_header(self, number).await
+    }
 }
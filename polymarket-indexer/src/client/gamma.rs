@@ -2,27 +2,373 @@
 //
 // Provides HTTP client for querying market information from:
 // https://gamma-api.polymarket.com
+//
+// Requests share a rate limit. Rate-limited (429) and failed (5xx, network)
+// requests are retried with jittered exponential backoff, waiting at least as
+// long as the server's `Retry-After`; 404 means not found and other statuses
+// fail straight away.
+//
+// Responses (status and JSON body) can be recorded to a fixtures directory
+// and replayed later without network access. Outside fixtures, successful
+// and 404 responses can be kept in an on-disk cache between runs.
 
+use crate::client::cache::ResponseCache;
+use crate::client::fixtures::Fixtures;
+use crate::client::rate_limit::RateLimiter;
+use crate::config::{CacheConfig, ConfigError, FixturesConfig, GammaConfig};
+use crate::error::{IndexerError, Result};
+use crate::metrics;
 use crate::polymarket::market::{MarketMetadata, Tag};
-use eyre::Result;
-use reqwest::Client;
-use tracing::warn;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tracing::{info, warn};
 
 /// Base URL for Gamma API
-const GAMMA_API_BASE_URL: &str = "https://gamma-api.polymarket.com";
+pub const GAMMA_API_BASE_URL: &str = "https://gamma-api.polymarket.com";
+
+/// Fixture subdirectory for Gamma responses
+const FIXTURE_KIND: &str = "gamma";
+
+/// Service name in errors
+const SERVICE: &str = "gamma";
+
+/// First retry delay; doubles on each attempt
+const BASE_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest backoff between retries
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Longest `Retry-After` honoured; longer requests are capped
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Raw Gamma response, as recorded in fixtures
+#[derive(Debug, Serialize, Deserialize)]
+struct GammaResponse {
+    status: u16,
+    /// `Retry-After` in seconds, when the server sent one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry_after_secs: Option<u64>,
+    body: Value,
+}
+
+impl GammaResponse {
+    fn status(&self) -> StatusCode {
+        StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// Whether the response carries data (not a 404, `null` or `[]`)
+    fn is_found(&self) -> bool {
+        self.status().is_success()
+            && match &self.body {
+                Value::Null => false,
+                Value::Array(items) => !items.is_empty(),
+                _ => true,
+            }
+    }
+
+    /// Map a non-2xx status to its error kind
+    ///
+    /// A 429, or a 503 carrying `Retry-After`, is the server asking us to
+    /// back off and becomes `RateLimited`.
+    fn error_for_status(self, what: &str) -> Result<Self> {
+        let status = self.status();
+        if status.is_success() {
+            return Ok(self);
+        }
+
+        let retry_after = self.retry_after_secs.map(Duration::from_secs);
+        Err(match status {
+            StatusCode::NOT_FOUND => IndexerError::NotFound(what.to_string()),
+            StatusCode::TOO_MANY_REQUESTS => IndexerError::RateLimited {
+                service: SERVICE,
+                retry_after,
+            },
+            StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => IndexerError::RateLimited {
+                service: SERVICE,
+                retry_after,
+            },
+            _ => IndexerError::Http {
+                service: SERVICE,
+                status: Some(status.as_u16()),
+                message: format!("{} returned {}", what, status),
+            },
+        })
+    }
+}
+
+/// Network or protocol failure talking to Gamma
+fn http_error(e: reqwest::Error) -> IndexerError {
+    IndexerError::Http {
+        service: SERVICE,
+        status: e.status().map(|s| s.as_u16()),
+        message: e.to_string(),
+    }
+}
+
+/// Parse a `Retry-After` value: delay in seconds, or an HTTP date
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(secs);
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).num_seconds().max(0) as u64)
+}
+
+/// Exponential backoff for `attempt`, jittered over its upper half
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = BASE_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_RETRY_DELAY);
+    rand::thread_rng().gen_range(ceiling / 2..=ceiling)
+}
+
+/// Delay before retrying after `error`
+fn retry_delay(error: &IndexerError, attempt: u32) -> Duration {
+    let delay = backoff(attempt);
+    match error {
+        IndexerError::RateLimited {
+            retry_after: Some(retry_after),
+            ..
+        } => delay.max((*retry_after).min(MAX_RETRY_AFTER)),
+        _ => delay,
+    }
+}
 
 /// HTTP client for Gamma API
 pub struct GammaClient {
     client: Client,
     base_url: String,
+    fixtures: Option<Fixtures>,
+    cache: Option<ResponseCache>,
+    limiter: RateLimiter,
+    max_retries: u32,
 }
 
 impl GammaClient {
     /// Create a new Gamma API client
     pub fn new() -> Self {
+        Self::with_base_url(GAMMA_API_BASE_URL)
+    }
+
+    /// Create a Gamma API client for a different deployment (e.g. a mock server)
+    ///
+    /// Uses the default `[gamma]` retry and rate limits.
+    pub fn with_base_url(base_url: &str) -> Self {
+        let defaults = GammaConfig::default();
         Self {
             client: Client::new(),
-            base_url: GAMMA_API_BASE_URL.to_string(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            fixtures: None,
+            cache: None,
+            limiter: RateLimiter::per_second(defaults.requests_per_second),
+            max_retries: defaults.max_retries,
+        }
+    }
+
+    /// Retry rate-limited and failed requests up to `max_retries` times
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Send at most `per_second` requests per second (0 disables the limit)
+    pub fn with_rate_limit(mut self, per_second: u32) -> Self {
+        self.limiter = RateLimiter::per_second(per_second);
+        self
+    }
+
+    /// Record responses to, or replay them from, a fixtures directory
+    pub fn with_fixtures(mut self, fixtures: Fixtures) -> Self {
+        if fixtures.is_replay() {
+            info!(
+                "Replaying Gamma responses from {}",
+                fixtures.dir().display()
+            );
+        } else {
+            info!("Recording Gamma responses to {}", fixtures.dir().display());
+        }
+        self.fixtures = Some(fixtures);
+        self
+    }
+
+    /// Serve fresh responses from, and store new ones in, an on-disk cache
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        info!("Caching Gamma responses in {}", cache.dir().display());
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Create a Gamma API client from the `[gamma]`, `[fixtures]` and `[cache]`
+    /// config sections
+    ///
+    /// The cache is skipped while fixtures are on, so recordings capture what
+    /// the API actually returned.
+    pub fn from_config(
+        config: &GammaConfig,
+        fixtures: &FixturesConfig,
+        cache: &CacheConfig,
+    ) -> Self {
+        let client = Self::with_base_url(&config.base_url)
+            .with_max_retries(config.max_retries)
+            .with_rate_limit(config.requests_per_second);
+        match (
+            Fixtures::from_config(fixtures),
+            ResponseCache::from_config(cache),
+        ) {
+            (Some(fixtures), _) => client.with_fixtures(fixtures),
+            (None, Some(cache)) => client.with_cache(cache),
+            (None, None) => client,
+        }
+    }
+
+    /// Whether responses come from fixtures rather than the network
+    fn is_replay(&self) -> bool {
+        matches!(self.fixtures, Some(ref fixtures) if fixtures.is_replay())
+    }
+
+    /// GET a Gamma endpoint once, going through the fixtures when configured
+    ///
+    /// `path` is relative to the base URL and, with the query, forms the
+    /// fixture key so recordings work against any deployment. With
+    /// `skip_cached_miss`, a cached empty or not-found answer is ignored and
+    /// the API asked again.
+    async fn send(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        skip_cached_miss: bool,
+    ) -> Result<GammaResponse> {
+        let request = self
+            .client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query)
+            .build()
+            .map_err(http_error)?;
+        let key = match request.url().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path.to_string(),
+        };
+        let label = path.trim_start_matches('/').split('/').next().unwrap_or("");
+
+        if let Some(ref fixtures) = self.fixtures {
+            if fixtures.is_replay() {
+                // A missing recording is no answer at all, not a 404
+                let response =
+                    fixtures
+                        .load(FIXTURE_KIND, label, &key)
+                        .map_err(|e| IndexerError::Http {
+                            service: SERVICE,
+                            status: None,
+                            message: e.to_string(),
+                        })?;
+                return Ok(serde_json::from_value(response)?);
+            }
+        }
+
+        if let Some(ref cache) = self.cache {
+            if let Some(response) = cache.get(label, &key) {
+                if let Ok(response) = serde_json::from_value::<GammaResponse>(response) {
+                    if response.is_found() || !skip_cached_miss {
+                        metrics::GAMMA_CACHE.with_label_values(&["hit"]).inc();
+                        return Ok(response);
+                    }
+                }
+            }
+            metrics::GAMMA_CACHE.with_label_values(&["miss"]).inc();
+        }
+
+        self.limiter.acquire().await;
+        let response = self.client.execute(request).await.map_err(http_error)?;
+        let status = response.status().as_u16();
+        let retry_after_secs = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| parse_retry_after(v, Utc::now()));
+        let bytes = response.bytes().await.map_err(http_error)?;
+        // Error pages are often not JSON; keep the status and drop the body
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        let response = GammaResponse {
+            status,
+            retry_after_secs,
+            body,
+        };
+
+        if let Some(ref fixtures) = self.fixtures {
+            fixtures
+                .save(FIXTURE_KIND, label, &key, &serde_json::to_value(&response)?)
+                .map_err(|e| ConfigError::new(format!("cannot record fixture: {}", e)))?;
+        }
+        if let Some(ref cache) = self.cache {
+            // Errors and rate limits are transient and never cached
+            if response.status().is_success() || response.status() == StatusCode::NOT_FOUND {
+                cache.put(
+                    label,
+                    &key,
+                    response.is_found(),
+                    &serde_json::to_value(&response)?,
+                );
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// GET a Gamma endpoint, retrying rate-limited and failed requests
+    ///
+    /// `what` names the resource in errors and logs; `skip_cached_miss` is
+    /// passed on to [`Self::send`].
+    async fn get(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        what: &str,
+        skip_cached_miss: bool,
+    ) -> Result<GammaResponse> {
+        // A replayed answer never changes, so retrying it only adds delay
+        let max_retries = if self.is_replay() {
+            0
+        } else {
+            self.max_retries
+        };
+        let mut attempt = 0;
+
+        loop {
+            let error = match self.send(path, query, skip_cached_miss).await {
+                Ok(response) => match response.error_for_status(what) {
+                    Ok(response) => return Ok(response),
+                    Err(e) => e,
+                },
+                Err(e) => e,
+            };
+            if !error.is_retryable() || attempt >= max_retries {
+                return Err(error);
+            }
+
+            let delay = retry_delay(&error, attempt);
+            if let IndexerError::RateLimited { .. } = error {
+                // Every request would be rejected until then, not just this one
+                self.limiter.pause(delay).await;
+            }
+            metrics::GAMMA_RETRIES
+                .with_label_values(&[error.kind()])
+                .inc();
+            warn!(
+                "Fetching {} failed: {}. Retrying in {}ms (attempt {}/{})",
+                what,
+                error,
+                delay.as_millis(),
+                attempt + 1,
+                max_retries
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         }
     }
 
@@ -34,96 +380,100 @@ impl GammaClient {
     /// # Returns
     /// * `Ok(Some(MarketMetadata))` - Market found
     /// * `Ok(None)` - Market not found in API (may be too new)
-    /// * `Err(_)` - Network or parsing error
+    /// * `Err(_)` - Network, status or parsing error (after retries)
     pub async fn get_market_by_condition_id(
         &self,
         condition_id: &str,
     ) -> Result<Option<MarketMetadata>> {
-        let url = format!("{}/markets", self.base_url);
+        self.lookup_market(condition_id, false).await
+    }
 
-        let response = self
-            .client
-            .get(&url)
-            .query(&[("condition_ids", condition_id)])
-            .send()
-            .await?;
+    async fn lookup_market(
+        &self,
+        condition_id: &str,
+        skip_cached_miss: bool,
+    ) -> Result<Option<MarketMetadata>> {
+        let _timer = metrics::GAMMA_LATENCY
+            .with_label_values(&["markets"])
+            .start_timer();
+        let result = self.fetch_market(condition_id, skip_cached_miss).await;
+        record_lookup("markets", &result, Option::is_some);
+        result
+    }
 
-        if !response.status().is_success() {
-            warn!(
-                "Gamma API returned non-success status: {}",
-                response.status()
-            );
-            return Ok(None);
-        }
+    async fn fetch_market(
+        &self,
+        condition_id: &str,
+        skip_cached_miss: bool,
+    ) -> Result<Option<MarketMetadata>> {
+        let what = format!("market {}", condition_id);
+        let response = match self
+            .get(
+                "/markets",
+                &[("condition_ids", condition_id)],
+                &what,
+                skip_cached_miss,
+            )
+            .await
+        {
+            Ok(response) => response,
+            Err(IndexerError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
 
         // API returns a direct array, not wrapped in an object
-        let markets: Vec<MarketMetadata> = response.json().await?;
+        let markets: Vec<MarketMetadata> = serde_json::from_value(response.body)?;
 
         // Return the first market if any were found
         Ok(markets.into_iter().next())
     }
 
-    /// Get market metadata with retry logic
+    /// Get market metadata, waiting for markets not yet in the API
     ///
-    /// New markets may not immediately appear in the Gamma API.
-    /// This method retries with exponential backoff.
+    /// New markets may not immediately appear in the Gamma API, so a
+    /// not-found answer is retried with backoff. Retries skip a cached
+    /// not-found answer, so the wait is not cut short by the cache. Errors
+    /// have already been retried by the request itself and are returned as
+    /// they are.
     ///
     /// # Arguments
     /// * `condition_id` - Hex string with 0x prefix
-    /// * `max_retries` - Maximum number of retry attempts
+    /// * `max_retries` - Maximum number of retries while the market is missing
     ///
     /// # Returns
     /// * `Ok(Some(MarketMetadata))` - Market found
     /// * `Ok(None)` - Market not found after all retries
-    /// * `Err(_)` - Persistent network or parsing error
+    /// * `Err(_)` - Network, status or parsing error
     pub async fn get_market_with_retry(
         &self,
         condition_id: &str,
         max_retries: u32,
     ) -> Result<Option<MarketMetadata>> {
+        let max_retries = if self.is_replay() { 0 } else { max_retries };
         let mut attempt = 0;
 
         loop {
-            match self.get_market_by_condition_id(condition_id).await {
-                Ok(Some(market)) => return Ok(Some(market)),
-                Ok(None) if attempt < max_retries => {
-                    // Market not found, retry with exponential backoff
-                    let delay_ms = 100 * 2u64.pow(attempt);
+            match self.lookup_market(condition_id, attempt > 0).await? {
+                Some(market) => return Ok(Some(market)),
+                None if attempt < max_retries => {
+                    let delay = backoff(attempt);
                     warn!(
                         "Market not found for condition_id {}, retrying in {}ms (attempt {}/{})",
                         condition_id,
-                        delay_ms,
+                        delay.as_millis(),
                         attempt + 1,
                         max_retries
                     );
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+                    tokio::time::sleep(delay).await;
                     attempt += 1;
                 }
-                Ok(None) => {
-                    // Market not found after all retries
+                None => {
                     warn!(
                         "Market not found for condition_id {} after {} retries",
                         condition_id, max_retries
                     );
                     return Ok(None);
                 }
-                Err(e) => {
-                    // Network or parsing error
-                    if attempt < max_retries {
-                        let delay_ms = 100 * 2u64.pow(attempt);
-                        warn!(
-                            "Error fetching market: {}. Retrying in {}ms (attempt {}/{})",
-                            e,
-                            delay_ms,
-                            attempt + 1,
-                            max_retries
-                        );
-                        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-                        attempt += 1;
-                    } else {
-                        return Err(e);
-                    }
-                }
             }
         }
     }
@@ -135,27 +485,237 @@ impl GammaClient {
     ///
     /// # Returns
     /// * `Ok(Vec<Tag>)` - List of tags (may be empty)
-    /// * `Err(_)` - Network or parsing error
+    /// * `Err(_)` - Network, status or parsing error (after retries)
     pub async fn get_market_tags(&self, market_id: &str) -> Result<Vec<Tag>> {
-        let url = format!("{}/markets/{}/tags", self.base_url, market_id);
-
-        let response = self.client.get(&url).send().await?;
+        let _timer = metrics::GAMMA_LATENCY
+            .with_label_values(&["tags"])
+            .start_timer();
+        let result = self.fetch_market_tags(market_id).await;
+        record_lookup("tags", &result, |tags| !tags.is_empty());
+        result
+    }
 
-        if !response.status().is_success() {
-            warn!(
-                "Gamma API returned non-success status: {}",
-                response.status()
-            );
-            return Ok(Vec::new());
-        }
+    async fn fetch_market_tags(&self, market_id: &str) -> Result<Vec<Tag>> {
+        let what = format!("tags for market {}", market_id);
+        let response = self
+            .get(&format!("/markets/{}/tags", market_id), &[], &what, false)
+            .await?;
 
-        let tags: Vec<Tag> = response.json().await?;
+        let tags: Vec<Tag> = serde_json::from_value(response.body)?;
         Ok(tags)
     }
 }
 
+/// Count a Gamma lookup as a hit, miss or the kind of error
+fn record_lookup<T>(endpoint: &str, result: &Result<T>, found: impl Fn(&T) -> bool) {
+    let outcome = match result {
+        Ok(value) if found(value) => "hit",
+        Ok(_) => "miss",
+        Err(e) => e.kind(),
+    };
+    metrics::GAMMA_REQUESTS
+        .with_label_values(&[endpoint, outcome])
+        .inc();
+}
+
 impl Default for GammaClient {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{header, HeaderMap, StatusCode as AxumStatus};
+    use axum::routing::get;
+    use axum::Router;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    /// Serve `/markets` and `/markets/:id/tags`, answering each request with
+    /// the next of `statuses` (then 200) and counting requests
+    async fn spawn_server(
+        statuses: Vec<(u16, Option<&'static str>)>,
+    ) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let respond = {
+            let hits = hits.clone();
+            move || {
+                let n = hits.fetch_add(1, Ordering::SeqCst);
+                let (status, retry_after) = statuses.get(n).copied().unwrap_or((200, None));
+                let mut headers = HeaderMap::new();
+                if let Some(value) = retry_after {
+                    headers.insert(header::RETRY_AFTER, value.parse().unwrap());
+                }
+                let body = json!([{
+                    "id": "1",
+                    "question": "Will it retry?",
+                    "slug": "will-it-retry",
+                    "conditionId": "0x01",
+                    "outcomes": "[\"Yes\", \"No\"]",
+                }]);
+                (
+                    AxumStatus::from_u16(status).unwrap(),
+                    headers,
+                    body.to_string(),
+                )
+            }
+        };
+        let markets = respond.clone();
+        let app = Router::new()
+            .route("/markets", get(move || async move { markets() }))
+            .route("/markets/:id/tags", get(move || async move { respond() }));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, hits)
+    }
+
+    fn client(url: &str, max_retries: u32) -> GammaClient {
+        GammaClient::with_base_url(url)
+            .with_max_retries(max_retries)
+            .with_rate_limit(0)
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried() {
+        let (url, hits) = spawn_server(vec![(503, None), (502, None)]).await;
+        let market = client(&url, 3)
+            .get_market_by_condition_id("0x01")
+            .await
+            .unwrap();
+
+        assert_eq!(market.unwrap().question, "Will it retry?");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_waits_for_retry_after() {
+        let (url, hits) = spawn_server(vec![(429, Some("1"))]).await;
+        let start = Instant::now();
+        let tags = client(&url, 1).get_market_tags("1").await;
+
+        assert_eq!(tags.unwrap().len(), 1);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_fatal_statuses_are_not_retried() {
+        let (url, hits) = spawn_server(vec![(404, None), (400, None)]).await;
+        let gamma = client(&url, 3);
+
+        let tags = gamma.get_market_tags("1").await;
+        assert!(matches!(tags, Err(IndexerError::NotFound(_))));
+
+        let market = gamma.get_market_by_condition_id("0x01").await;
+        assert!(matches!(
+            market,
+            Err(IndexerError::Http {
+                status: Some(400),
+                ..
+            })
+        ));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_give_up_after_max() {
+        let (url, hits) = spawn_server(vec![(500, None); 3]).await;
+        let market = client(&url, 1).get_market_by_condition_id("0x01").await;
+
+        assert!(matches!(market, Err(ref e) if e.is_retryable()));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_responses_skip_the_api() {
+        let (url, hits) = spawn_server(vec![(503, None)]).await;
+        let dir = std::env::temp_dir().join(format!("gamma-cache-test-{}", std::process::id()));
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60), Duration::from_secs(60));
+        let gamma = client(&url, 1).with_cache(cache);
+
+        // The 503 is retried, not cached; the 200 is served from disk next time
+        for _ in 0..2 {
+            let market = gamma.get_market_by_condition_id("0x01").await.unwrap();
+            assert_eq!(market.unwrap().slug, "will-it-retry");
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retries_skip_cached_not_found() {
+        // The market is not listed yet on the first request
+        let hits = Arc::new(AtomicUsize::new(0));
+        let app = Router::new().route(
+            "/markets",
+            get({
+                let hits = hits.clone();
+                move || async move {
+                    if hits.fetch_add(1, Ordering::SeqCst) == 0 {
+                        return json!([]).to_string();
+                    }
+                    json!([{
+                        "id": "1",
+                        "question": "Will it be listed?",
+                        "slug": "will-it-be-listed",
+                        "conditionId": "0x01",
+                        "outcomes": "[\"Yes\", \"No\"]",
+                    }])
+                    .to_string()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir =
+            std::env::temp_dir().join(format!("gamma-cache-miss-test-{}", std::process::id()));
+        let cache = ResponseCache::new(&dir, Duration::from_secs(60), Duration::from_secs(600));
+        let gamma = client(&url, 0).with_cache(cache);
+
+        let market = gamma.get_market_with_retry("0x01", 2).await.unwrap();
+        assert_eq!(market.unwrap().slug, "will-it-be-listed");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        // The market replaced the cached miss
+        let market = gamma.get_market_by_condition_id("0x01").await.unwrap();
+        assert_eq!(market.unwrap().slug, "will-it-be-listed");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("120", now), Some(120));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(30)
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(0)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_backoff_is_jittered_and_capped() {
+        for attempt in 0..20 {
+            let delay = backoff(attempt);
+            let ceiling = (BASE_RETRY_DELAY * 2u32.saturating_pow(attempt)).min(MAX_RETRY_DELAY);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{:?}", delay);
+        }
+    }
+}
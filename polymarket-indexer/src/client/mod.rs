@@ -1,40 +1,88 @@
+pub mod cache;
 pub mod evm;
+pub mod fixtures;
 pub mod gamma;
+pub mod rate_limit;
+pub mod transport;
+
+use crate::config::ConfigError;
+use serde::Deserialize;
 
 // RPC Provider and Chain Configuration
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Provider {
     Alchemy,
+    /// Any JSON-RPC endpoint (self-hosted node, local anvil, other vendors)
+    Custom {
+        http_url: String,
+        ws_url: Option<String>,
+    },
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Chain {
     Polygon,
+    Amoy,
+}
+
+impl Chain {
+    /// EIP-155 chain ID
+    pub fn id(&self) -> u64 {
+        match self {
+            Chain::Polygon => 137,
+            Chain::Amoy => 80002,
+        }
+    }
 }
 
 impl Provider {
-    /// Build HTTP RPC URL for Polygon mainnet on Alchemy
-    pub(crate) fn http_url(&self, chain: Chain, api_key: Option<&str>) -> String {
-        match (self, chain) {
-            (Provider::Alchemy, Chain::Polygon) => {
-                format!(
-                    "https://polygon-mainnet.g.alchemy.com/v2/{}",
-                    api_key.expect("Alchemy requires API key")
-                )
-            }
+    /// Build HTTP RPC URL for the given chain
+    pub(crate) fn http_url(
+        &self,
+        chain: Chain,
+        api_key: Option<&str>,
+    ) -> Result<String, ConfigError> {
+        match self {
+            Provider::Alchemy => Ok(format!(
+                "https://{}.g.alchemy.com/v2/{}",
+                alchemy_network(chain),
+                alchemy_api_key(api_key)?
+            )),
+            Provider::Custom { http_url, .. } => Ok(http_url.clone()),
         }
     }
 
-    /// Build WebSocket RPC URL for Polygon mainnet on Alchemy
-    pub(crate) fn ws_url(&self, chain: Chain, api_key: Option<&str>) -> String {
-        match (self, chain) {
-            (Provider::Alchemy, Chain::Polygon) => {
-                format!(
-                    "wss://polygon-mainnet.g.alchemy.com/v2/{}",
-                    api_key.expect("Alchemy requires API key")
-                )
-            }
+    /// Build WebSocket RPC URL for the given chain
+    pub(crate) fn ws_url(
+        &self,
+        chain: Chain,
+        api_key: Option<&str>,
+    ) -> Result<String, ConfigError> {
+        match self {
+            Provider::Alchemy => Ok(format!(
+                "wss://{}.g.alchemy.com/v2/{}",
+                alchemy_network(chain),
+                alchemy_api_key(api_key)?
+            )),
+            Provider::Custom { ws_url, .. } => ws_url.clone().ok_or_else(|| {
+                ConfigError::new("rpc.ws_url is required for streaming with a custom provider")
+            }),
         }
     }
 }
+
+/// Alchemy subdomain for a chain
+fn alchemy_network(chain: Chain) -> &'static str {
+    match chain {
+        Chain::Polygon => "polygon-mainnet",
+        Chain::Amoy => "polygon-amoy",
+    }
+}
+
+fn alchemy_api_key(api_key: Option<&str>) -> Result<&str, ConfigError> {
+    api_key.ok_or_else(|| {
+        ConfigError::new("Alchemy requires an API key (set rpc.api_key or ALCHEMY_API_KEY)")
+    })
+}
@@ -0,0 +1,73 @@
+// Rate limiter shared by concurrent requests to one API
+//
+// Requests are spaced evenly at the configured rate. A server-requested pause
+// (e.g. `Retry-After` on a 429) holds back every caller, not just the one
+// that was rejected.
+
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Minimum spacing between requests (zero when unlimited)
+    interval: Duration,
+    /// Earliest time the next request may start
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Allow up to `per_second` requests per second (0 means unlimited)
+    pub fn per_second(per_second: u32) -> Self {
+        let interval = match per_second {
+            0 => Duration::ZERO,
+            n => Duration::from_secs(1) / n,
+        };
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for this caller's turn
+    pub async fn acquire(&self) {
+        let start = {
+            let mut next = self.next.lock().await;
+            let start = (*next).max(Instant::now());
+            *next = start + self.interval;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+
+    /// Hold back every caller for at least `delay` from now
+    pub async fn pause(&self, delay: Duration) {
+        let mut next = self.next.lock().await;
+        *next = (*next).max(Instant::now() + delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_are_spaced() {
+        let limiter = RateLimiter::per_second(50);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+        // First request is immediate, the next two wait 20ms each
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    async fn test_pause_holds_back_next_request() {
+        let limiter = RateLimiter::per_second(0);
+        limiter.pause(Duration::from_millis(30)).await;
+        let start = Instant::now();
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(25));
+    }
+}
@@ -0,0 +1,145 @@
+// JSON-RPC transport with optional response recording and replay
+//
+// Wraps the ethers HTTP transport so `HttpClient` can save every response
+// under a fixtures directory, or serve a previous recording with no node.
+
+use crate::client::fixtures::Fixtures;
+use async_trait::async_trait;
+use ethers::providers::{
+    Http, HttpClientError, JsonRpcClient, JsonRpcError, ProviderError, RpcError,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{self, Debug};
+
+/// Fixture subdirectory for RPC responses
+const KIND: &str = "rpc";
+
+#[derive(Debug)]
+pub enum Transport {
+    /// Plain HTTP
+    Http(Http),
+    /// HTTP, saving each response
+    Record(Http, Fixtures),
+    /// Recorded responses only
+    Replay(Fixtures),
+}
+
+#[derive(Debug)]
+pub enum TransportError {
+    Http(HttpClientError),
+    Json(serde_json::Error),
+    /// Missing or unwritable fixture
+    Fixture(String),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::Http(e) => write!(f, "{}", e),
+            TransportError::Json(e) => write!(f, "{}", e),
+            TransportError::Fixture(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TransportError::Http(e) => Some(e),
+            TransportError::Json(e) => Some(e),
+            TransportError::Fixture(_) => None,
+        }
+    }
+}
+
+impl RpcError for TransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            TransportError::Http(e) => e.as_error_response(),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            TransportError::Http(e) => e.as_serde_error(),
+            TransportError::Json(e) => Some(e),
+            TransportError::Fixture(_) => None,
+        }
+    }
+}
+
+impl From<TransportError> for ProviderError {
+    fn from(e: TransportError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(e))
+    }
+}
+
+/// Fixture key: the method and its serialized params
+fn request_key<T: Serialize>(method: &str, params: &T) -> Result<String, TransportError> {
+    let params = serde_json::to_string(params).map_err(TransportError::Json)?;
+    Ok(format!("{} {}", method, params))
+}
+
+#[async_trait]
+impl JsonRpcClient for Transport {
+    type Error = TransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let response: Value = match self {
+            Transport::Http(http) => {
+                return http
+                    .request(method, params)
+                    .await
+                    .map_err(TransportError::Http)
+            }
+            Transport::Record(http, fixtures) => {
+                let key = request_key(method, &params)?;
+                let response: Value = http
+                    .request(method, params)
+                    .await
+                    .map_err(TransportError::Http)?;
+                fixtures
+                    .save(KIND, method, &key, &response)
+                    .map_err(|e| TransportError::Fixture(e.to_string()))?;
+                response
+            }
+            Transport::Replay(fixtures) => {
+                let key = request_key(method, &params)?;
+                fixtures
+                    .load(KIND, method, &key)
+                    .map_err(|e| TransportError::Fixture(e.to_string()))?
+            }
+        };
+
+        serde_json::from_value(response).map_err(TransportError::Json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{Middleware, Provider};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_replay_serves_recorded_block_number() {
+        let dir = std::env::temp_dir().join(format!("transport-test-{}", std::process::id()));
+        let key = request_key("eth_blockNumber", &()).unwrap();
+        Fixtures::record(&dir)
+            .save(KIND, "eth_blockNumber", &key, &json!("0x2a"))
+            .unwrap();
+
+        let provider = Provider::new(Transport::Replay(Fixtures::replay(&dir)));
+        assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 42);
+        assert!(provider.get_chainid().await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
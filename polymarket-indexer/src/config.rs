@@ -0,0 +1,715 @@
+// Indexer Configuration
+//
+// Settings are layered, later layers winning:
+// 1. Built-in defaults (Alchemy on Polygon, mainnet contracts, public Gamma API)
+// 2. TOML file (`--config <PATH>`, or `indexer.toml` in the working directory)
+// 3. Environment variables (ALCHEMY_API_KEY, DATABASE_URL, INDEXER_*)
+//
+// See indexer.example.toml for every key.
+
+use crate::client::{Chain, Provider};
+use crate::polymarket::constants::{
+    CTF_CONTRACT_ADDRESS, CTF_EXCHANGE_ADDRESS, NEG_RISK_ADAPTER_ADDRESS,
+    NEG_RISK_EXCHANGE_ADDRESS, UMA_CTF_ADAPTER_ADDRESSES,
+};
+use ethers::types::H160;
+use reqwest::Url;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::Deserialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Config file picked up from the working directory when `--config` is not given
+const DEFAULT_CONFIG_FILE: &str = "indexer.toml";
+
+/// Upper bound for Gamma retries (backoff grows as 2^attempt)
+const MAX_GAMMA_RETRIES: u32 = 16;
+
+/// Invalid or missing configuration
+#[derive(Debug)]
+pub struct ConfigError(String);
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self(message.into())
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Complete indexer configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rpc: RpcConfig,
+    pub contracts: ContractsConfig,
+    pub database: DatabaseConfig,
+    pub gamma: GammaConfig,
+    pub indexer: IndexerConfig,
+    pub metrics: MetricsConfig,
+    pub api: ApiConfig,
+    pub fixtures: FixturesConfig,
+    pub cache: CacheConfig,
+}
+
+/// Which RPC vendor to connect to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Alchemy,
+    Custom,
+}
+
+/// `[rpc]` - JSON-RPC endpoints
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpcConfig {
+    pub provider: ProviderKind,
+    pub chain: Chain,
+    /// Alchemy API key (env: ALCHEMY_API_KEY)
+    pub api_key: Option<String>,
+    /// HTTP endpoint for the custom provider
+    pub http_url: Option<String>,
+    /// WebSocket endpoint for the custom provider (needed by `stream`)
+    pub ws_url: Option<String>,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::Alchemy,
+            chain: Chain::Polygon,
+            api_key: None,
+            http_url: None,
+            ws_url: None,
+        }
+    }
+}
+
+impl RpcConfig {
+    /// Build the client-side provider description
+    pub fn provider(&self) -> Provider {
+        match self.provider {
+            ProviderKind::Alchemy => Provider::Alchemy,
+            ProviderKind::Custom => Provider::Custom {
+                http_url: self.http_url.clone().unwrap_or_default(),
+                ws_url: self.ws_url.clone(),
+            },
+        }
+    }
+}
+
+/// `[contracts]` - Polymarket contract addresses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContractsConfig {
+    /// CTFExchange (emits TokenRegistered)
+    pub ctf_exchange: H160,
+    /// Conditional Tokens Framework
+    pub ctf: H160,
+    /// CTFExchange instance for NegRisk (multi-outcome) markets
+    pub neg_risk_exchange: H160,
+    /// NegRiskAdapter
+    pub neg_risk_adapter: H160,
+    /// UMA CTF Adapter deployments (emit the question lifecycle events)
+    ///
+    /// Each must be the oracle of the conditions it prepares, since questions
+    /// are linked to markets through that condition (see `polymarket::uma`).
+    pub uma_ctf_adapters: Vec<H160>,
+}
+
+impl Default for ContractsConfig {
+    fn default() -> Self {
+        Self {
+            ctf_exchange: H160::from_str(CTF_EXCHANGE_ADDRESS)
+                .expect("Invalid CTF_EXCHANGE_ADDRESS constant"),
+            ctf: H160::from_str(CTF_CONTRACT_ADDRESS)
+                .expect("Invalid CTF_CONTRACT_ADDRESS constant"),
+            neg_risk_exchange: H160::from_str(NEG_RISK_EXCHANGE_ADDRESS)
+                .expect("Invalid NEG_RISK_EXCHANGE_ADDRESS constant"),
+            neg_risk_adapter: H160::from_str(NEG_RISK_ADAPTER_ADDRESS)
+                .expect("Invalid NEG_RISK_ADAPTER_ADDRESS constant"),
+            uma_ctf_adapters: UMA_CTF_ADAPTER_ADDRESSES
+                .iter()
+                .map(|address| {
+                    H160::from_str(address).expect("Invalid UMA_CTF_ADAPTER_ADDRESSES constant")
+                })
+                .collect(),
+        }
+    }
+}
+
+/// `[database]` - connection pool for PostgreSQL or, with the `sqlite`
+/// feature, a SQLite file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// Connection URL (env: DATABASE_URL); the scheme selects the backend
+    pub url: Option<String>,
+    pub max_connections: u32,
+    pub acquire_timeout_secs: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            max_connections: 5,
+            acquire_timeout_secs: 3,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// Connection URL, required by every command that touches the database
+    pub fn url(&self) -> Result<&str, ConfigError> {
+        self.url
+            .as_deref()
+            .ok_or_else(|| ConfigError::new("database.url is not set (set it or DATABASE_URL)"))
+    }
+
+    /// Backend selected by the URL scheme
+    pub fn backend(&self) -> Result<DatabaseBackend, ConfigError> {
+        let url = self.url()?;
+        match url.split_once(':').map(|(scheme, _)| scheme) {
+            Some("postgres" | "postgresql") => Ok(DatabaseBackend::Postgres),
+            #[cfg(feature = "sqlite")]
+            Some("sqlite") => Ok(DatabaseBackend::Sqlite),
+            #[cfg(not(feature = "sqlite"))]
+            Some("sqlite") => Err(ConfigError::new(
+                "database.url uses sqlite but this build lacks the `sqlite` feature",
+            )),
+            _ => Err(ConfigError::new(format!(
+                "database.url {:?} must start with postgres://, postgresql:// or sqlite:",
+                url
+            ))),
+        }
+    }
+
+    pub fn acquire_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_timeout_secs)
+    }
+}
+
+/// Storage backend behind `database.url`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Postgres,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+/// `[gamma]` - Gamma metadata API
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GammaConfig {
+    pub base_url: String,
+    /// Retries for markets not yet visible in the API, and for rate-limited
+    /// or failed requests
+    pub max_retries: u32,
+    /// Markets enriched in parallel during backfill
+    pub concurrency: usize,
+    /// Requests per second across all Gamma calls (0 disables the limit)
+    pub requests_per_second: u32,
+}
+
+impl Default for GammaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: crate::client::gamma::GAMMA_API_BASE_URL.to_string(),
+            max_retries: 5,
+            concurrency: 4,
+            requests_per_second: 10,
+        }
+    }
+}
+
+/// `[indexer]` - indexing behaviour
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexerConfig {
+    /// Blocks behind the chain head considered final (0 indexes up to the head)
+    pub confirmations: u64,
+    /// Blocks fetched per eth_getLogs call during backfill; the checkpoint
+    /// advances after each chunk
+    pub chunk_blocks: u64,
+    /// Block headers fetched at once for the blocks holding indexed events
+    pub block_concurrency: usize,
+    /// Index exchange OrderFilled events into fills and candles
+    pub index_trades: bool,
+    /// Index Conditional Tokens and NegRiskAdapter split, merge and redeem
+    /// events into daily market stats
+    pub index_positions: bool,
+    /// Index Conditional Tokens ERC-1155 transfers for per-wallet positions
+    pub index_transfers: bool,
+    /// Seconds in-flight work may run after SIGINT/SIGTERM before it is abandoned
+    pub shutdown_grace_secs: u64,
+}
+
+impl Default for IndexerConfig {
+    fn default() -> Self {
+        Self {
+            confirmations: 0,
+            chunk_blocks: 2000,
+            block_concurrency: 8,
+            index_trades: false,
+            index_positions: false,
+            index_transfers: false,
+            shutdown_grace_secs: 30,
+        }
+    }
+}
+
+/// `[metrics]` - Prometheus endpoint for long-running commands
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    /// Address for the `/metrics` endpoint (disabled when unset)
+    pub listen_addr: Option<SocketAddr>,
+}
+
+/// `[api]` - read-only REST API
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    pub listen_addr: SocketAddr,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: SocketAddr::from(([127, 0, 0, 1], 8080)),
+        }
+    }
+}
+
+/// Whether RPC and Gamma responses are captured or served from disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureMode {
+    /// Talk to the network only
+    #[default]
+    Off,
+    /// Talk to the network and save every response
+    Record,
+    /// Serve saved responses without touching the network
+    Replay,
+}
+
+/// `[fixtures]` - record/replay of RPC and Gamma responses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FixturesConfig {
+    pub mode: FixtureMode,
+    /// Directory holding the recorded responses
+    pub dir: PathBuf,
+}
+
+impl Default for FixturesConfig {
+    fn default() -> Self {
+        Self {
+            mode: FixtureMode::Off,
+            dir: PathBuf::from("fixtures"),
+        }
+    }
+}
+
+/// `[cache]` - on-disk cache of Gamma responses
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    /// Cache directory (caching is off when unset)
+    pub dir: Option<PathBuf>,
+    /// Seconds a response carrying data stays fresh
+    pub found_ttl_secs: u64,
+    /// Seconds an empty or 404 response stays fresh (new markets appear late)
+    pub not_found_ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            found_ttl_secs: 86400,
+            not_found_ttl_secs: 600,
+        }
+    }
+}
+
+impl Config {
+    /// Load and validate configuration from all layers
+    ///
+    /// An explicit `path` must exist; otherwise `indexer.toml` is used if present.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = match path {
+            Some(path) => Self::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            None => Self::default(),
+        };
+
+        config.apply_overrides(|name| std::env::var(name).ok())?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Parse a TOML config file
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::new(format!("cannot read {}: {}", path.display(), e)))?;
+
+        toml::from_str(&contents)
+            .map_err(|e| ConfigError::new(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Apply environment overrides, looking variables up through `var`
+    pub fn apply_overrides(
+        &mut self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        if let Some(v) = var("ALCHEMY_API_KEY") {
+            self.rpc.api_key = Some(v);
+        }
+        if let Some(v) = var("DATABASE_URL") {
+            self.database.url = Some(v);
+        }
+        if let Some(v) = var("INDEXER_RPC_PROVIDER") {
+            self.rpc.provider = parse_enum("INDEXER_RPC_PROVIDER", &v)?;
+        }
+        if let Some(v) = var("INDEXER_RPC_CHAIN") {
+            self.rpc.chain = parse_enum("INDEXER_RPC_CHAIN", &v)?;
+        }
+        if let Some(v) = var("INDEXER_RPC_HTTP_URL") {
+            self.rpc.http_url = Some(v);
+        }
+        if let Some(v) = var("INDEXER_RPC_WS_URL") {
+            self.rpc.ws_url = Some(v);
+        }
+        if let Some(v) = var("INDEXER_CONTRACTS_CTF_EXCHANGE") {
+            self.contracts.ctf_exchange = parse_value("INDEXER_CONTRACTS_CTF_EXCHANGE", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CONTRACTS_CTF") {
+            self.contracts.ctf = parse_value("INDEXER_CONTRACTS_CTF", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CONTRACTS_NEG_RISK_EXCHANGE") {
+            self.contracts.neg_risk_exchange =
+                parse_value("INDEXER_CONTRACTS_NEG_RISK_EXCHANGE", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CONTRACTS_NEG_RISK_ADAPTER") {
+            self.contracts.neg_risk_adapter =
+                parse_value("INDEXER_CONTRACTS_NEG_RISK_ADAPTER", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CONTRACTS_UMA_CTF_ADAPTERS") {
+            self.contracts.uma_ctf_adapters = v
+                .split(',')
+                .map(|address| parse_value("INDEXER_CONTRACTS_UMA_CTF_ADAPTERS", address.trim()))
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(v) = var("INDEXER_DATABASE_MAX_CONNECTIONS") {
+            self.database.max_connections = parse_value("INDEXER_DATABASE_MAX_CONNECTIONS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_DATABASE_ACQUIRE_TIMEOUT_SECS") {
+            self.database.acquire_timeout_secs =
+                parse_value("INDEXER_DATABASE_ACQUIRE_TIMEOUT_SECS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_GAMMA_BASE_URL") {
+            self.gamma.base_url = v;
+        }
+        if let Some(v) = var("INDEXER_GAMMA_MAX_RETRIES") {
+            self.gamma.max_retries = parse_value("INDEXER_GAMMA_MAX_RETRIES", &v)?;
+        }
+        if let Some(v) = var("INDEXER_GAMMA_CONCURRENCY") {
+            self.gamma.concurrency = parse_value("INDEXER_GAMMA_CONCURRENCY", &v)?;
+        }
+        if let Some(v) = var("INDEXER_GAMMA_REQUESTS_PER_SECOND") {
+            self.gamma.requests_per_second = parse_value("INDEXER_GAMMA_REQUESTS_PER_SECOND", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CONFIRMATIONS") {
+            self.indexer.confirmations = parse_value("INDEXER_CONFIRMATIONS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CHUNK_BLOCKS") {
+            self.indexer.chunk_blocks = parse_value("INDEXER_CHUNK_BLOCKS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_BLOCK_CONCURRENCY") {
+            self.indexer.block_concurrency = parse_value("INDEXER_BLOCK_CONCURRENCY", &v)?;
+        }
+        if let Some(v) = var("INDEXER_INDEX_TRADES") {
+            self.indexer.index_trades = parse_value("INDEXER_INDEX_TRADES", &v)?;
+        }
+        if let Some(v) = var("INDEXER_INDEX_POSITIONS") {
+            self.indexer.index_positions = parse_value("INDEXER_INDEX_POSITIONS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_INDEX_TRANSFERS") {
+            self.indexer.index_transfers = parse_value("INDEXER_INDEX_TRANSFERS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_SHUTDOWN_GRACE_SECS") {
+            self.indexer.shutdown_grace_secs = parse_value("INDEXER_SHUTDOWN_GRACE_SECS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_METRICS_LISTEN_ADDR") {
+            self.metrics.listen_addr = Some(parse_value("INDEXER_METRICS_LISTEN_ADDR", &v)?);
+        }
+        if let Some(v) = var("INDEXER_API_LISTEN_ADDR") {
+            self.api.listen_addr = parse_value("INDEXER_API_LISTEN_ADDR", &v)?;
+        }
+        if let Some(v) = var("INDEXER_FIXTURES_MODE") {
+            self.fixtures.mode = parse_enum("INDEXER_FIXTURES_MODE", &v)?;
+        }
+        if let Some(v) = var("INDEXER_FIXTURES_DIR") {
+            self.fixtures.dir = PathBuf::from(v);
+        }
+        if let Some(v) = var("INDEXER_CACHE_DIR") {
+            self.cache.dir = Some(PathBuf::from(v));
+        }
+        if let Some(v) = var("INDEXER_CACHE_FOUND_TTL_SECS") {
+            self.cache.found_ttl_secs = parse_value("INDEXER_CACHE_FOUND_TTL_SECS", &v)?;
+        }
+        if let Some(v) = var("INDEXER_CACHE_NOT_FOUND_TTL_SECS") {
+            self.cache.not_found_ttl_secs = parse_value("INDEXER_CACHE_NOT_FOUND_TTL_SECS", &v)?;
+        }
+
+        Ok(())
+    }
+
+    /// Check values that would otherwise fail deep inside a command
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self.rpc.provider {
+            ProviderKind::Alchemy => {
+                if self.rpc.api_key.as_deref().is_none_or(str::is_empty) {
+                    return Err(ConfigError::new(
+                        "rpc.api_key (env: ALCHEMY_API_KEY) is required when \
+                         rpc.provider = \"alchemy\"",
+                    ));
+                }
+            }
+            ProviderKind::Custom => {
+                let http_url = self.rpc.http_url.as_deref().ok_or_else(|| {
+                    ConfigError::new("rpc.http_url is required when rpc.provider = \"custom\"")
+                })?;
+                validate_url("rpc.http_url", http_url, &["http", "https"])?;
+            }
+        }
+        if let Some(ws_url) = self.rpc.ws_url.as_deref() {
+            validate_url("rpc.ws_url", ws_url, &["ws", "wss"])?;
+        }
+
+        if self.contracts.ctf_exchange.is_zero() {
+            return Err(ConfigError::new(
+                "contracts.ctf_exchange must not be the zero address",
+            ));
+        }
+        if self.contracts.ctf.is_zero() {
+            return Err(ConfigError::new(
+                "contracts.ctf must not be the zero address",
+            ));
+        }
+        if self.contracts.neg_risk_exchange.is_zero() {
+            return Err(ConfigError::new(
+                "contracts.neg_risk_exchange must not be the zero address",
+            ));
+        }
+        if self.contracts.neg_risk_adapter.is_zero() {
+            return Err(ConfigError::new(
+                "contracts.neg_risk_adapter must not be the zero address",
+            ));
+        }
+        if self.contracts.uma_ctf_adapters.is_empty() {
+            return Err(ConfigError::new(
+                "contracts.uma_ctf_adapters must list at least one adapter",
+            ));
+        }
+        for (i, adapter) in self.contracts.uma_ctf_adapters.iter().enumerate() {
+            if adapter.is_zero() {
+                return Err(ConfigError::new(
+                    "contracts.uma_ctf_adapters must not contain the zero address",
+                ));
+            }
+            if self.contracts.uma_ctf_adapters[..i].contains(adapter) {
+                return Err(ConfigError::new(format!(
+                    "contracts.uma_ctf_adapters lists {:?} twice",
+                    adapter
+                )));
+            }
+        }
+
+        if self.database.url.is_some() {
+            self.database.backend()?;
+        }
+        if self.database.max_connections == 0 {
+            return Err(ConfigError::new(
+                "database.max_connections must be at least 1",
+            ));
+        }
+        if self.database.acquire_timeout_secs == 0 {
+            return Err(ConfigError::new(
+                "database.acquire_timeout_secs must be at least 1",
+            ));
+        }
+
+        validate_url("gamma.base_url", &self.gamma.base_url, &["http", "https"])?;
+        if self.gamma.max_retries > MAX_GAMMA_RETRIES {
+            return Err(ConfigError::new(format!(
+                "gamma.max_retries must be at most {}",
+                MAX_GAMMA_RETRIES
+            )));
+        }
+        if self.gamma.concurrency == 0 {
+            return Err(ConfigError::new("gamma.concurrency must be at least 1"));
+        }
+        if self.indexer.chunk_blocks == 0 {
+            return Err(ConfigError::new("indexer.chunk_blocks must be at least 1"));
+        }
+        if self.indexer.block_concurrency == 0 {
+            return Err(ConfigError::new(
+                "indexer.block_concurrency must be at least 1",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse an environment value with `FromStr`
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, ConfigError>
+where
+    T::Err: fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| ConfigError::new(format!("{}={:?}: {}", name, value, e)))
+}
+
+/// Parse an environment value into a unit enum using its serde names
+fn parse_enum<'de, T: Deserialize<'de>>(name: &str, value: &'de str) -> Result<T, ConfigError> {
+    T::deserialize(StrDeserializer::<ValueError>::new(value))
+        .map_err(|e| ConfigError::new(format!("{}={:?}: {}", name, value, e)))
+}
+
+fn validate_url(key: &str, value: &str, schemes: &[&str]) -> Result<(), ConfigError> {
+    let url =
+        Url::parse(value).map_err(|e| ConfigError::new(format!("{} {:?}: {}", key, value, e)))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(ConfigError::new(format!(
+            "{} must use one of the schemes {:?}, got {:?}",
+            key,
+            schemes,
+            url.scheme()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn with_env(config: &mut Config, vars: &[(&str, &str)]) -> Result<(), ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        config.apply_overrides(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_defaults_need_only_an_alchemy_key() {
+        let mut config = Config::default();
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("rpc.api_key") && error.contains("ALCHEMY_API_KEY"));
+        with_env(&mut config, &[("ALCHEMY_API_KEY", "")]).unwrap();
+        assert!(config.validate().is_err());
+        with_env(&mut config, &[("ALCHEMY_API_KEY", "key")]).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.database.max_connections, 5);
+        assert_eq!(config.gamma.max_retries, 5);
+        assert!(config.database.url().is_err());
+    }
+
+    #[test]
+    fn test_file_then_env_layering() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [rpc]
+            provider = "custom"
+            http_url = "http://localhost:8545"
+
+            [gamma]
+            concurrency = 8
+            max_retries = 2
+            "#,
+        )
+        .unwrap();
+
+        with_env(
+            &mut config,
+            &[
+                ("INDEXER_GAMMA_CONCURRENCY", "2"),
+                ("DATABASE_URL", "postgres://db"),
+                (
+                    "INDEXER_CONTRACTS_UMA_CTF_ADAPTERS",
+                    "0x6A9D222616C90FcA5754cd1333cFD9b7fb6a4F74",
+                ),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(config.rpc.provider, ProviderKind::Custom);
+        assert_eq!(config.gamma.concurrency, 2);
+        assert_eq!(config.gamma.max_retries, 2);
+        assert_eq!(config.database.url().unwrap(), "postgres://db");
+        assert_eq!(config.contracts.uma_ctf_adapters.len(), 1);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let mut config = Config::default();
+        assert!(with_env(&mut config, &[("INDEXER_RPC_CHAIN", "mainnet")]).is_err());
+        assert!(with_env(&mut config, &[("INDEXER_GAMMA_CONCURRENCY", "many")]).is_err());
+
+        config.rpc.provider = ProviderKind::Custom;
+        assert!(config.validate().is_err());
+        config.rpc.provider = ProviderKind::Alchemy;
+        config.rpc.api_key = Some("key".to_string());
+        assert!(config.validate().is_ok());
+
+        let adapter = config.contracts.uma_ctf_adapters[0];
+        config.contracts.uma_ctf_adapters.push(adapter);
+        assert!(config.validate().is_err());
+        config.contracts.uma_ctf_adapters.clear();
+        assert!(config.validate().is_err());
+        config.rpc.provider = ProviderKind::Custom;
+
+        config.rpc.http_url = Some("ws://localhost:8545".to_string());
+        assert!(config.validate().is_err());
+
+        assert!(toml::from_str::<Config>("[database]\nmax_conections = 5").is_err());
+    }
+    #[test]
+    fn test_database_backend_from_url() {
+        let mut config = Config::default();
+        config.rpc.api_key = Some("key".to_string());
+        config.database.url = Some("postgresql://user@localhost/polymarket".to_string());
+        assert_eq!(
+            config.database.backend().unwrap(),
+            DatabaseBackend::Postgres
+        );
+
+        config.database.url = Some("sqlite://markets.db".to_string());
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "sqlite"));
+
+        config.database.url = Some("mysql://localhost/polymarket".to_string());
+        assert!(config.validate().is_err());
+    }
+}
@@ -0,0 +1,71 @@
+// Data anomaly database operations
+
+use crate::db::models::DataAnomaly;
+use crate::error::Result;
+use crate::metrics;
+use sqlx::PgPool;
+
+/// Record indexed data that contradicts what is already stored
+pub async fn record_anomaly(
+    pool: &PgPool,
+    condition_id: Option<&str>,
+    kind: &str,
+    detail: &str,
+    block_number: Option<u64>,
+) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["record_anomaly"])
+        .start_timer();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO data_anomalies (condition_id, kind, detail, block_number)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        condition_id,
+        kind,
+        detail,
+        block_number.map(|b| b as i64)
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Get the anomalies recorded for a market, oldest first
+pub async fn get_anomalies_for_market(
+    pool: &PgPool,
+    condition_id: &str,
+) -> Result<Vec<DataAnomaly>> {
+    let anomalies = sqlx::query_as!(
+        DataAnomaly,
+        r#"
+        SELECT id, condition_id, kind, detail, block_number, created_at
+        FROM data_anomalies
+        WHERE condition_id = $1
+        ORDER BY id ASC
+        "#,
+        condition_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(anomalies)
+}
+
+/// Count recorded anomalies by kind, most frequent first
+pub async fn count_anomalies_by_kind(pool: &PgPool) -> Result<Vec<(String, i64)>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT kind, COUNT(*) AS "count!"
+        FROM data_anomalies
+        GROUP BY kind
+        ORDER BY "count!" DESC, kind ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.kind, r.count)).collect())
+}
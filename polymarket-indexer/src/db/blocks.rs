@@ -0,0 +1,80 @@
+// Block header database operations
+
+use crate::blocks::{BlockHeader, ReplacedBlock};
+use crate::db::models::Block;
+use crate::error::Result;
+use crate::metrics;
+use sqlx::PgPool;
+
+/// Insert or replace block headers, setting `registered_at` on markets
+/// registered in those blocks
+///
+/// Returns the stored blocks whose hash changed (reorganized blocks).
+pub async fn upsert_blocks(pool: &PgPool, headers: &[BlockHeader]) -> Result<Vec<ReplacedBlock>> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["upsert_blocks"])
+        .start_timer();
+    let mut replaced = Vec::new();
+    let mut tx = pool.begin().await?;
+
+    for header in headers {
+        let hash = header.hash_hex();
+        let previous = sqlx::query_scalar!(
+            r#"
+            WITH previous AS (SELECT hash FROM blocks WHERE number = $1)
+            INSERT INTO blocks (number, hash, parent_hash, timestamp)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (number) DO UPDATE SET
+                hash = EXCLUDED.hash,
+                parent_hash = EXCLUDED.parent_hash,
+                timestamp = EXCLUDED.timestamp
+            RETURNING (SELECT hash FROM previous) AS previous_hash
+            "#,
+            header.number as i64,
+            hash,
+            header.parent_hash_hex(),
+            header.time()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if let Some(previous) = previous.filter(|previous| *previous != hash) {
+            replaced.push(ReplacedBlock {
+                number: header.number,
+                hash: previous,
+                new_hash: hash,
+            });
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE markets SET registered_at = $2
+            WHERE block_number = $1 AND registered_at IS DISTINCT FROM $2
+            "#,
+            header.number as i64,
+            header.time()
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(replaced)
+}
+
+/// Get a stored block header by number
+pub async fn get_block(pool: &PgPool, number: u64) -> Result<Option<Block>> {
+    let block = sqlx::query_as!(
+        Block,
+        r#"
+        SELECT number, hash, parent_hash, timestamp, created_at
+        FROM blocks
+        WHERE number = $1
+        "#,
+        number as i64
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(block)
+}
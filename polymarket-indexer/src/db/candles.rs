@@ -0,0 +1,169 @@
+// Candle database operations
+//
+// Token IDs are NUMERIC columns bound and selected as text, as in `tokens`.
+
+use crate::candles::Resolution;
+use crate::db::models::Candle;
+use crate::error::Result;
+use crate::metrics;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+/// Merge candles built from new fills into the stored ones
+///
+/// Runs inside `fills::record_fills`; see `Candle::merge` for the rules.
+pub(crate) async fn merge_candles(conn: &mut PgConnection, candles: &[Candle]) -> Result<()> {
+    for candle in candles {
+        sqlx::query!(
+            r#"
+            INSERT INTO candles (
+                token_id, resolution, bucket_start, open, high, low, close,
+                volume, notional, trades,
+                first_block, first_log_index, last_block, last_log_index
+            ) VALUES ($1::TEXT::NUMERIC, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT (token_id, resolution, bucket_start) DO UPDATE SET
+                open = CASE
+                    WHEN (EXCLUDED.first_block, EXCLUDED.first_log_index)
+                        < (candles.first_block, candles.first_log_index)
+                    THEN EXCLUDED.open ELSE candles.open END,
+                first_block = CASE
+                    WHEN (EXCLUDED.first_block, EXCLUDED.first_log_index)
+                        < (candles.first_block, candles.first_log_index)
+                    THEN EXCLUDED.first_block ELSE candles.first_block END,
+                first_log_index = CASE
+                    WHEN (EXCLUDED.first_block, EXCLUDED.first_log_index)
+                        < (candles.first_block, candles.first_log_index)
+                    THEN EXCLUDED.first_log_index ELSE candles.first_log_index END,
+                close = CASE
+                    WHEN (EXCLUDED.last_block, EXCLUDED.last_log_index)
+                        > (candles.last_block, candles.last_log_index)
+                    THEN EXCLUDED.close ELSE candles.close END,
+                last_block = CASE
+                    WHEN (EXCLUDED.last_block, EXCLUDED.last_log_index)
+                        > (candles.last_block, candles.last_log_index)
+                    THEN EXCLUDED.last_block ELSE candles.last_block END,
+                last_log_index = CASE
+                    WHEN (EXCLUDED.last_block, EXCLUDED.last_log_index)
+                        > (candles.last_block, candles.last_log_index)
+                    THEN EXCLUDED.last_log_index ELSE candles.last_log_index END,
+                high = GREATEST(candles.high, EXCLUDED.high),
+                low = LEAST(candles.low, EXCLUDED.low),
+                volume = candles.volume + EXCLUDED.volume,
+                notional = candles.notional + EXCLUDED.notional,
+                trades = candles.trades + EXCLUDED.trades,
+                updated_at = NOW()
+            "#,
+            candle.token_id,
+            candle.resolution,
+            candle.bucket_start,
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+            candle.volume,
+            candle.notional,
+            candle.trades,
+            candle.first_block,
+            candle.first_log_index,
+            candle.last_block,
+            candle.last_log_index
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Insert candles, overwriting stored ones with the same key
+pub async fn replace_candles(pool: &PgPool, candles: &[Candle]) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["replace_candles"])
+        .start_timer();
+    let mut tx = pool.begin().await?;
+
+    for candle in candles {
+        sqlx::query!(
+            r#"
+            INSERT INTO candles (
+                token_id, resolution, bucket_start, open, high, low, close,
+                volume, notional, trades,
+                first_block, first_log_index, last_block, last_log_index
+            ) VALUES ($1::TEXT::NUMERIC, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT (token_id, resolution, bucket_start) DO UPDATE SET
+                open = EXCLUDED.open,
+                high = EXCLUDED.high,
+                low = EXCLUDED.low,
+                close = EXCLUDED.close,
+                volume = EXCLUDED.volume,
+                notional = EXCLUDED.notional,
+                trades = EXCLUDED.trades,
+                first_block = EXCLUDED.first_block,
+                first_log_index = EXCLUDED.first_log_index,
+                last_block = EXCLUDED.last_block,
+                last_log_index = EXCLUDED.last_log_index,
+                updated_at = NOW()
+            "#,
+            candle.token_id,
+            candle.resolution,
+            candle.bucket_start,
+            candle.open,
+            candle.high,
+            candle.low,
+            candle.close,
+            candle.volume,
+            candle.notional,
+            candle.trades,
+            candle.first_block,
+            candle.first_log_index,
+            candle.last_block,
+            candle.last_log_index
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Get a token's candles at one resolution with a bucket start in
+/// `[start, end)`, oldest first
+///
+/// Open bounds are unlimited; at most `limit` candles are returned, the most
+/// recent ones when the range holds more.
+pub async fn get_candles(
+    pool: &PgPool,
+    token_id: &str,
+    resolution: Resolution,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<Vec<Candle>> {
+    let mut candles = sqlx::query_as!(
+        Candle,
+        r#"
+        SELECT
+            token_id::TEXT AS "token_id!", resolution, bucket_start,
+            open, high, low, close, volume, notional, trades,
+            first_block, first_log_index, last_block, last_log_index
+        FROM candles
+        WHERE token_id = $1::TEXT::NUMERIC
+          AND resolution = $2
+          AND ($3::TIMESTAMPTZ IS NULL OR bucket_start >= $3)
+          AND ($4::TIMESTAMPTZ IS NULL OR bucket_start < $4)
+        ORDER BY bucket_start DESC
+        LIMIT $5
+        "#,
+        token_id,
+        resolution.as_str(),
+        start,
+        end,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    candles.reverse();
+    Ok(candles)
+}
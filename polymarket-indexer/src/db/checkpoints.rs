@@ -0,0 +1,43 @@
+// Checkpoint database operations
+
+use crate::error::Result;
+use crate::metrics;
+use sqlx::PgPool;
+
+/// Get the last indexed block recorded under `name`
+pub async fn get_checkpoint(pool: &PgPool, name: &str) -> Result<Option<u64>> {
+    let block = sqlx::query_scalar!(
+        r#"
+        SELECT block_number FROM checkpoints
+        WHERE name = $1
+        "#,
+        name
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(block.map(|b| b as u64))
+}
+
+/// Record `block_number` as the last indexed block under `name`
+pub async fn set_checkpoint(pool: &PgPool, name: &str, block_number: u64) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["set_checkpoint"])
+        .start_timer();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO checkpoints (name, block_number)
+        VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE SET
+            block_number = EXCLUDED.block_number,
+            updated_at = NOW()
+        "#,
+        name,
+        block_number as i64
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
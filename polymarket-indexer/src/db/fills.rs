@@ -0,0 +1,128 @@
+// Exchange fill database operations
+
+use crate::candles::{self, Tick};
+use crate::db::candles::merge_candles;
+use crate::db::market_stats::merge_candle_volume;
+use crate::db::models::Fill as FillRow;
+use crate::error::Result;
+use crate::metrics;
+use crate::polymarket::trades::Fill;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Record exchange fills and merge their trades into the candles and the
+/// daily stats of markets with known outcome tokens
+///
+/// Fills already recorded are skipped and don't count twice. Returns the
+/// number of new fills.
+pub async fn record_fills(pool: &PgPool, fills: &[Fill]) -> Result<usize> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["record_fills"])
+        .start_timer();
+    let mut ticks = Vec::new();
+    let mut inserted = 0;
+    let mut tx = pool.begin().await?;
+
+    for fill in fills {
+        let trade = fill.trade();
+        let new = sqlx::query_scalar!(
+            r#"
+            INSERT INTO fills (
+                tx_hash, log_index, exchange, order_hash, maker, taker,
+                maker_asset_id, taker_asset_id, maker_amount_filled, taker_amount_filled, fee,
+                token_id, side, price, size, notional, block_number, block_time
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6,
+                $7::TEXT::NUMERIC, $8::TEXT::NUMERIC, $9::TEXT::NUMERIC, $10::TEXT::NUMERIC,
+                $11::TEXT::NUMERIC, $12::TEXT::NUMERIC, $13, $14, $15, $16, $17, $18
+            )
+            ON CONFLICT (tx_hash, log_index) DO NOTHING
+            RETURNING log_index
+            "#,
+            fill.tx_hash,
+            fill.log_index as i64,
+            format!("{:?}", fill.exchange),
+            fill.order_hash_hex(),
+            format!("{:?}", fill.maker),
+            format!("{:?}", fill.taker),
+            fill.maker_asset_id.to_string(),
+            fill.taker_asset_id.to_string(),
+            fill.maker_amount_filled.to_string(),
+            fill.taker_amount_filled.to_string(),
+            fill.fee.to_string(),
+            trade.map(|t| t.token_id.to_string()),
+            trade.map(|t| t.side.as_str()),
+            trade.map(|t| t.price),
+            trade.map(|t| t.size),
+            trade.map(|t| t.notional),
+            fill.block_number as i64,
+            fill.block_time
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if new.is_some() {
+            inserted += 1;
+            ticks.extend(Tick::from_fill(fill));
+        }
+    }
+
+    let candles = candles::aggregate(&ticks);
+    merge_candles(&mut tx, &candles).await?;
+    merge_candle_volume(&mut tx, &candles).await?;
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Block times of the first and last stored fills in a block range
+pub async fn get_fill_time_range(
+    pool: &PgPool,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT MIN(block_time) AS first, MAX(block_time) AS last
+        FROM fills
+        WHERE block_number BETWEEN $1 AND $2
+        "#,
+        from_block as i64,
+        to_block as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.first.zip(row.last))
+}
+
+/// Get the fills with a block time in `[start, end)`, in chain order
+pub async fn get_fills_between(
+    pool: &PgPool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<FillRow>> {
+    let fills = sqlx::query_as!(
+        FillRow,
+        r#"
+        SELECT
+            tx_hash, log_index, exchange, order_hash, maker, taker,
+            maker_asset_id::TEXT AS "maker_asset_id!",
+            taker_asset_id::TEXT AS "taker_asset_id!",
+            maker_amount_filled::TEXT AS "maker_amount_filled!",
+            taker_amount_filled::TEXT AS "taker_amount_filled!",
+            fee::TEXT AS "fee!",
+            token_id::TEXT AS token_id, side, price, size, notional,
+            block_number, block_time, created_at
+        FROM fills
+        WHERE block_time >= $1 AND block_time < $2
+        ORDER BY block_number ASC, log_index ASC
+        "#,
+        start,
+        end
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(fills)
+}
@@ -0,0 +1,127 @@
+// Daily market stats database operations
+//
+// Open interest is not stored; `get_market_series` computes it as the
+// running total of each day's net collateral flow.
+
+use crate::candles::Resolution;
+use crate::db::models::{Candle, MarketDailyStats, MarketSeriesPoint};
+use crate::error::Result;
+use crate::metrics;
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+/// Add rows to the stored ones with the same market and day
+pub(crate) async fn merge_market_stats(
+    conn: &mut PgConnection,
+    stats: &[MarketDailyStats],
+) -> Result<()> {
+    for row in stats {
+        sqlx::query!(
+            r#"
+            INSERT INTO market_daily_stats (
+                condition_id, day, split_amount, merge_amount, redeem_amount, volume, trades
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (condition_id, day) DO UPDATE SET
+                split_amount = market_daily_stats.split_amount + EXCLUDED.split_amount,
+                merge_amount = market_daily_stats.merge_amount + EXCLUDED.merge_amount,
+                redeem_amount = market_daily_stats.redeem_amount + EXCLUDED.redeem_amount,
+                volume = market_daily_stats.volume + EXCLUDED.volume,
+                trades = market_daily_stats.trades + EXCLUDED.trades,
+                updated_at = NOW()
+            "#,
+            row.condition_id,
+            row.day,
+            row.split_amount,
+            row.merge_amount,
+            row.redeem_amount,
+            row.volume,
+            row.trades
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Add the volume of daily candles to their markets' stats
+///
+/// Candles of tokens without a stored market are left out.
+pub(crate) async fn merge_candle_volume(conn: &mut PgConnection, candles: &[Candle]) -> Result<()> {
+    let mut stats = Vec::new();
+    for candle in candles {
+        if candle.resolution != Resolution::Day.as_str() {
+            continue;
+        }
+        let condition_id = sqlx::query_scalar!(
+            "SELECT condition_id FROM outcome_tokens WHERE token_id = $1::TEXT::NUMERIC",
+            candle.token_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+        if let Some(condition_id) = condition_id {
+            stats.push(MarketDailyStats::from_candle(condition_id, candle));
+        }
+    }
+
+    merge_market_stats(conn, &stats).await
+}
+
+/// Replace every market's stats for the day starting at `day`
+pub async fn replace_market_stats(
+    pool: &PgPool,
+    day: DateTime<Utc>,
+    stats: &[MarketDailyStats],
+) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["replace_market_stats"])
+        .start_timer();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM market_daily_stats WHERE day = $1", day)
+        .execute(&mut *tx)
+        .await?;
+    merge_market_stats(&mut tx, stats).await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Get a market's daily series with a day in `[start, end)`, oldest first
+///
+/// Open bounds are unlimited. Open interest counts every earlier day, not
+/// only those in range.
+pub async fn get_market_series(
+    pool: &PgPool,
+    condition_id: &str,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+) -> Result<Vec<MarketSeriesPoint>> {
+    let series = sqlx::query_as!(
+        MarketSeriesPoint,
+        r#"
+        SELECT
+            day AS "day!", open_interest AS "open_interest!", volume AS "volume!",
+            trades AS "trades!", split_amount AS "split_amount!",
+            merge_amount AS "merge_amount!", redeem_amount AS "redeem_amount!"
+        FROM (
+            SELECT
+                day, volume, trades, split_amount, merge_amount, redeem_amount,
+                SUM(split_amount - merge_amount - redeem_amount)
+                    OVER (ORDER BY day)::BIGINT AS open_interest
+            FROM market_daily_stats
+            WHERE condition_id = $1
+        ) series
+        WHERE ($2::TIMESTAMPTZ IS NULL OR day >= $2)
+          AND ($3::TIMESTAMPTZ IS NULL OR day < $3)
+        ORDER BY day ASC
+        "#,
+        condition_id,
+        start,
+        end
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(series)
+}
@@ -0,0 +1,82 @@
+// Market lifecycle status database operations
+
+use crate::db::models::MarketStatusTransition;
+use crate::error::Result;
+use crate::metrics;
+use crate::polymarket::lifecycle::{MarketStatus, StatusSignal};
+use sqlx::PgPool;
+
+/// Move a market from `from` to `to` and record the transition
+///
+/// The update only applies while the market is still `from`, so a status
+/// changed concurrently is never overwritten: returns `false` when the
+/// market is missing or no longer `from`.
+pub async fn set_market_status(
+    pool: &PgPool,
+    condition_id: &str,
+    from: MarketStatus,
+    to: MarketStatus,
+    signal: StatusSignal,
+    block_number: Option<u64>,
+) -> Result<bool> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["set_market_status"])
+        .start_timer();
+    let mut tx = pool.begin().await?;
+
+    let updated = sqlx::query!(
+        r#"
+        UPDATE markets SET status = $3, status_updated_at = NOW()
+        WHERE condition_id = $1 AND status = $2
+        "#,
+        condition_id,
+        from.as_str(),
+        to.as_str()
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !updated {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO market_status_transitions (
+            condition_id, from_status, to_status, source, block_number
+        ) VALUES ($1, $2, $3, $4, $5)
+        "#,
+        condition_id,
+        from.as_str(),
+        to.as_str(),
+        signal.source(),
+        block_number.map(|b| b as i64)
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Get a market's status transitions, oldest first
+pub async fn get_status_transitions(
+    pool: &PgPool,
+    condition_id: &str,
+) -> Result<Vec<MarketStatusTransition>> {
+    let transitions = sqlx::query_as!(
+        MarketStatusTransition,
+        r#"
+        SELECT condition_id, from_status, to_status, source, block_number, transitioned_at
+        FROM market_status_transitions
+        WHERE condition_id = $1
+        ORDER BY id ASC
+        "#,
+        condition_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transitions)
+}
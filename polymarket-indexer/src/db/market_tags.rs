@@ -1,8 +1,9 @@
 // Market tags database operations
 
-use crate::db::models::Tag as DbTag;
+use crate::db::models::{Tag as DbTag, TagSummary};
+use crate::error::Result;
+use crate::metrics;
 use crate::polymarket::market::Tag as ApiTag;
-use eyre::Result;
 use sqlx::PgPool;
 
 /// Insert tags for a market
@@ -11,6 +12,10 @@ use sqlx::PgPool;
 /// 1. Upsert tags into the tags table (idempotent)
 /// 2. Insert relationships into market_tags join table (idempotent)
 pub async fn insert_market_tags(pool: &PgPool, condition_id: &str, tags: &[ApiTag]) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["insert_market_tags"])
+        .start_timer();
+
     for tag in tags {
         // Step 1: Upsert into tags table
         sqlx::query!(
@@ -42,6 +47,19 @@ pub async fn insert_market_tags(pool: &PgPool, condition_id: &str, tags: &[ApiTa
         .await?;
     }
 
+    // Bump the market so incremental exports pick up tag changes
+    if !tags.is_empty() {
+        sqlx::query!(
+            r#"
+            UPDATE markets SET updated_at = NOW()
+            WHERE condition_id = $1
+            "#,
+            condition_id
+        )
+        .execute(pool)
+        .await?;
+    }
+
     Ok(())
 }
 
@@ -63,3 +81,21 @@ pub async fn get_tags_for_market(pool: &PgPool, condition_id: &str) -> Result<Ve
 
     Ok(tags)
 }
+
+/// List all tags with the number of markets carrying each
+pub async fn list_tags(pool: &PgPool) -> Result<Vec<TagSummary>> {
+    let tags = sqlx::query_as!(
+        TagSummary,
+        r#"
+        SELECT t.pm_tag_id, t.label, t.slug, COUNT(mt.condition_id) as "market_count!"
+        FROM tags t
+        LEFT JOIN market_tags mt ON mt.pm_tag_id = t.pm_tag_id
+        GROUP BY t.pm_tag_id, t.label, t.slug
+        ORDER BY t.label
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tags)
+}
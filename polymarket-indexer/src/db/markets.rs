@@ -1,37 +1,55 @@
 // Market database operations
 
-use crate::db::models::Market;
+use crate::db::models::{Market, MarketExportRow, MarketSearchResult};
+use crate::db::tokens;
+use crate::error::Result;
+use crate::metrics;
 use crate::polymarket::events::TokenRegistered;
+use crate::polymarket::lifecycle::MarketStatus;
 use crate::polymarket::market::MarketMetadata;
-use eyre::Result;
+use chrono::{DateTime, Utc};
+use futures::{Stream, TryStreamExt};
 use sqlx::PgPool;
 
 /// Insert or update a market with on-chain data and optional metadata
 ///
 /// This is idempotent - safe to call multiple times with the same condition_id.
-/// If metadata is provided, it will update the existing record.
+/// If metadata is provided, it will update the existing record. The market's
+/// outcome tokens are written in the same transaction.
 pub async fn upsert_market(
     pool: &PgPool,
     event: &TokenRegistered,
     metadata: Option<&MarketMetadata>,
 ) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["upsert_market"])
+        .start_timer();
     let outcomes_json = metadata.and_then(|m| serde_json::to_value(&m.outcomes).ok());
+    let mut tx = pool.begin().await?;
 
     sqlx::query!(
         r#"
         INSERT INTO markets (
             condition_id, token0, token1, block_number, tx_hash,
             question, slug, pm_market_id, outcomes, start_date, end_date,
-            metadata_fetched_at
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            metadata_fetched_at, start_at, registered_at
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
+            (SELECT timestamp FROM blocks WHERE number = $4)
+        )
         ON CONFLICT (condition_id) DO UPDATE SET
             question = COALESCE(EXCLUDED.question, markets.question),
             slug = COALESCE(EXCLUDED.slug, markets.slug),
             pm_market_id = COALESCE(EXCLUDED.pm_market_id, markets.pm_market_id),
             outcomes = COALESCE(EXCLUDED.outcomes, markets.outcomes),
             start_date = COALESCE(EXCLUDED.start_date, markets.start_date),
+            start_at = CASE
+                WHEN EXCLUDED.start_date IS NULL THEN markets.start_at
+                ELSE EXCLUDED.start_at
+            END,
             end_date = COALESCE(EXCLUDED.end_date, markets.end_date),
             metadata_fetched_at = COALESCE(EXCLUDED.metadata_fetched_at, markets.metadata_fetched_at),
+            registered_at = COALESCE(markets.registered_at, EXCLUDED.registered_at),
             updated_at = NOW()
         "#,
         event.condition_id_hex(),
@@ -49,11 +67,60 @@ pub async fn upsert_market(
             Some(chrono::Utc::now())
         } else {
             None
-        }
+        },
+        metadata.and_then(MarketMetadata::start_at)
     )
-    .execute(pool)
+    .execute(&mut *tx)
     .await?;
 
+    tokens::upsert_outcome_tokens(&mut tx, event, metadata.map(|m| m.outcomes.as_slice())).await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Set Gamma metadata on an existing market
+///
+/// Used by `enrich` for markets that were indexed before metadata was available.
+pub async fn update_market_metadata(
+    pool: &PgPool,
+    condition_id: &str,
+    metadata: &MarketMetadata,
+) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["update_market_metadata"])
+        .start_timer();
+    let outcomes_json = serde_json::to_value(&metadata.outcomes).ok();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE markets SET
+            question = $2,
+            slug = $3,
+            pm_market_id = COALESCE($4, pm_market_id),
+            outcomes = COALESCE($5, outcomes),
+            start_date = COALESCE($6, start_date),
+            start_at = CASE WHEN $6::TEXT IS NULL THEN start_at ELSE $8 END,
+            end_date = COALESCE($7, end_date),
+            metadata_fetched_at = NOW()
+        WHERE condition_id = $1
+        "#,
+        condition_id,
+        metadata.question,
+        metadata.slug,
+        metadata.id.as_deref(),
+        outcomes_json,
+        metadata.start_date.as_deref(),
+        metadata.end_date.as_deref(),
+        metadata.start_at()
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tokens::set_outcome_labels(&mut tx, condition_id, &metadata.outcomes).await?;
+
+    tx.commit().await?;
     Ok(())
 }
 
@@ -65,7 +132,12 @@ pub async fn get_market_by_condition_id(
     let market = sqlx::query_as!(
         Market,
         r#"
-        SELECT * FROM markets
+        SELECT
+            condition_id, token0, token1, block_number, tx_hash,
+            question, slug, pm_market_id, outcomes, start_date, end_date,
+            created_at, updated_at, metadata_fetched_at, status, status_updated_at,
+            registered_at
+        FROM markets
         WHERE condition_id = $1
         "#,
         condition_id
@@ -76,12 +148,140 @@ pub async fn get_market_by_condition_id(
     Ok(market)
 }
 
+/// Filters for listing markets (all optional, combined with AND)
+#[derive(Debug, Clone, Default)]
+pub struct MarketFilter {
+    /// Only markets carrying the tag with this slug
+    pub tag_slug: Option<String>,
+    /// Registration block lower bound (inclusive)
+    pub from_block: Option<i64>,
+    /// Registration block upper bound (inclusive)
+    pub to_block: Option<i64>,
+    /// Market start date lower bound (inclusive); markets whose start date
+    /// doesn't parse (see `MarketMetadata::start_at`) match neither bound
+    pub start_after: Option<DateTime<Utc>>,
+    /// Market start date upper bound (exclusive)
+    pub start_before: Option<DateTime<Utc>>,
+    /// Only markets with (true) or without (false) Gamma metadata
+    pub has_metadata: Option<bool>,
+    /// Only markets in this lifecycle status
+    pub status: Option<MarketStatus>,
+}
+
+/// List markets matching a filter, newest registrations first
+pub async fn list_markets(
+    pool: &PgPool,
+    filter: &MarketFilter,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<Market>> {
+    let markets = sqlx::query_as!(
+        Market,
+        r#"
+        SELECT
+            m.condition_id, m.token0, m.token1, m.block_number, m.tx_hash,
+            m.question, m.slug, m.pm_market_id, m.outcomes, m.start_date, m.end_date,
+            m.created_at, m.updated_at, m.metadata_fetched_at, m.status, m.status_updated_at,
+            m.registered_at
+        FROM markets m
+        WHERE ($1::TEXT IS NULL OR EXISTS (
+                SELECT 1 FROM market_tags mt
+                JOIN tags t ON mt.pm_tag_id = t.pm_tag_id
+                WHERE mt.condition_id = m.condition_id AND t.slug = $1
+            ))
+          AND ($2::BIGINT IS NULL OR m.block_number >= $2)
+          AND ($3::BIGINT IS NULL OR m.block_number <= $3)
+          AND ($4::TIMESTAMPTZ IS NULL OR m.start_at >= $4)
+          AND ($5::TIMESTAMPTZ IS NULL OR m.start_at < $5)
+          AND ($6::BOOLEAN IS NULL OR (m.metadata_fetched_at IS NOT NULL) = $6)
+          AND ($7::TEXT IS NULL OR m.status = $7)
+        ORDER BY m.block_number DESC, m.condition_id ASC
+        LIMIT $8 OFFSET $9
+        "#,
+        filter.tag_slug.as_deref(),
+        filter.from_block,
+        filter.to_block,
+        filter.start_after,
+        filter.start_before,
+        filter.has_metadata,
+        filter.status.map(MarketStatus::as_str),
+        limit,
+        offset
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(markets)
+}
+
+/// Filters for exporting markets (all optional, combined with AND)
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Registration block lower bound (inclusive)
+    pub from_block: Option<i64>,
+    /// Registration block upper bound (inclusive)
+    pub to_block: Option<i64>,
+    /// Only markets carrying the tag with this slug
+    pub tag_slug: Option<String>,
+    /// Only markets updated at or after this time (incremental exports)
+    pub updated_since: Option<DateTime<Utc>>,
+}
+
+/// Stream markets joined with their tags, oldest update first
+pub fn stream_export_rows<'a>(
+    pool: &'a PgPool,
+    filter: &'a ExportFilter,
+) -> impl Stream<Item = Result<MarketExportRow>> + 'a {
+    sqlx::query_as!(
+        MarketExportRow,
+        r#"
+        SELECT
+            m.condition_id, m.token0, m.token1, m.block_number, m.tx_hash,
+            m.question, m.slug, m.pm_market_id, m.outcomes, m.start_date, m.end_date,
+            COALESCE(
+                array_agg(t.pm_tag_id ORDER BY t.pm_tag_id) FILTER (WHERE t.pm_tag_id IS NOT NULL),
+                '{}'
+            ) AS "tag_ids!",
+            COALESCE(
+                array_agg(COALESCE(t.slug, '') ORDER BY t.pm_tag_id)
+                    FILTER (WHERE t.pm_tag_id IS NOT NULL),
+                '{}'
+            ) AS "tag_slugs!",
+            m.created_at, m.updated_at, m.metadata_fetched_at
+        FROM markets m
+        LEFT JOIN market_tags mt ON mt.condition_id = m.condition_id
+        LEFT JOIN tags t ON t.pm_tag_id = mt.pm_tag_id
+        WHERE ($1::BIGINT IS NULL OR m.block_number >= $1)
+          AND ($2::BIGINT IS NULL OR m.block_number <= $2)
+          AND ($3::TEXT IS NULL OR EXISTS (
+                SELECT 1 FROM market_tags fmt
+                JOIN tags ft ON fmt.pm_tag_id = ft.pm_tag_id
+                WHERE fmt.condition_id = m.condition_id AND ft.slug = $3
+            ))
+          AND ($4::TIMESTAMPTZ IS NULL OR m.updated_at >= $4)
+        GROUP BY m.condition_id
+        ORDER BY m.updated_at ASC, m.condition_id ASC
+        "#,
+        filter.from_block,
+        filter.to_block,
+        filter.tag_slug.as_deref(),
+        filter.updated_since
+    )
+    .fetch(pool)
+    .map_err(Into::into)
+}
+
 /// Get markets that don't have metadata yet (for retry logic)
 pub async fn get_markets_without_metadata(pool: &PgPool, limit: i64) -> Result<Vec<Market>> {
     let markets = sqlx::query_as!(
         Market,
         r#"
-        SELECT * FROM markets
+        SELECT
+            condition_id, token0, token1, block_number, tx_hash,
+            question, slug, pm_market_id, outcomes, start_date, end_date,
+            created_at, updated_at, metadata_fetched_at, status, status_updated_at,
+            registered_at
+        FROM markets
         WHERE metadata_fetched_at IS NULL
         ORDER BY created_at ASC
         LIMIT $1
@@ -106,3 +306,66 @@ pub async fn count_markets(pool: &PgPool) -> Result<i64> {
 
     Ok(result.count.unwrap_or(0))
 }
+
+/// Count markets that have no Gamma metadata
+pub async fn count_markets_without_metadata(pool: &PgPool) -> Result<i64> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count FROM markets
+        WHERE metadata_fetched_at IS NULL
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count.unwrap_or(0))
+}
+
+/// Count markets that have metadata but no tags
+pub async fn count_markets_without_tags(pool: &PgPool) -> Result<i64> {
+    let result = sqlx::query!(
+        r#"
+        SELECT COUNT(*) as count FROM markets m
+        WHERE m.metadata_fetched_at IS NOT NULL
+          AND NOT EXISTS (SELECT 1 FROM market_tags mt WHERE mt.condition_id = m.condition_id)
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(result.count.unwrap_or(0))
+}
+
+/// Search market questions and slugs, best matches first
+///
+/// Combines full-text matching (stemmed words, e.g. "Fed rate cut" matches
+/// "Will the Fed cut rates?") with trigram word similarity for typos and
+/// partial words.
+pub async fn search_markets(
+    pool: &PgPool,
+    query: &str,
+    limit: i64,
+) -> Result<Vec<MarketSearchResult>> {
+    let results = sqlx::query_as!(
+        MarketSearchResult,
+        r#"
+        WITH q AS (SELECT websearch_to_tsquery('english', $1) AS tsq)
+        SELECT
+            m.condition_id, m.question, m.slug, m.pm_market_id, m.block_number,
+            (ts_rank(m.search_vector, q.tsq)
+                + word_similarity($1, COALESCE(m.question, ''))) AS "rank!"
+        FROM markets m, q
+        WHERE m.search_vector @@ q.tsq
+           OR $1 <% m.question
+           OR $1 <% m.slug
+        ORDER BY "rank!" DESC, m.block_number DESC
+        LIMIT $2
+        "#,
+        query,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(results)
+}
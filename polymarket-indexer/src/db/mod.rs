@@ -1,25 +1,39 @@
 // Database module - PostgreSQL connection and operations
 
+pub mod anomalies;
+pub mod blocks;
+pub mod candles;
+pub mod checkpoints;
+pub mod fills;
+pub mod market_stats;
+pub mod market_status;
 pub mod market_tags;
 pub mod markets;
 pub mod models;
+pub mod positions;
+pub mod tokens;
+pub mod transfers;
+pub mod uma_questions;
+pub mod wallets;
 
-use eyre::Result;
+use crate::config::{ConfigError, DatabaseBackend, DatabaseConfig};
+use crate::error::Result;
 use sqlx::postgres::{PgPool, PgPoolOptions};
-use std::time::Duration;
 use tracing::info;
 
-/// Create a PostgreSQL connection pool
+/// Create a PostgreSQL connection pool from the `[database]` config section
 ///
-/// Reads DATABASE_URL from environment variable
-pub async fn create_pool() -> Result<PgPool> {
-    let database_url =
-        std::env::var("DATABASE_URL").expect("DATABASE_URL must be set in .env file");
+/// Commands that query beyond `MarketStore` (lookup, search, export, the API)
+/// need PostgreSQL, so a SQLite URL is a configuration error here.
+pub async fn create_pool(config: &DatabaseConfig) -> Result<PgPool> {
+    if config.backend()? != DatabaseBackend::Postgres {
+        return Err(ConfigError::new("this command requires a PostgreSQL database.url").into());
+    }
 
     let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .acquire_timeout(Duration::from_secs(3))
-        .connect(&database_url)
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout())
+        .connect(config.url()?)
         .await?;
 
     info!("✓ Connected to PostgreSQL database");
@@ -1,13 +1,14 @@
 // Database model structs
 
 use chrono::{DateTime, Utc};
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use sqlx::FromRow;
 
 /// Market database row
 ///
 /// Combines on-chain event data with enriched metadata from Gamma API
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Market {
     /// Unique condition ID (hex string with 0x prefix)
     pub condition_id: String,
@@ -48,10 +49,431 @@ pub struct Market {
 
     /// When metadata was fetched from Gamma API (null if not fetched)
     pub metadata_fetched_at: Option<DateTime<Utc>>,
+
+    /// Lifecycle status (see `polymarket::lifecycle::MarketStatus`)
+    pub status: String,
+
+    /// When the status last changed (null while still 'registered')
+    pub status_updated_at: Option<DateTime<Utc>>,
+
+    /// Timestamp of the registration block (null until its header is recorded)
+    pub registered_at: Option<DateTime<Utc>>,
+}
+
+impl Market {
+    /// Pretty-print the stored market
+    pub fn display(&self) {
+        println!("=================================");
+        println!("Market {}", self.condition_id);
+        match self.registered_at {
+            Some(at) => println!("  Block: {} ({})", self.block_number, at),
+            None => println!("  Block: {}", self.block_number),
+        }
+        println!("  TX: {}", self.tx_hash);
+        println!("  Token 0: {}", self.token0);
+        println!("  Token 1: {}", self.token1);
+        if let Some(question) = &self.question {
+            println!("  Question: {}", question);
+        }
+        if let Some(slug) = &self.slug {
+            println!("  Slug: {}", slug);
+        }
+        if let Some(outcomes) = &self.outcomes {
+            println!("  Outcomes: {}", outcomes);
+        }
+        match self.status_updated_at {
+            Some(at) => println!("  Status: {} (since {})", self.status, at),
+            None => println!("  Status: {}", self.status),
+        }
+        match self.metadata_fetched_at {
+            Some(at) => println!("  Metadata fetched: {}", at),
+            None => println!("  Metadata fetched: never"),
+        }
+        println!("=================================");
+    }
+}
+
+/// Market joined with its tags, as written by `export`
+///
+/// Field order is the export column order; keep it stable for consumers.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct MarketExportRow {
+    pub condition_id: String,
+    pub token0: String,
+    pub token1: String,
+    pub block_number: i64,
+    pub tx_hash: String,
+    pub question: Option<String>,
+    pub slug: Option<String>,
+    pub pm_market_id: Option<String>,
+    pub outcomes: Option<JsonValue>,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    /// Polymarket tag IDs, sorted
+    pub tag_ids: Vec<String>,
+    /// Tag slugs, one per entry of `tag_ids` ("" for tags without a slug)
+    pub tag_slugs: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub metadata_fetched_at: Option<DateTime<Utc>>,
+}
+
+/// Market matched by a text search, with its relevance score
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct MarketSearchResult {
+    /// Unique condition ID (hex string with 0x prefix)
+    pub condition_id: String,
+
+    /// Market question (from Gamma API)
+    pub question: Option<String>,
+
+    /// URL-friendly slug (from Gamma API)
+    pub slug: Option<String>,
+
+    /// Polymarket's internal market ID (from Gamma API)
+    pub pm_market_id: Option<String>,
+
+    /// Block where market was registered
+    pub block_number: i64,
+
+    /// Relevance (full-text rank plus trigram word similarity)
+    pub rank: f32,
+}
+
+/// UMA CTF Adapter question row
+///
+/// The on-chain question text and resolution state of a market, linked by
+/// condition ID
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct UmaQuestion {
+    /// Question ID (hex string with 0x prefix)
+    pub question_id: String,
+
+    /// Condition ID the adapter prepared for the question
+    pub condition_id: String,
+
+    /// Adapter that initialized the question
+    pub adapter: String,
+
+    /// Question creator and UMA request time (from QuestionInitialized)
+    pub creator: Option<String>,
+    pub request_timestamp: Option<i64>,
+
+    /// Raw ancillary data and the sections decoded from it
+    pub ancillary_data: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub resolution_data: Option<String>,
+
+    /// 'initialized', 'reset' (disputed and re-requested) or 'resolved'
+    pub status: String,
+
+    pub paused: bool,
+
+    /// Number of times the question was reset after a dispute
+    pub reset_count: i32,
+
+    /// Settled price and payouts (from QuestionResolved)
+    pub settled_price: Option<String>,
+    pub payouts: Option<JsonValue>,
+
+    pub initialized_block: Option<i64>,
+    pub resolved_block: Option<i64>,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl UmaQuestion {
+    /// Pretty-print the question below its market
+    pub fn display(&self) {
+        println!("  UMA question: {}", self.question_id);
+        if let Some(title) = &self.title {
+            println!("    Title: {}", title);
+        }
+        if let Some(resolution_data) = &self.resolution_data {
+            println!("    Resolution: {}", resolution_data);
+        }
+        let paused = if self.paused { " (paused)" } else { "" };
+        println!("    Status: {}{}", self.status, paused);
+        if self.reset_count > 0 {
+            println!("    Resets: {}", self.reset_count);
+        }
+        if let Some(payouts) = &self.payouts {
+            println!("    Payouts: {}", payouts);
+        }
+    }
+}
+
+/// Outcome token row: one ERC-1155 position of a market
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct OutcomeToken {
+    /// Token ID (uint256 as a decimal string)
+    pub token_id: String,
+
+    /// Market the token belongs to
+    pub condition_id: String,
+
+    /// Position in the market's outcome list (0 or 1 for binary markets)
+    pub outcome_index: i16,
+
+    /// Outcome label, e.g. "Yes" (from Gamma API)
+    pub outcome_label: Option<String>,
+
+    /// The other token of the pair
+    pub complement_token_id: String,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Applied market status change
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct MarketStatusTransition {
+    pub condition_id: String,
+    pub from_status: String,
+    pub to_status: String,
+
+    /// 'gamma' or 'uma'
+    pub source: String,
+
+    /// Block of the on-chain event behind the change (null for Gamma)
+    pub block_number: Option<i64>,
+
+    pub transitioned_at: DateTime<Utc>,
+}
+
+/// Indexed data that contradicts what was already stored
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DataAnomaly {
+    pub id: i64,
+    pub condition_id: Option<String>,
+
+    /// What kind of check failed, e.g. 'invalid_status_transition'
+    pub kind: String,
+
+    pub detail: String,
+    pub block_number: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Header of a block containing indexed events
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Block {
+    pub number: i64,
+
+    /// Block and parent hashes (hex strings with 0x prefix)
+    pub hash: String,
+    pub parent_hash: String,
+
+    /// Block timestamp
+    pub timestamp: DateTime<Utc>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Exchange OrderFilled event row
+///
+/// Amounts and asset IDs are decimal strings; the trade columns are null for
+/// fills that aren't trades (see `polymarket::trades::Fill::trade`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct Fill {
+    pub tx_hash: String,
+    pub log_index: i64,
+
+    /// Exchange that emitted the event
+    pub exchange: String,
+    pub order_hash: String,
+    pub maker: String,
+    pub taker: String,
+    pub maker_asset_id: String,
+    pub taker_asset_id: String,
+    pub maker_amount_filled: String,
+    pub taker_amount_filled: String,
+    pub fee: String,
+
+    /// Outcome token traded and the maker's side ('buy' or 'sell')
+    pub token_id: Option<String>,
+    pub side: Option<String>,
+
+    /// Collateral per outcome token
+    pub price: Option<f64>,
+
+    /// Outcome tokens and collateral traded, in raw 6-decimal units
+    pub size: Option<i64>,
+    pub notional: Option<i64>,
+
+    pub block_number: i64,
+    pub block_time: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// OHLCV candle for one outcome token (see `candles`)
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct Candle {
+    pub token_id: String,
+
+    /// '1m', '1h' or '1d'
+    pub resolution: String,
+    pub bucket_start: DateTime<Utc>,
+
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+
+    /// Outcome tokens and collateral traded, in raw 6-decimal units
+    pub volume: i64,
+    pub notional: i64,
+    pub trades: i64,
+
+    /// Position of the opening and closing fills, to merge candles built
+    /// from fills seen out of order
+    #[serde(skip)]
+    pub first_block: i64,
+    #[serde(skip)]
+    pub first_log_index: i64,
+    #[serde(skip)]
+    pub last_block: i64,
+    #[serde(skip)]
+    pub last_log_index: i64,
+}
+
+/// Conditional Tokens split, merge or redeem event row
+///
+/// `amount` is the collateral moved, as a decimal string (see
+/// `polymarket::positions`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PositionEvent {
+    pub tx_hash: String,
+    pub log_index: i64,
+
+    /// Contract that emitted the event (Conditional Tokens or NegRiskAdapter)
+    pub contract: String,
+
+    /// 'split', 'merge' or 'redeem'
+    pub kind: String,
+    pub condition_id: String,
+    pub stakeholder: String,
+    pub amount: String,
+
+    pub block_number: i64,
+    pub block_time: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One market's collateral flows and exchange volume over a UTC day (see
+/// `market_stats`)
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct MarketDailyStats {
+    pub condition_id: String,
+    pub day: DateTime<Utc>,
+
+    /// Collateral split into, merged out of and redeemed from the market, in
+    /// raw 6-decimal units
+    pub split_amount: i64,
+    pub merge_amount: i64,
+    pub redeem_amount: i64,
+
+    /// Collateral traded on the exchanges and the number of trades
+    pub volume: i64,
+    pub trades: i64,
+}
+
+/// A day of a market's time series, as served by the API
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct MarketSeriesPoint {
+    pub day: DateTime<Utc>,
+
+    /// Collateral locked in the market's outcome tokens at the end of the day
+    pub open_interest: i64,
+
+    pub volume: i64,
+    pub trades: i64,
+    pub split_amount: i64,
+    pub merge_amount: i64,
+    pub redeem_amount: i64,
+}
+
+/// Conditional Tokens ERC-1155 transfer row (one per token of a batch)
+///
+/// Token ID and amount are decimal strings (see `polymarket::transfers`).
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TokenTransfer {
+    pub tx_hash: String,
+    pub log_index: i64,
+
+    /// Position within a TransferBatch (0 for TransferSingle)
+    pub batch_index: i32,
+
+    pub operator: String,
+    pub from_address: String,
+    pub to_address: String,
+    pub token_id: String,
+    pub amount: String,
+
+    pub block_number: i64,
+    pub block_time: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A wallet's position in one outcome token (see `wallets`)
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct WalletPosition {
+    pub wallet: String,
+    pub token_id: String,
+
+    /// Market of the token, when it is indexed
+    pub condition_id: Option<String>,
+
+    /// Shares held and the collateral paid for them, in raw 6-decimal units
+    pub shares: i64,
+    pub cost_basis: i64,
+
+    /// Average entry price of the shares held
+    pub avg_price: Option<f64>,
+
+    /// Proceeds less cost of the shares disposed of and, once settled, of
+    /// the shares held at the market's payout
+    pub realized_pnl: i64,
+
+    /// Whether the market resolved and `realized_pnl` counts the held shares
+    pub settled: bool,
+
+    /// Blocks of the first and last activity in the position
+    pub first_block: i64,
+    pub last_block: i64,
+}
+
+/// A wallet's totals over its positions (see `wallets`)
+#[derive(Debug, Clone, PartialEq, FromRow, Serialize)]
+pub struct WalletStats {
+    pub wallet: String,
+
+    /// Markets the wallet held a position in, and positions still held
+    pub markets: i64,
+    pub open_positions: i64,
+
+    /// Resolved markets the wallet held and those it made a profit on
+    pub resolved_markets: i64,
+    pub winning_markets: i64,
+
+    /// `winning_markets / resolved_markets`
+    pub win_rate: Option<f64>,
+
+    /// Sum of the positions' realized PnL, in raw 6-decimal units
+    pub realized_pnl: i64,
+
+    /// Collateral traded on the exchanges and the number of fills
+    pub volume: i64,
+    pub trades: i64,
+
+    pub first_block: i64,
+    pub last_block: i64,
 }
 
 /// Tag database row (stores tag metadata)
-#[derive(Debug, Clone, FromRow)]
+#[derive(Debug, Clone, FromRow, Serialize)]
 pub struct Tag {
     /// Polymarket's internal tag ID
     pub pm_tag_id: String,
@@ -63,6 +485,22 @@ pub struct Tag {
     pub slug: Option<String>,
 }
 
+/// Tag with the number of markets carrying it
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TagSummary {
+    /// Polymarket's internal tag ID
+    pub pm_tag_id: String,
+
+    /// Human-readable tag label
+    pub label: Option<String>,
+
+    /// URL-friendly tag slug
+    pub slug: Option<String>,
+
+    /// Number of indexed markets with this tag
+    pub market_count: i64,
+}
+
 /// Market-Tag join table row (many-to-many relationship)
 #[derive(Debug, Clone, FromRow)]
 pub struct MarketTag {
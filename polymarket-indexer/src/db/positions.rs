@@ -0,0 +1,111 @@
+// Position event database operations
+
+use crate::db::market_stats::merge_market_stats;
+use crate::db::models::{MarketDailyStats, PositionEvent as PositionEventRow};
+use crate::error::Result;
+use crate::market_stats;
+use crate::metrics;
+use crate::polymarket::positions::PositionEvent;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use tracing::warn;
+
+/// Record position events and merge their flows into the daily market stats
+///
+/// Events already recorded are skipped and don't count twice. Returns the
+/// number of new events.
+pub async fn record_position_events(pool: &PgPool, events: &[PositionEvent]) -> Result<usize> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["record_position_events"])
+        .start_timer();
+    let mut rows = Vec::new();
+    let mut inserted = 0;
+    let mut tx = pool.begin().await?;
+
+    for event in events {
+        let new = sqlx::query_scalar!(
+            r#"
+            INSERT INTO position_events (
+                tx_hash, log_index, contract, kind, condition_id, stakeholder, amount,
+                block_number, block_time
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7::TEXT::NUMERIC, $8, $9)
+            ON CONFLICT (tx_hash, log_index) DO NOTHING
+            RETURNING log_index
+            "#,
+            event.tx_hash,
+            event.log_index as i64,
+            format!("{:?}", event.contract),
+            event.kind.as_str(),
+            event.condition_id_hex(),
+            format!("{:?}", event.stakeholder),
+            event.amount.to_string(),
+            event.block_number as i64,
+            event.block_time
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if new.is_some() {
+            inserted += 1;
+            match MarketDailyStats::from_position(event) {
+                Some(row) => rows.push(row),
+                None => warn!(
+                    "Position event {}:{} amount {} too large for market stats",
+                    event.tx_hash, event.log_index, event.amount
+                ),
+            }
+        }
+    }
+
+    merge_market_stats(&mut tx, &market_stats::aggregate(rows)).await?;
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Block times of the first and last stored position events in a block range
+pub async fn get_position_time_range(
+    pool: &PgPool,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT MIN(block_time) AS first, MAX(block_time) AS last
+        FROM position_events
+        WHERE block_number BETWEEN $1 AND $2
+        "#,
+        from_block as i64,
+        to_block as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.first.zip(row.last))
+}
+
+/// Get the position events with a block time in `[start, end)`, in chain
+/// order
+pub async fn get_position_events_between(
+    pool: &PgPool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<PositionEventRow>> {
+    let events = sqlx::query_as!(
+        PositionEventRow,
+        r#"
+        SELECT
+            tx_hash, log_index, contract, kind, condition_id, stakeholder,
+            amount::TEXT AS "amount!", block_number, block_time, created_at
+        FROM position_events
+        WHERE block_time >= $1 AND block_time < $2
+        ORDER BY block_number ASC, log_index ASC
+        "#,
+        start,
+        end
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(events)
+}
@@ -0,0 +1,152 @@
+// Outcome token database operations
+//
+// Token IDs are NUMERIC columns bound and selected as text, so they round-trip
+// as the same decimal strings used everywhere else.
+
+use crate::db::models::OutcomeToken;
+use crate::error::Result;
+use crate::metrics;
+use crate::polymarket::events::TokenRegistered;
+use sqlx::{PgConnection, PgPool};
+use tracing::warn;
+
+/// Insert a market's outcome tokens, labelled from `outcomes` when given
+///
+/// Runs inside the market upsert; existing rows keep their label unless a
+/// new one is given.
+pub(crate) async fn upsert_outcome_tokens(
+    conn: &mut PgConnection,
+    event: &TokenRegistered,
+    outcomes: Option<&[String]>,
+) -> Result<()> {
+    let token_ids = event.outcome_token_ids();
+    let condition_id = event.condition_id_hex();
+
+    for (index, token_id) in token_ids.iter().enumerate() {
+        let complement = token_ids[1 - index];
+        sqlx::query!(
+            r#"
+            INSERT INTO outcome_tokens (
+                token_id, condition_id, outcome_index, outcome_label, complement_token_id
+            ) VALUES ($1::TEXT::NUMERIC, $2, $3, $4, $5::TEXT::NUMERIC)
+            ON CONFLICT (token_id) DO UPDATE SET
+                outcome_label = COALESCE(EXCLUDED.outcome_label, outcome_tokens.outcome_label)
+            "#,
+            token_id.to_string(),
+            condition_id,
+            index as i16,
+            outcomes.and_then(|o| o.get(index)).map(String::as_str),
+            complement.to_string()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Label a market's outcome tokens from Gamma's outcome list
+pub(crate) async fn set_outcome_labels(
+    conn: &mut PgConnection,
+    condition_id: &str,
+    outcomes: &[String],
+) -> Result<()> {
+    for (index, label) in outcomes.iter().enumerate() {
+        sqlx::query!(
+            r#"
+            UPDATE outcome_tokens SET outcome_label = $3
+            WHERE condition_id = $1 AND outcome_index = $2
+            "#,
+            condition_id,
+            index as i16,
+            label
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Insert the outcome tokens of markets stored before `outcome_tokens`
+/// existed
+///
+/// Tokens are ordered and labelled as on upsert, from the stored pair and
+/// outcome list; markets that already have tokens are left alone. Returns the
+/// number of markets filled in.
+pub async fn backfill_outcome_tokens(pool: &PgPool) -> Result<usize> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["backfill_outcome_tokens"])
+        .start_timer();
+    let markets = sqlx::query!(
+        r#"
+        SELECT condition_id, token0, token1, block_number, tx_hash, outcomes
+        FROM markets m
+        WHERE NOT EXISTS (SELECT 1 FROM outcome_tokens t WHERE t.condition_id = m.condition_id)
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    let mut filled = 0;
+    for market in markets {
+        let Some(event) = TokenRegistered::from_stored(
+            &market.condition_id,
+            &market.token0,
+            &market.token1,
+            market.block_number as u64,
+            &market.tx_hash,
+        ) else {
+            warn!(
+                "Skipping outcome tokens of market {}: unparseable IDs",
+                market.condition_id
+            );
+            continue;
+        };
+        let outcomes: Option<Vec<String>> =
+            market.outcomes.and_then(|o| serde_json::from_value(o).ok());
+        upsert_outcome_tokens(&mut tx, &event, outcomes.as_deref()).await?;
+        filled += 1;
+    }
+    tx.commit().await?;
+
+    Ok(filled)
+}
+
+/// Get an outcome token by its ID (decimal string)
+pub async fn get_by_token_id(pool: &PgPool, token_id: &str) -> Result<Option<OutcomeToken>> {
+    let token = sqlx::query_as!(
+        OutcomeToken,
+        r#"
+        SELECT token_id::TEXT AS "token_id!", condition_id, outcome_index, outcome_label,
+               complement_token_id::TEXT AS "complement_token_id!", created_at, updated_at
+        FROM outcome_tokens
+        WHERE token_id = $1::TEXT::NUMERIC
+        "#,
+        token_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(token)
+}
+
+/// Get a market's outcome tokens in outcome order
+pub async fn get_tokens_for_market(pool: &PgPool, condition_id: &str) -> Result<Vec<OutcomeToken>> {
+    let tokens = sqlx::query_as!(
+        OutcomeToken,
+        r#"
+        SELECT token_id::TEXT AS "token_id!", condition_id, outcome_index, outcome_label,
+               complement_token_id::TEXT AS "complement_token_id!", created_at, updated_at
+        FROM outcome_tokens
+        WHERE condition_id = $1
+        ORDER BY outcome_index ASC
+        "#,
+        condition_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(tokens)
+}
@@ -0,0 +1,94 @@
+// Outcome token transfer database operations
+
+use crate::db::models::TokenTransfer as TokenTransferRow;
+use crate::error::Result;
+use crate::metrics;
+use crate::polymarket::transfers::TokenTransfer;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+/// Record outcome token transfers, one row per token moved
+///
+/// Transfers already recorded are skipped. Returns the number of new rows.
+pub async fn record_transfers(pool: &PgPool, transfers: &[TokenTransfer]) -> Result<usize> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["record_transfers"])
+        .start_timer();
+    let mut inserted = 0;
+    let mut tx = pool.begin().await?;
+
+    for transfer in transfers {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO token_transfers (
+                tx_hash, log_index, batch_index, operator, from_address, to_address,
+                token_id, amount, block_number, block_time
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7::TEXT::NUMERIC, $8::TEXT::NUMERIC, $9, $10)
+            ON CONFLICT (tx_hash, log_index, batch_index) DO NOTHING
+            "#,
+            transfer.tx_hash,
+            transfer.log_index as i64,
+            transfer.batch_index as i32,
+            format!("{:?}", transfer.operator),
+            format!("{:?}", transfer.from),
+            format!("{:?}", transfer.to),
+            transfer.token_id.to_string(),
+            transfer.amount.to_string(),
+            transfer.block_number as i64,
+            transfer.block_time
+        )
+        .execute(&mut *tx)
+        .await?;
+        inserted += result.rows_affected() as usize;
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Block times of the first and last stored transfers in a block range
+pub async fn get_transfer_time_range(
+    pool: &PgPool,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+    let row = sqlx::query!(
+        r#"
+        SELECT MIN(block_time) AS first, MAX(block_time) AS last
+        FROM token_transfers
+        WHERE block_number BETWEEN $1 AND $2
+        "#,
+        from_block as i64,
+        to_block as i64
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(row.first.zip(row.last))
+}
+
+/// Get the transfers with a block time in `[start, end)`, in chain order
+pub async fn get_transfers_between(
+    pool: &PgPool,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<TokenTransferRow>> {
+    let transfers = sqlx::query_as!(
+        TokenTransferRow,
+        r#"
+        SELECT
+            tx_hash, log_index, batch_index, operator, from_address, to_address,
+            token_id::TEXT AS "token_id!", amount::TEXT AS "amount!",
+            block_number, block_time, created_at
+        FROM token_transfers
+        WHERE block_time >= $1 AND block_time < $2
+        ORDER BY block_number ASC, log_index ASC, batch_index ASC
+        "#,
+        start,
+        end
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(transfers)
+}
@@ -0,0 +1,123 @@
+// UMA CTF Adapter question database operations
+
+use crate::db::models::UmaQuestion;
+use crate::error::Result;
+use crate::metrics;
+use crate::polymarket::uma::{QuestionEvent, QuestionEventKind};
+use sqlx::PgPool;
+
+/// Record a question event and apply it to the question
+///
+/// The event history is keyed by (tx_hash, log_index), so re-indexing a range
+/// is a no-op: returns `false` when the event was already recorded.
+pub async fn record_question_event(pool: &PgPool, event: &QuestionEvent) -> Result<bool> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["record_question_event"])
+        .start_timer();
+    let (settled_price, payouts) = event.resolution().unzip();
+    let payouts_json = payouts.map(|p| serde_json::json!(p));
+    let initialization = event.initialization();
+    let reset_count = i32::from(event.kind == QuestionEventKind::Reset);
+
+    let mut tx = pool.begin().await?;
+
+    let recorded = sqlx::query!(
+        r#"
+        INSERT INTO uma_question_events (
+            tx_hash, log_index, question_id, event, block_number, settled_price, payouts
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (tx_hash, log_index) DO NOTHING
+        "#,
+        event.tx_hash,
+        event.log_index as i64,
+        event.question_id_hex(),
+        event.kind.name(),
+        event.block_number as i64,
+        settled_price.as_deref(),
+        payouts_json.clone()
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+    if !recorded {
+        return Ok(false);
+    }
+
+    sqlx::query!(
+        r#"
+        INSERT INTO uma_questions (
+            question_id, condition_id, adapter, creator, request_timestamp,
+            ancillary_data, title, description, resolution_data,
+            status, paused, reset_count, settled_price, payouts,
+            initialized_block, resolved_block
+        ) VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9,
+            COALESCE($10, 'initialized'), COALESCE($11, FALSE), $12, $13, $14, $15, $16
+        )
+        ON CONFLICT (question_id) DO UPDATE SET
+            creator = COALESCE(EXCLUDED.creator, uma_questions.creator),
+            request_timestamp = COALESCE(EXCLUDED.request_timestamp, uma_questions.request_timestamp),
+            ancillary_data = COALESCE(EXCLUDED.ancillary_data, uma_questions.ancillary_data),
+            title = COALESCE(EXCLUDED.title, uma_questions.title),
+            description = COALESCE(EXCLUDED.description, uma_questions.description),
+            resolution_data = COALESCE(EXCLUDED.resolution_data, uma_questions.resolution_data),
+            status = COALESCE($10, uma_questions.status),
+            paused = COALESCE($11, uma_questions.paused),
+            reset_count = uma_questions.reset_count + EXCLUDED.reset_count,
+            settled_price = COALESCE(EXCLUDED.settled_price, uma_questions.settled_price),
+            payouts = COALESCE(EXCLUDED.payouts, uma_questions.payouts),
+            initialized_block = COALESCE(EXCLUDED.initialized_block, uma_questions.initialized_block),
+            resolved_block = COALESCE(EXCLUDED.resolved_block, uma_questions.resolved_block)
+        "#,
+        event.question_id_hex(),
+        event.condition_id_hex(),
+        format!("{:?}", event.adapter),
+        initialization.map(|(creator, _, _)| format!("{:?}", creator)),
+        initialization.map(|(_, timestamp, _)| timestamp as i64),
+        initialization.map(|(_, _, data)| data.raw.as_str()),
+        initialization.and_then(|(_, _, data)| data.title.as_deref()),
+        initialization.and_then(|(_, _, data)| data.description.as_deref()),
+        initialization.and_then(|(_, _, data)| data.resolution_data.as_deref()),
+        event.kind.status(),
+        event.kind.paused(),
+        reset_count,
+        settled_price.as_deref(),
+        payouts_json,
+        initialization.map(|_| event.block_number as i64),
+        settled_price.as_ref().map(|_| event.block_number as i64)
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Get the UMA question behind a market
+///
+/// A condition has one question per adapter; the most recently updated one
+/// wins if several adapters were indexed.
+pub async fn get_question_by_condition_id(
+    pool: &PgPool,
+    condition_id: &str,
+) -> Result<Option<UmaQuestion>> {
+    let question = sqlx::query_as!(
+        UmaQuestion,
+        r#"
+        SELECT question_id, condition_id, adapter, creator, request_timestamp,
+               ancillary_data, title, description, resolution_data,
+               status, paused, reset_count, settled_price, payouts,
+               initialized_block, resolved_block, created_at, updated_at
+        FROM uma_questions
+        WHERE condition_id = $1
+        ORDER BY updated_at DESC
+        LIMIT 1
+        "#,
+        condition_id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(question)
+}
@@ -0,0 +1,126 @@
+// Wallet analytics database operations
+//
+// Both tables are derived data: `wallets::rebuild` replays the stored fills,
+// position events and transfers and replaces them, as a whole or for the
+// wallets active in a block range.
+
+use crate::db::models::{WalletPosition, WalletStats};
+use crate::error::Result;
+use crate::metrics;
+use sqlx::PgPool;
+
+/// Replace the stored positions and stats of `wallets`, or of every wallet
+/// when `None`, in one transaction
+pub async fn replace_wallets(
+    pool: &PgPool,
+    wallets: Option<&[String]>,
+    positions: &[WalletPosition],
+    stats: &[WalletStats],
+) -> Result<()> {
+    let _timer = metrics::DB_WRITE_LATENCY
+        .with_label_values(&["replace_wallets"])
+        .start_timer();
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM wallet_positions WHERE $1::TEXT[] IS NULL OR wallet = ANY($1)",
+        wallets
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "DELETE FROM wallet_stats WHERE $1::TEXT[] IS NULL OR wallet = ANY($1)",
+        wallets
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    for position in positions {
+        sqlx::query!(
+            r#"
+            INSERT INTO wallet_positions (
+                wallet, token_id, condition_id, shares, cost_basis, avg_price, realized_pnl,
+                settled, first_block, last_block
+            ) VALUES ($1, $2::TEXT::NUMERIC, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            position.wallet,
+            position.token_id,
+            position.condition_id,
+            position.shares,
+            position.cost_basis,
+            position.avg_price,
+            position.realized_pnl,
+            position.settled,
+            position.first_block,
+            position.last_block
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for row in stats {
+        sqlx::query!(
+            r#"
+            INSERT INTO wallet_stats (
+                wallet, markets, open_positions, resolved_markets, winning_markets, win_rate,
+                realized_pnl, volume, trades, first_block, last_block
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            row.wallet,
+            row.markets,
+            row.open_positions,
+            row.resolved_markets,
+            row.winning_markets,
+            row.win_rate,
+            row.realized_pnl,
+            row.volume,
+            row.trades,
+            row.first_block,
+            row.last_block
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Get a wallet's positions, by token ID
+pub async fn get_wallet_positions(pool: &PgPool, wallet: &str) -> Result<Vec<WalletPosition>> {
+    let positions = sqlx::query_as!(
+        WalletPosition,
+        r#"
+        SELECT
+            wallet, token_id::TEXT AS "token_id!", condition_id, shares, cost_basis,
+            avg_price, realized_pnl, settled, first_block, last_block
+        FROM wallet_positions
+        WHERE wallet = $1
+        ORDER BY token_id ASC
+        "#,
+        wallet
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(positions)
+}
+
+/// Get a wallet's stats
+pub async fn get_wallet_stats(pool: &PgPool, wallet: &str) -> Result<Option<WalletStats>> {
+    let stats = sqlx::query_as!(
+        WalletStats,
+        r#"
+        SELECT
+            wallet, markets, open_positions, resolved_markets, winning_markets, win_rate,
+            realized_pnl, volume, trades, first_block, last_block
+        FROM wallet_stats
+        WHERE wallet = $1
+        "#,
+        wallet
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(stats)
+}
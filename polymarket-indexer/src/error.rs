@@ -0,0 +1,173 @@
+// Indexer errors - typed failures from the RPC, Gamma and database layers
+//
+// The clients and `db` module return `IndexerError` so callers can tell a
+// transient failure (timeout, rate limit, dropped connection) from one that
+// will fail the same way again (bad log, missing market, constraint
+// violation). Commands still work in `eyre::Result`; `IndexerError`
+// converts into a report with `?` and can be recovered with `downcast_ref`.
+
+use crate::config::ConfigError;
+use ethers::providers::ProviderError;
+use std::time::Duration;
+
+/// Result type for the clients and `db` module
+pub type Result<T, E = IndexerError> = std::result::Result<T, E>;
+
+/// JSON-RPC error codes nodes and vendors use for rate limiting
+const RPC_RATE_LIMIT_CODES: &[i64] = &[429, -32005];
+
+/// Postgres SQLSTATEs worth retrying: serialization_failure, deadlock_detected
+const PG_RETRYABLE_CODES: &[&str] = &["40001", "40P01"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    /// JSON-RPC transport or node error
+    #[error("RPC error: {0}")]
+    Rpc(ProviderError),
+
+    /// An HTTP API failed: a network error (no status) or an unexpected status
+    #[error("{service} request failed: {message}")]
+    Http {
+        service: &'static str,
+        status: Option<u16>,
+        message: String,
+    },
+
+    /// An API asked us to slow down
+    #[error("rate limited by {service}")]
+    RateLimited {
+        service: &'static str,
+        /// Delay requested by the server (Retry-After), if any
+        retry_after: Option<Duration>,
+    },
+
+    /// The requested resource does not exist
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// A log or response body could not be decoded
+    #[error("decode error: {0}")]
+    Decode(String),
+
+    /// Database error
+    #[error("database error: {0}")]
+    Db(sqlx::Error),
+
+    /// Invalid or missing configuration
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+impl IndexerError {
+    /// Whether the same request may succeed if retried later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            IndexerError::Rpc(e) => rpc_is_retryable(e),
+            IndexerError::Http { status, .. } => match status {
+                None => true,
+                Some(status) => *status >= 500 || *status == 408,
+            },
+            IndexerError::RateLimited { .. } => true,
+            IndexerError::Db(e) => db_is_retryable(e),
+            IndexerError::NotFound(_) | IndexerError::Decode(_) | IndexerError::Config(_) => false,
+        }
+    }
+
+    /// Short name of the error kind, for metric labels
+    pub fn kind(&self) -> &'static str {
+        match self {
+            IndexerError::Rpc(_) => "rpc",
+            IndexerError::Http { .. } => "http",
+            IndexerError::RateLimited { .. } => "rate_limited",
+            IndexerError::NotFound(_) => "not_found",
+            IndexerError::Decode(_) => "decode",
+            IndexerError::Db(_) => "db",
+            IndexerError::Config(_) => "config",
+        }
+    }
+}
+
+// `Rpc` and `Db` carry the inner error in their message, so it is not also
+// exposed as `source()` (eyre would print it twice)
+impl From<ProviderError> for IndexerError {
+    fn from(e: ProviderError) -> Self {
+        IndexerError::Rpc(e)
+    }
+}
+
+impl From<sqlx::Error> for IndexerError {
+    fn from(e: sqlx::Error) -> Self {
+        IndexerError::Db(e)
+    }
+}
+
+impl From<serde_json::Error> for IndexerError {
+    fn from(e: serde_json::Error) -> Self {
+        IndexerError::Decode(e.to_string())
+    }
+}
+
+fn rpc_is_retryable(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::JsonRpcClientError(e) => {
+            if let Some(response) = e.as_error_response() {
+                RPC_RATE_LIMIT_CODES.contains(&response.code)
+            } else {
+                // No JSON-RPC error and no bad payload: the transport failed
+                e.as_serde_error().is_none()
+            }
+        }
+        ProviderError::HTTPError(_) => true,
+        _ => false,
+    }
+}
+
+fn db_is_retryable(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed => true,
+        sqlx::Error::Database(e) => e
+            .code()
+            .is_some_and(|code| PG_RETRYABLE_CODES.contains(&code.as_ref())),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_kinds() {
+        let rate_limited = IndexerError::RateLimited {
+            service: "gamma",
+            retry_after: None,
+        };
+        assert!(rate_limited.is_retryable());
+        assert_eq!(rate_limited.kind(), "rate_limited");
+
+        let http = |status| IndexerError::Http {
+            service: "gamma",
+            status,
+            message: String::new(),
+        };
+        assert!(http(None).is_retryable());
+        assert!(http(Some(503)).is_retryable());
+        assert!(!http(Some(400)).is_retryable());
+
+        assert!(IndexerError::Db(sqlx::Error::PoolTimedOut).is_retryable());
+        assert!(!IndexerError::Db(sqlx::Error::RowNotFound).is_retryable());
+        assert!(!IndexerError::NotFound("market".into()).is_retryable());
+        assert!(!IndexerError::Decode("bad log".into()).is_retryable());
+        assert!(!IndexerError::Config(ConfigError::new("bad")).is_retryable());
+    }
+
+    #[test]
+    fn test_config_error_survives_eyre() {
+        let report = eyre::Report::from(IndexerError::Config(ConfigError::new("missing url")));
+        assert_eq!(report.to_string(), "invalid configuration: missing url");
+        assert!(matches!(
+            report.downcast_ref::<IndexerError>(),
+            Some(IndexerError::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+// CSV export
+//
+// Lists (tag_ids, tag_slugs) are joined with ';', outcomes is the JSON text,
+// timestamps are RFC 3339 in UTC and missing values are empty cells.
+
+use crate::db::models::MarketExportRow;
+use crate::export::{RowWriter, COLUMNS};
+use chrono::{DateTime, SecondsFormat, Utc};
+use eyre::Result;
+use std::io::Write;
+
+/// Separator for list columns
+const LIST_SEPARATOR: &str = ";";
+
+pub struct CsvWriter {
+    writer: ::csv::Writer<Box<dyn Write + Send>>,
+}
+
+impl CsvWriter {
+    /// Create a writer and emit the header row
+    pub fn new(output: Box<dyn Write + Send>) -> Result<Self> {
+        let mut writer = ::csv::Writer::from_writer(output);
+        writer.write_record(COLUMNS)?;
+        Ok(Self { writer })
+    }
+}
+
+fn timestamp(value: &DateTime<Utc>) -> String {
+    value.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+impl RowWriter for CsvWriter {
+    fn write_row(&mut self, row: &MarketExportRow) -> Result<()> {
+        self.writer.write_record([
+            row.condition_id.clone(),
+            row.token0.clone(),
+            row.token1.clone(),
+            row.block_number.to_string(),
+            row.tx_hash.clone(),
+            row.question.clone().unwrap_or_default(),
+            row.slug.clone().unwrap_or_default(),
+            row.pm_market_id.clone().unwrap_or_default(),
+            row.outcomes
+                .as_ref()
+                .map(|o| o.to_string())
+                .unwrap_or_default(),
+            row.start_date.clone().unwrap_or_default(),
+            row.end_date.clone().unwrap_or_default(),
+            row.tag_ids.join(LIST_SEPARATOR),
+            row.tag_slugs.join(LIST_SEPARATOR),
+            timestamp(&row.created_at),
+            timestamp(&row.updated_at),
+            row.metadata_fetched_at
+                .as_ref()
+                .map(timestamp)
+                .unwrap_or_default(),
+        ])?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
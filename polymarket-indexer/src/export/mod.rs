@@ -0,0 +1,105 @@
+// Market Export - write markets joined with tags to flat files
+//
+// All formats share the column set and order of `MarketExportRow`:
+// condition_id, token0, token1, block_number, tx_hash, question, slug,
+// pm_market_id, outcomes, start_date, end_date, tag_ids, tag_slugs,
+// created_at, updated_at, metadata_fetched_at
+
+pub mod csv;
+pub mod ndjson;
+pub mod parquet;
+
+use crate::db::models::MarketExportRow;
+use clap::ValueEnum;
+use eyre::Result;
+use std::io::Write;
+use std::path::Path;
+
+/// Export column names, in order
+pub const COLUMNS: &[&str] = &[
+    "condition_id",
+    "token0",
+    "token1",
+    "block_number",
+    "tx_hash",
+    "question",
+    "slug",
+    "pm_market_id",
+    "outcomes",
+    "start_date",
+    "end_date",
+    "tag_ids",
+    "tag_slugs",
+    "created_at",
+    "updated_at",
+    "metadata_fetched_at",
+];
+
+/// Output file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Newline-delimited JSON, one market per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Apache Parquet (requires --output)
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Guess the format from a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "ndjson" | "jsonl" => Some(ExportFormat::Ndjson),
+            "csv" => Some(ExportFormat::Csv),
+            "parquet" => Some(ExportFormat::Parquet),
+            _ => None,
+        }
+    }
+}
+
+/// Sink for exported rows
+pub trait RowWriter {
+    /// Write one row
+    fn write_row(&mut self, row: &MarketExportRow) -> Result<()>;
+
+    /// Flush buffered rows and write any footer
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// Create a row writer for the given format
+pub fn writer_for(
+    format: ExportFormat,
+    output: Box<dyn Write + Send>,
+) -> Result<Box<dyn RowWriter>> {
+    Ok(match format {
+        ExportFormat::Ndjson => Box::new(self::ndjson::NdjsonWriter::new(output)),
+        ExportFormat::Csv => Box::new(self::csv::CsvWriter::new(output)?),
+        ExportFormat::Parquet => Box::new(self::parquet::ParquetWriter::new(output)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out/markets.parquet")),
+            Some(ExportFormat::Parquet)
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("markets.jsonl")),
+            Some(ExportFormat::Ndjson)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("markets")), None);
+    }
+
+    #[test]
+    fn test_parquet_schema_matches_columns() {
+        let schema = self::parquet::schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, COLUMNS);
+    }
+}
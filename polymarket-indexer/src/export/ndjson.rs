@@ -0,0 +1,29 @@
+// Newline-delimited JSON export
+
+use crate::db::models::MarketExportRow;
+use crate::export::RowWriter;
+use eyre::Result;
+use std::io::Write;
+
+pub struct NdjsonWriter {
+    output: Box<dyn Write + Send>,
+}
+
+impl NdjsonWriter {
+    pub fn new(output: Box<dyn Write + Send>) -> Self {
+        Self { output }
+    }
+}
+
+impl RowWriter for NdjsonWriter {
+    fn write_row(&mut self, row: &MarketExportRow) -> Result<()> {
+        serde_json::to_writer(&mut self.output, row)?;
+        self.output.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.output.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+// Parquet export
+//
+// Rows are buffered into Arrow record batches and written with Snappy
+// compression. Lists are LIST<UTF8>, timestamps are microseconds in UTC and
+// outcomes is the JSON text.
+
+use crate::db::models::MarketExportRow;
+use crate::export::{RowWriter, COLUMNS};
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use eyre::Result;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Rows per record batch (and at most per row group)
+const BATCH_SIZE: usize = 8192;
+
+pub struct ParquetWriter {
+    writer: ArrowWriter<Box<dyn Write + Send>>,
+    schema: SchemaRef,
+    buffer: Vec<MarketExportRow>,
+}
+
+/// Arrow schema matching `export::COLUMNS`
+pub fn schema() -> SchemaRef {
+    let utf8 = |name: &str, nullable| Field::new(name, DataType::Utf8, nullable);
+    let list = |name: &str| {
+        Field::new(
+            name,
+            DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+            false,
+        )
+    };
+    let timestamp = |name: &str, nullable| {
+        Field::new(
+            name,
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            nullable,
+        )
+    };
+
+    let fields = vec![
+        utf8("condition_id", false),
+        utf8("token0", false),
+        utf8("token1", false),
+        Field::new("block_number", DataType::Int64, false),
+        utf8("tx_hash", false),
+        utf8("question", true),
+        utf8("slug", true),
+        utf8("pm_market_id", true),
+        utf8("outcomes", true),
+        utf8("start_date", true),
+        utf8("end_date", true),
+        list("tag_ids"),
+        list("tag_slugs"),
+        timestamp("created_at", false),
+        timestamp("updated_at", false),
+        timestamp("metadata_fetched_at", true),
+    ];
+    debug_assert!(fields.iter().map(|f| f.name()).eq(COLUMNS.iter()));
+
+    Arc::new(Schema::new(fields))
+}
+
+impl ParquetWriter {
+    pub fn new(output: Box<dyn Write + Send>) -> Result<Self> {
+        let schema = schema();
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(output, schema.clone(), Some(props))?;
+
+        Ok(Self {
+            writer,
+            schema,
+            buffer: Vec::with_capacity(BATCH_SIZE),
+        })
+    }
+
+    /// Convert buffered rows into a record batch and write it
+    fn flush_batch(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let rows = std::mem::take(&mut self.buffer);
+        let string = |f: fn(&MarketExportRow) -> Option<&str>| -> ArrayRef {
+            Arc::new(StringArray::from_iter(rows.iter().map(f)))
+        };
+        let list = |f: fn(&MarketExportRow) -> &[String]| -> ArrayRef {
+            let mut builder = ListBuilder::new(StringBuilder::new());
+            for row in &rows {
+                for value in f(row) {
+                    builder.values().append_value(value);
+                }
+                builder.append(true);
+            }
+            Arc::new(builder.finish())
+        };
+        let timestamp = |f: fn(&MarketExportRow) -> Option<i64>| -> ArrayRef {
+            Arc::new(TimestampMicrosecondArray::from_iter(rows.iter().map(f)).with_timezone("UTC"))
+        };
+
+        let columns: Vec<ArrayRef> = vec![
+            string(|r| Some(&r.condition_id)),
+            string(|r| Some(&r.token0)),
+            string(|r| Some(&r.token1)),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.block_number),
+            )),
+            string(|r| Some(&r.tx_hash)),
+            string(|r| r.question.as_deref()),
+            string(|r| r.slug.as_deref()),
+            string(|r| r.pm_market_id.as_deref()),
+            Arc::new(StringArray::from_iter(
+                rows.iter()
+                    .map(|r| r.outcomes.as_ref().map(|o| o.to_string())),
+            )),
+            string(|r| r.start_date.as_deref()),
+            string(|r| r.end_date.as_deref()),
+            list(|r| &r.tag_ids),
+            list(|r| &r.tag_slugs),
+            timestamp(|r| Some(r.created_at.timestamp_micros())),
+            timestamp(|r| Some(r.updated_at.timestamp_micros())),
+            timestamp(|r| r.metadata_fetched_at.map(|t| t.timestamp_micros())),
+        ];
+
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.writer.write(&batch)?;
+        self.buffer = rows;
+        self.buffer.clear();
+
+        Ok(())
+    }
+}
+
+impl RowWriter for ParquetWriter {
+    fn write_row(&mut self, row: &MarketExportRow) -> Result<()> {
+        self.buffer.push(row.clone());
+        if self.buffer.len() >= BATCH_SIZE {
+            self.flush_batch()?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.flush_batch()?;
+        self.writer.close()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,709 @@
+// Market Ingestion - enrich on-chain markets with Gamma metadata and store them
+//
+// Shared by the backfill, stream and enrich commands so every entry point
+// writes markets and tags the same way. UMA question events are stored
+// alongside by `QuestionIngester`. Both move markets through their lifecycle
+// status (see `polymarket::lifecycle`): Gamma flags when metadata is stored,
+// question resets and resolutions as they are indexed.
+
+use crate::client::gamma::GammaClient;
+use crate::config::Config;
+use crate::error::IndexerError;
+use crate::metrics;
+use crate::polymarket::contracts::ctf_exchange::CTFExchangeEvents;
+use crate::polymarket::events::{PairedRegistration, PolymarketEvent, TokenRegistered};
+use crate::polymarket::lifecycle::{MarketStatus, StatusSignal, INVALID_TRANSITION};
+use crate::polymarket::market::MarketMetadata;
+use crate::polymarket::positions::PositionEvent;
+use crate::polymarket::trades::Fill;
+use crate::polymarket::transfers::TokenTransfer;
+use crate::polymarket::uma::QuestionEvent;
+use crate::registry::{DecodedLog, EventHandler};
+use crate::store::MarketStore;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, Log};
+use eyre::{eyre, Result};
+use futures::{stream, StreamExt, TryStreamExt};
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Counters accumulated while ingesting markets
+#[derive(Debug, Default, Clone)]
+pub struct IngestStats {
+    pub inserted: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub enriched: usize,
+    pub tags_inserted: usize,
+    pub tags_failed: usize,
+    pub question_events: usize,
+    pub status_changes: usize,
+    pub anomalies: usize,
+    pub blocks: usize,
+    pub fills: usize,
+    pub position_events: usize,
+    pub transfers: usize,
+}
+
+impl IngestStats {
+    /// Log the counters as a summary block
+    pub fn log_summary(&self) {
+        info!("  Markets inserted: {}", self.inserted);
+        info!("  Markets skipped (already in DB): {}", self.skipped);
+        info!("  Markets enriched: {}", self.enriched);
+        info!("  Markets failed: {}", self.failed);
+        info!("  Tags inserted: {}", self.tags_inserted);
+        info!("  Tags failed: {}", self.tags_failed);
+        info!("  UMA question events: {}", self.question_events);
+        info!("  Market status changes: {}", self.status_changes);
+        info!("  Data anomalies: {}", self.anomalies);
+        info!("  Block headers recorded: {}", self.blocks);
+        info!("  Exchange fills recorded: {}", self.fills);
+        info!("  Position events recorded: {}", self.position_events);
+        info!("  Token transfers recorded: {}", self.transfers);
+    }
+}
+
+impl AddAssign for IngestStats {
+    fn add_assign(&mut self, other: Self) {
+        self.inserted += other.inserted;
+        self.skipped += other.skipped;
+        self.failed += other.failed;
+        self.enriched += other.enriched;
+        self.tags_inserted += other.tags_inserted;
+        self.tags_failed += other.tags_failed;
+        self.question_events += other.question_events;
+        self.status_changes += other.status_changes;
+        self.anomalies += other.anomalies;
+        self.blocks += other.blocks;
+        self.fills += other.fills;
+        self.position_events += other.position_events;
+        self.transfers += other.transfers;
+    }
+}
+
+/// Writes markets and their Gamma metadata to a market store
+pub struct MarketIngester {
+    gamma: GammaClient,
+    store: Arc<dyn MarketStore>,
+    max_retries: u32,
+    concurrency: usize,
+}
+
+impl MarketIngester {
+    /// Create a new ingester
+    ///
+    /// `max_retries` bounds Gamma retries for markets not yet visible in the API.
+    pub fn new(gamma: GammaClient, store: Arc<dyn MarketStore>, max_retries: u32) -> Self {
+        Self {
+            gamma,
+            store,
+            max_retries,
+            concurrency: 1,
+        }
+    }
+
+    /// Ingest up to `concurrency` markets at a time when handling events
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Create an ingester using the `[gamma]` and `[fixtures]` config sections
+    pub fn from_config(config: &Config, store: Arc<dyn MarketStore>) -> Self {
+        Self::new(
+            GammaClient::from_config(&config.gamma, &config.fixtures, &config.cache),
+            store,
+            config.gamma.max_retries,
+        )
+        .with_concurrency(config.gamma.concurrency)
+    }
+
+    /// Ingest a newly registered market
+    ///
+    /// Markets already in the store are skipped. Metadata failures are
+    /// logged and the market is stored without metadata so `enrich` can
+    /// pick it up later. The new market's status follows its Gamma flags and
+    /// any question events indexed before it was registered.
+    pub async fn ingest(&self, event: &TokenRegistered) -> Result<IngestStats> {
+        let mut stats = IngestStats::default();
+        let condition_id = event.condition_id_hex();
+
+        // Check if already stored
+        if self.store.get_market(&condition_id).await?.is_some() {
+            stats.skipped += 1;
+            return Ok(stats);
+        }
+
+        let metadata = self.fetch_metadata(&condition_id).await;
+
+        match self.store.upsert_market(event, metadata.as_ref()).await {
+            Ok(_) => {
+                info!("✓ Inserted market {}", condition_id);
+                if let Some(ref meta) = metadata {
+                    self.ingest_tags(&condition_id, meta, &mut stats).await;
+                    if let Some(signal) = StatusSignal::from_metadata(meta) {
+                        apply_status(&*self.store, &condition_id, signal, None, &mut stats).await?;
+                    }
+                }
+                if let Some(question) = self.store.get_question(&condition_id).await? {
+                    if let Some(signal) = StatusSignal::from_question_status(&question.status) {
+                        let block = question
+                            .resolved_block
+                            .filter(|_| signal == StatusSignal::Resolved);
+                        apply_status(
+                            &*self.store,
+                            &condition_id,
+                            signal,
+                            block.map(|b| b as u64),
+                            &mut stats,
+                        )
+                        .await?;
+                    }
+                }
+                stats.inserted += 1;
+            }
+            Err(e) => {
+                warn!("Failed to insert market {}: {}", condition_id, e);
+                stats.failed += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Ingest a market from its paired registration events
+    ///
+    /// Pairing anomalies are recorded when the market is first inserted, so
+    /// re-indexing a range doesn't record them again.
+    pub async fn ingest_paired(&self, paired: &PairedRegistration) -> Result<IngestStats> {
+        let mut stats = self.ingest(&paired.event).await?;
+        if stats.inserted == 0 {
+            return Ok(stats);
+        }
+
+        let condition_id = paired.event.condition_id_hex();
+        for anomaly in &paired.anomalies {
+            record_anomaly(
+                &*self.store,
+                &condition_id,
+                anomaly.kind(),
+                &anomaly.to_string(),
+                Some(paired.event.block_number),
+                &mut stats,
+            )
+            .await?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Fetch and store metadata for a market that was indexed without it
+    pub async fn enrich(&self, condition_id: &str) -> Result<IngestStats> {
+        let mut stats = IngestStats::default();
+        let Some(metadata) = self.fetch_metadata(condition_id).await else {
+            stats.failed += 1;
+            return Ok(stats);
+        };
+
+        match self
+            .store
+            .update_market_metadata(condition_id, &metadata)
+            .await
+        {
+            Ok(_) => {
+                info!("✓ Enriched market {}", condition_id);
+                self.ingest_tags(condition_id, &metadata, &mut stats).await;
+                if let Some(signal) = StatusSignal::from_metadata(&metadata) {
+                    apply_status(&*self.store, condition_id, signal, None, &mut stats).await?;
+                }
+                stats.enriched += 1;
+            }
+            Err(e) => {
+                warn!("Failed to update market {}: {}", condition_id, e);
+                stats.failed += 1;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Fetch metadata from Gamma API, logging (not propagating) failures
+    async fn fetch_metadata(&self, condition_id: &str) -> Option<MarketMetadata> {
+        match self
+            .gamma
+            .get_market_with_retry(condition_id, self.max_retries)
+            .await
+        {
+            Ok(Some(m)) => Some(m),
+            Ok(None) => {
+                warn!("No metadata found for {}", condition_id);
+                None
+            }
+            Err(e) => {
+                warn!("Failed to fetch metadata for {}: {}", condition_id, e);
+                None
+            }
+        }
+    }
+
+    /// Fetch and insert tags if the metadata carries a pm_market_id
+    async fn ingest_tags(
+        &self,
+        condition_id: &str,
+        metadata: &MarketMetadata,
+        stats: &mut IngestStats,
+    ) {
+        let Some(ref market_id) = metadata.id else {
+            return;
+        };
+
+        match self.gamma.get_market_tags(market_id).await {
+            Ok(tags) if !tags.is_empty() => {
+                match self.store.insert_market_tags(condition_id, &tags).await {
+                    Ok(_) => {
+                        info!("  ✓ Inserted {} tags", tags.len());
+                        stats.tags_inserted += tags.len();
+                    }
+                    Err(e) => {
+                        warn!("  Failed to insert tags: {}", e);
+                        stats.tags_failed += 1;
+                    }
+                }
+            }
+            Ok(_) => {} // No tags, skip silently
+            Err(e) => {
+                warn!("  Failed to fetch tags: {}", e);
+                stats.tags_failed += 1;
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for MarketIngester {
+    /// Ingest each market registered in the batch once
+    ///
+    /// The exchange emits TokenRegistered twice per market with the tokens
+    /// swapped, so events are grouped by condition ID and paired first.
+    async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats> {
+        let mut by_condition: BTreeMap<String, Vec<TokenRegistered>> = BTreeMap::new();
+        for DecodedLog { log, event } in events {
+            let PolymarketEvent::Exchange(CTFExchangeEvents::TokenRegisteredFilter(event)) = event
+            else {
+                continue;
+            };
+            match TokenRegistered::from_event(event, &log) {
+                Ok(event) => by_condition
+                    .entry(event.condition_id_hex())
+                    .or_default()
+                    .push(event),
+                Err(e) => warn!("Failed to parse log: {}", e),
+            }
+        }
+
+        info!("Unique markets: {}", by_condition.len());
+
+        stream::iter(by_condition.into_values().filter_map(TokenRegistered::pair))
+            .map(|paired| async move { self.ingest_paired(&paired).await })
+            .buffer_unordered(self.concurrency)
+            .try_fold(IngestStats::default(), |mut total, stats| async move {
+                total += stats;
+                Ok(total)
+            })
+            .await
+    }
+}
+
+/// Move a stored market's status according to `signal`
+///
+/// Impossible transitions are recorded as data anomalies rather than
+/// failing ingestion. Markets not stored yet are left alone; `ingest` picks
+/// up their question status when they are registered.
+async fn apply_status(
+    store: &dyn MarketStore,
+    condition_id: &str,
+    signal: StatusSignal,
+    block_number: Option<u64>,
+    stats: &mut IngestStats,
+) -> Result<()> {
+    let Some(market) = store.get_market(condition_id).await? else {
+        return Ok(());
+    };
+    let current: MarketStatus = market.status.parse().map_err(|e: String| eyre!(e))?;
+
+    match current.apply(signal) {
+        Ok(None) => {}
+        Ok(Some(next)) => {
+            if store
+                .set_market_status(condition_id, current, next, signal, block_number)
+                .await?
+            {
+                info!("  ✓ Market {} {} -> {}", condition_id, current, next);
+                metrics::STATUS_TRANSITIONS
+                    .with_label_values(&[next.as_str()])
+                    .inc();
+                stats.status_changes += 1;
+            }
+        }
+        Err(invalid) => {
+            record_anomaly(
+                store,
+                condition_id,
+                INVALID_TRANSITION,
+                &invalid.to_string(),
+                block_number,
+                stats,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Record a data anomaly for a market, logging and counting it
+async fn record_anomaly(
+    store: &dyn MarketStore,
+    condition_id: &str,
+    kind: &str,
+    detail: &str,
+    block_number: Option<u64>,
+    stats: &mut IngestStats,
+) -> Result<()> {
+    warn!("Data anomaly ({}) for {}: {}", kind, condition_id, detail);
+    store
+        .record_anomaly(Some(condition_id), kind, detail, block_number)
+        .await?;
+    metrics::DATA_ANOMALIES.with_label_values(&[kind]).inc();
+    stats.anomalies += 1;
+    Ok(())
+}
+
+/// Stores UMA CTF Adapter question events
+pub struct QuestionIngester {
+    store: Arc<dyn MarketStore>,
+}
+
+impl QuestionIngester {
+    pub fn new(store: Arc<dyn MarketStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl EventHandler for QuestionIngester {
+    /// Apply question events one at a time, in log order
+    ///
+    /// Events already recorded (a re-indexed range) are skipped.
+    async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats> {
+        let mut stats = IngestStats::default();
+        for DecodedLog { log, event } in events {
+            let PolymarketEvent::UmaCtfAdapter(event) = event else {
+                continue;
+            };
+            let event = match QuestionEvent::from_event(event, &log) {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Failed to parse log: {}", e);
+                    continue;
+                }
+            };
+
+            if self.store.record_question_event(&event).await? {
+                info!(
+                    "✓ Question {} {} (market {})",
+                    event.question_id_hex(),
+                    event.kind.name(),
+                    event.condition_id_hex()
+                );
+                stats.question_events += 1;
+
+                if let Some(signal) = StatusSignal::from_question_event(&event.kind) {
+                    apply_status(
+                        &*self.store,
+                        &event.condition_id_hex(),
+                        signal,
+                        Some(event.block_number),
+                        &mut stats,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Records exchange fills and the candles built from them
+pub struct FillIngester {
+    store: Arc<dyn MarketStore>,
+}
+
+impl FillIngester {
+    pub fn new(store: Arc<dyn MarketStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl EventHandler for FillIngester {
+    /// Record the batch's OrderFilled events in one write
+    ///
+    /// See `BlockTimes` for where the block times come from.
+    async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats> {
+        let mut block_times = BlockTimes::new(&*self.store);
+        let mut fills = Vec::new();
+        for DecodedLog { log, event } in events {
+            let PolymarketEvent::Exchange(CTFExchangeEvents::OrderFilledFilter(event)) = event
+            else {
+                continue;
+            };
+            let Some(block_time) = block_times.get(&log).await? else {
+                continue;
+            };
+
+            match Fill::from_event(event, &log, block_time) {
+                Ok(fill) => fills.push(fill),
+                Err(e) => warn!("Failed to parse log: {}", e),
+            }
+        }
+
+        let recorded = self.store.record_fills(&fills).await?;
+        if recorded > 0 {
+            info!("✓ Recorded {} exchange fills", recorded);
+        }
+
+        Ok(IngestStats {
+            fills: recorded,
+            ..Default::default()
+        })
+    }
+}
+
+/// Stores Conditional Tokens and NegRiskAdapter split, merge and redeem
+/// events
+pub struct PositionIngester {
+    store: Arc<dyn MarketStore>,
+    neg_risk_adapter: Address,
+}
+
+impl PositionIngester {
+    pub fn new(store: Arc<dyn MarketStore>, neg_risk_adapter: Address) -> Self {
+        Self {
+            store,
+            neg_risk_adapter,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for PositionIngester {
+    /// Record the batch's top-level position events in one write
+    ///
+    /// Conditional Tokens events the NegRiskAdapter caused are dropped in
+    /// favour of the adapter's own. See `BlockTimes` for where the block
+    /// times come from.
+    async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats> {
+        let mut block_times = BlockTimes::new(&*self.store);
+        let mut positions = Vec::new();
+        for DecodedLog { log, event } in events {
+            let Some(block_time) = block_times.get(&log).await? else {
+                continue;
+            };
+            match PositionEvent::from_event(event, &log, block_time) {
+                Ok(Some(event)) if !event.is_adapter_passthrough(self.neg_risk_adapter) => {
+                    positions.push(event)
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to parse log: {}", e),
+            }
+        }
+
+        let recorded = self.store.record_position_events(&positions).await?;
+        if recorded > 0 {
+            info!("✓ Recorded {} position events", recorded);
+        }
+
+        Ok(IngestStats {
+            position_events: recorded,
+            ..Default::default()
+        })
+    }
+}
+
+/// Stores Conditional Tokens TransferSingle and TransferBatch events
+pub struct TransferIngester {
+    store: Arc<dyn MarketStore>,
+}
+
+impl TransferIngester {
+    pub fn new(store: Arc<dyn MarketStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl EventHandler for TransferIngester {
+    /// Record the batch's transfers in one write, one row per token moved
+    ///
+    /// See `BlockTimes` for where the block times come from.
+    async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats> {
+        let mut block_times = BlockTimes::new(&*self.store);
+        let mut transfers = Vec::new();
+        for DecodedLog { log, event } in events {
+            let PolymarketEvent::ConditionalTokens(event) = event else {
+                continue;
+            };
+            let Some(block_time) = block_times.get(&log).await? else {
+                continue;
+            };
+
+            match TokenTransfer::from_event(event, &log, block_time) {
+                Ok(moved) => transfers.extend(moved),
+                Err(e) => warn!("Failed to parse log: {}", e),
+            }
+        }
+
+        let recorded = self.store.record_transfers(&transfers).await?;
+        if recorded > 0 {
+            info!("✓ Recorded {} token transfers", recorded);
+        }
+
+        Ok(IngestStats {
+            transfers: recorded,
+            ..Default::default()
+        })
+    }
+}
+
+/// Block timestamps of a batch's logs
+///
+/// Read from the headers `BlockRecorder` stored for the same logs before
+/// dispatch, so a missing header is an error rather than a skipped event.
+struct BlockTimes<'a> {
+    store: &'a dyn MarketStore,
+    times: BTreeMap<u64, DateTime<Utc>>,
+}
+
+impl<'a> BlockTimes<'a> {
+    fn new(store: &'a dyn MarketStore) -> Self {
+        Self {
+            store,
+            times: BTreeMap::new(),
+        }
+    }
+
+    /// Timestamp of the block holding `log`; `None` (logged) for logs
+    /// without a block number
+    async fn get(&mut self, log: &Log) -> Result<Option<DateTime<Utc>>> {
+        let Some(number) = log.block_number.map(|n| n.as_u64()) else {
+            warn!("Failed to parse log: log missing block_number");
+            return Ok(None);
+        };
+        if let Some(time) = self.times.get(&number) {
+            return Ok(Some(*time));
+        }
+
+        let block = self
+            .store
+            .get_block(number)
+            .await?
+            .ok_or_else(|| IndexerError::NotFound(format!("block header {}", number)))?;
+        self.times.insert(number, block.timestamp);
+        Ok(Some(block.timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::contracts::{conditional_tokens, neg_risk_adapter};
+    use crate::testing;
+    use ethers::abi::Token;
+    use ethers::contract::EthEvent;
+    use ethers::types::{H256, U256};
+
+    #[tokio::test]
+    async fn test_fills_are_recorded_once() {
+        let store = testing::store().await;
+        let log = testing::order_filled(Address::repeat_byte(0xee), 3);
+
+        let ingester = FillIngester::new(store.clone());
+        let stats = ingester
+            .handle(testing::decoded(&[log.clone(), log]))
+            .await
+            .unwrap();
+        assert_eq!(stats.fills, 1);
+    }
+
+    #[tokio::test]
+    async fn test_adapter_position_events_count_once() {
+        let store = testing::store().await;
+        let adapter = Address::repeat_byte(0xad);
+        let condition_id = H256::repeat_byte(3);
+
+        // A user splits 5 USDC through the adapter, which splits on the
+        // Conditional Tokens contract itself
+        let outer = testing::log(
+            adapter,
+            vec![
+                neg_risk_adapter::PositionSplitFilter::signature(),
+                H256::from(Address::repeat_byte(1)),
+                condition_id,
+            ],
+            &[Token::Uint(U256::from(5_000_000))],
+            1,
+        );
+        let inner = testing::log(
+            Address::repeat_byte(0xc7),
+            vec![
+                conditional_tokens::PositionSplitFilter::signature(),
+                H256::from(adapter),
+                H256::zero(),
+                condition_id,
+            ],
+            &[
+                Token::Address(Address::repeat_byte(0xcc)),
+                Token::Array(vec![Token::Uint(U256::one()), Token::Uint(U256::from(2))]),
+                Token::Uint(U256::from(5_000_000)),
+            ],
+            0,
+        );
+
+        let ingester = PositionIngester::new(store.clone(), adapter);
+        let stats = ingester
+            .handle(testing::decoded(&[inner, outer]))
+            .await
+            .unwrap();
+        assert_eq!(stats.position_events, 1);
+    }
+
+    #[tokio::test]
+    async fn test_transfer_batches_are_flattened() {
+        let store = testing::store().await;
+        let uints = |values: [u64; 2]| Token::Array(values.map(|v| Token::Uint(v.into())).into());
+        let log = testing::log(
+            Address::repeat_byte(0xc7),
+            vec![
+                conditional_tokens::TransferBatchFilter::signature(),
+                H256::from(Address::repeat_byte(1)),
+                H256::from(Address::repeat_byte(1)),
+                H256::from(Address::repeat_byte(2)),
+            ],
+            &[uints([7, 8]), uints([5_000_000, 5_000_000])],
+            3,
+        );
+
+        let ingester = TransferIngester::new(store.clone());
+        let stats = ingester
+            .handle(testing::decoded(&[log.clone(), log]))
+            .await
+            .unwrap();
+        assert_eq!(stats.transfers, 2);
+    }
+}
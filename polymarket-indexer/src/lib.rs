@@ -1,7 +1,23 @@
 // Polymarket Indexer Library
 //
-// Provides shared functionality for backfill and stream binaries
+// Provides shared functionality for the indexer CLI and its subcommands
 
+pub mod api;
+pub mod blocks;
+pub mod candles;
+pub mod cli;
 pub mod client;
+pub mod config;
 pub mod db;
+pub mod error;
+pub mod export;
+pub mod ingest;
+pub mod market_stats;
+pub mod metrics;
 pub mod polymarket;
+pub mod registry;
+pub mod shutdown;
+pub mod store;
+#[cfg(test)]
+mod testing;
+pub mod wallets;
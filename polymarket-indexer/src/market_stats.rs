@@ -0,0 +1,250 @@
+// Market stats - daily open interest and volume per market
+//
+// Each market gets one row per UTC day with the collateral split into,
+// merged out of and redeemed from it (see `polymarket::positions`) and the
+// collateral traded on the exchanges (the day's candles of its outcome
+// tokens). Every column is a sum, so rows built from different batches merge
+// by addition in any order; open interest is the running total of the net
+// flows and is computed when the series is read.
+//
+// Stores merge rows as position events and fills arrive. Fill volume is
+// attributed through `outcome_tokens`, so fills on tokens of markets not
+// indexed yet only count once `rebuild` runs after the market is.
+
+use crate::candles::{self, Resolution, Tick};
+use crate::db::models::{
+    Candle, MarketDailyStats, MarketSeriesPoint, PositionEvent as PositionEventRow,
+};
+use crate::polymarket::positions::{PositionEvent, PositionEventKind};
+use crate::store::MarketStore;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap};
+use tracing::{info, warn};
+
+impl MarketDailyStats {
+    /// Row with no activity
+    pub fn empty(condition_id: String, day: DateTime<Utc>) -> Self {
+        Self {
+            condition_id,
+            day,
+            split_amount: 0,
+            merge_amount: 0,
+            redeem_amount: 0,
+            volume: 0,
+            trades: 0,
+        }
+    }
+
+    /// Row of a single position event; `None` if the amount overflows `i64`
+    pub fn from_position(event: &PositionEvent) -> Option<Self> {
+        Some(Self::from_flow(
+            event.condition_id_hex(),
+            event.block_time,
+            event.kind,
+            event.collateral()?,
+        ))
+    }
+
+    /// Row of a stored position event; `None` if it can't be read back
+    pub fn from_row(event: &PositionEventRow) -> Option<Self> {
+        Some(Self::from_flow(
+            event.condition_id.clone(),
+            event.block_time,
+            event.kind.parse().ok()?,
+            event.amount.parse().ok()?,
+        ))
+    }
+
+    /// Row of a token's daily candle, for the market the token belongs to
+    pub fn from_candle(condition_id: String, candle: &Candle) -> Self {
+        Self {
+            volume: candle.notional,
+            trades: candle.trades,
+            ..Self::empty(
+                condition_id,
+                Resolution::Day.bucket_start(candle.bucket_start),
+            )
+        }
+    }
+
+    fn from_flow(
+        condition_id: String,
+        time: DateTime<Utc>,
+        kind: PositionEventKind,
+        amount: i64,
+    ) -> Self {
+        let mut row = Self::empty(condition_id, Resolution::Day.bucket_start(time));
+        match kind {
+            PositionEventKind::Split => row.split_amount = amount,
+            PositionEventKind::Merge => row.merge_amount = amount,
+            PositionEventKind::Redeem => row.redeem_amount = amount,
+        }
+        row
+    }
+
+    /// Fold `other`, a row for the same market and day, into this one
+    pub fn merge(&mut self, other: &MarketDailyStats) {
+        self.split_amount += other.split_amount;
+        self.merge_amount += other.merge_amount;
+        self.redeem_amount += other.redeem_amount;
+        self.volume += other.volume;
+        self.trades += other.trades;
+    }
+
+    /// Collateral added to the market over the day (negative when it shrank)
+    pub fn net_flow(&self) -> i64 {
+        self.split_amount - self.merge_amount - self.redeem_amount
+    }
+
+    /// Store key: (condition ID, day)
+    pub fn key(&self) -> (String, DateTime<Utc>) {
+        (self.condition_id.clone(), self.day)
+    }
+}
+
+/// Merge rows with the same market and day, in key order
+pub fn aggregate(rows: impl IntoIterator<Item = MarketDailyStats>) -> Vec<MarketDailyStats> {
+    let mut merged: BTreeMap<(String, DateTime<Utc>), MarketDailyStats> = BTreeMap::new();
+    for row in rows {
+        merged
+            .entry(row.key())
+            .and_modify(|existing| existing.merge(&row))
+            .or_insert(row);
+    }
+    merged.into_values().collect()
+}
+
+/// Time series of one market's rows, sorted by day
+pub fn series(rows: &[MarketDailyStats]) -> Vec<MarketSeriesPoint> {
+    let mut open_interest = 0;
+    rows.iter()
+        .map(|row| {
+            open_interest += row.net_flow();
+            MarketSeriesPoint {
+                day: row.day,
+                open_interest,
+                volume: row.volume,
+                trades: row.trades,
+                split_amount: row.split_amount,
+                merge_amount: row.merge_amount,
+                redeem_amount: row.redeem_amount,
+            }
+        })
+        .collect()
+}
+
+/// Recompute the rows of every day holding a stored position event or fill
+/// between `from_block` and `to_block` (inclusive)
+///
+/// Whole days are rebuilt from all of their stored events and fills, as in
+/// `candles::rebuild`. Returns the rows written.
+pub async fn rebuild(
+    store: &dyn MarketStore,
+    from_block: u64,
+    to_block: u64,
+) -> eyre::Result<usize> {
+    let ranges = [
+        store.get_position_time_range(from_block, to_block).await?,
+        store.get_fill_time_range(from_block, to_block).await?,
+    ];
+    let Some(first) = ranges.iter().flatten().map(|(first, _)| *first).min() else {
+        return Ok(0);
+    };
+    let last = ranges
+        .iter()
+        .flatten()
+        .map(|(_, last)| *last)
+        .max()
+        .unwrap_or(first);
+
+    let mut markets: HashMap<String, Option<String>> = HashMap::new();
+    let mut written = 0;
+    let mut day = Resolution::Day.bucket_start(first);
+    while day <= last {
+        let next = day + Resolution::Day.duration();
+
+        let events = store.get_position_events_between(day, next).await?;
+        let mut rows: Vec<MarketDailyStats> = Vec::new();
+        for event in &events {
+            match MarketDailyStats::from_row(event) {
+                Some(row) => rows.push(row),
+                None => warn!(
+                    "Skipping position event {}:{} with amount {}",
+                    event.tx_hash, event.log_index, event.amount
+                ),
+            }
+        }
+
+        let fills = store.get_fills_between(day, next).await?;
+        let ticks: Vec<Tick> = fills.iter().filter_map(Tick::from_row).collect();
+        for candle in candles::aggregate(&ticks) {
+            if candle.resolution != Resolution::Day.as_str() {
+                continue;
+            }
+            let condition_id = match markets.get(&candle.token_id) {
+                Some(condition_id) => condition_id.clone(),
+                None => {
+                    let condition_id = store
+                        .get_outcome_token(&candle.token_id)
+                        .await?
+                        .map(|token| token.condition_id);
+                    markets.insert(candle.token_id.clone(), condition_id.clone());
+                    condition_id
+                }
+            };
+            if let Some(condition_id) = condition_id {
+                rows.push(MarketDailyStats::from_candle(condition_id, &candle));
+            }
+        }
+
+        let rows = aggregate(rows);
+        store.replace_market_stats(day, &rows).await?;
+        info!(
+            "Rebuilt {} market rows from {} position events and {} fills on {}",
+            rows.len(),
+            events.len(),
+            fills.len(),
+            day.date_naive()
+        );
+        written += rows.len();
+        day = next;
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn row(day: u32, kind: PositionEventKind, amount: i64) -> MarketDailyStats {
+        MarketDailyStats::from_flow(
+            "0xaa".to_string(),
+            Utc.with_ymd_and_hms(2024, 8, day, 12, 30, 0).unwrap(),
+            kind,
+            amount,
+        )
+    }
+
+    #[test]
+    fn test_open_interest_is_running_net_flow() {
+        let rows = aggregate([
+            row(2, PositionEventKind::Merge, 30),
+            row(1, PositionEventKind::Split, 100),
+            row(2, PositionEventKind::Split, 50),
+            row(3, PositionEventKind::Redeem, 120),
+        ]);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            rows[0].day,
+            Utc.with_ymd_and_hms(2024, 8, 1, 0, 0, 0).unwrap()
+        );
+        assert_eq!(rows[1].net_flow(), 20);
+
+        let points = series(&rows);
+        let open_interest: Vec<i64> = points.iter().map(|p| p.open_interest).collect();
+        assert_eq!(open_interest, [100, 120, 0]);
+        assert_eq!(points[2].redeem_amount, 120);
+    }
+}
@@ -0,0 +1,255 @@
+// Prometheus Metrics for indexer health
+//
+// Metrics are registered on the default prometheus registry and exposed on
+// an HTTP `/metrics` endpoint by long-running commands (see `[metrics]` in
+// the config). Recording is always on; serving is opt-in.
+
+use crate::config::MetricsConfig;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use eyre::Result;
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::sync::LazyLock;
+use tracing::{error, info};
+
+/// Latency buckets for HTTP and database calls (seconds)
+const LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Raw logs returned by eth_getLogs
+pub static LOGS_FETCHED: LazyLock<IntCounter> = LazyLock::new(|| {
+    register_int_counter!("indexer_logs_fetched_total", "Logs returned by eth_getLogs").unwrap()
+});
+
+/// Logs successfully decoded into events, by event name
+pub static EVENTS_DECODED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_events_decoded_total",
+        "Logs decoded into events",
+        &["event"]
+    )
+    .unwrap()
+});
+
+/// Logs that failed to decode, by event name
+pub static DECODE_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_decode_failures_total",
+        "Logs that failed to decode",
+        &["event"]
+    )
+    .unwrap()
+});
+
+/// Logs with no registered decoder for their address and topic0
+pub static UNKNOWN_EVENTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_unknown_events_total",
+        "Logs with no registered decoder, by topic0",
+        &["topic"]
+    )
+    .unwrap()
+});
+
+/// Gamma API lookups by endpoint and result (hit, miss, or the error kind)
+pub static GAMMA_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_gamma_requests_total",
+        "Gamma API requests by endpoint and result",
+        &["endpoint", "result"]
+    )
+    .unwrap()
+});
+
+/// Gamma API requests retried, by error kind
+pub static GAMMA_RETRIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_gamma_retries_total",
+        "Gamma API requests retried, by error kind",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+/// Gamma response cache lookups, by result (hit, miss)
+pub static GAMMA_CACHE: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_gamma_cache_total",
+        "Gamma response cache lookups, by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// Gamma API request latency by endpoint
+pub static GAMMA_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "indexer_gamma_request_duration_seconds",
+        "Gamma API request latency",
+        &["endpoint"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+/// Database write latency by operation
+pub static DB_WRITE_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "indexer_db_write_duration_seconds",
+        "Database write latency",
+        &["operation"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .unwrap()
+});
+
+/// Market status transitions applied, by new status
+pub static STATUS_TRANSITIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_market_status_transitions_total",
+        "Market status transitions applied, by new status",
+        &["status"]
+    )
+    .unwrap()
+});
+
+/// Data anomalies recorded, by kind
+pub static DATA_ANOMALIES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_data_anomalies_total",
+        "Data anomalies recorded, by kind",
+        &["kind"]
+    )
+    .unwrap()
+});
+
+/// Block headers needed for indexed logs, by result (fetched, cached)
+pub static BLOCK_HEADERS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "indexer_block_headers_total",
+        "Block headers needed for indexed logs, by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+/// Highest block whose logs have been fully processed
+pub static LAST_INDEXED_BLOCK: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "indexer_last_indexed_block",
+        "Highest block whose logs have been processed"
+    )
+    .unwrap()
+});
+
+/// Latest chain head seen by the indexer
+pub static CHAIN_HEAD_BLOCK: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!("indexer_chain_head_block", "Latest chain head block seen").unwrap()
+});
+
+/// Blocks between the chain head and the last indexed block
+pub static HEAD_LAG_BLOCKS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "indexer_head_lag_blocks",
+        "Blocks between the chain head and the last indexed block"
+    )
+    .unwrap()
+});
+
+/// Record indexing progress against the chain head
+pub fn record_progress(head: u64, last_indexed: u64) {
+    CHAIN_HEAD_BLOCK.set(head as i64);
+    LAST_INDEXED_BLOCK.set(last_indexed as i64);
+    HEAD_LAG_BLOCKS.set(head.saturating_sub(last_indexed) as i64);
+}
+
+/// Record the outcome of decoding one log
+pub fn record_decode<T, E>(event: &str, result: &std::result::Result<T, E>) {
+    match result {
+        Ok(_) => EVENTS_DECODED.with_label_values(&[event]).inc(),
+        Err(_) => DECODE_FAILURES.with_label_values(&[event]).inc(),
+    }
+}
+
+/// Register every metric so all series appear before their first update
+pub fn register_all() {
+    LazyLock::force(&LOGS_FETCHED);
+    LazyLock::force(&EVENTS_DECODED);
+    LazyLock::force(&DECODE_FAILURES);
+    LazyLock::force(&UNKNOWN_EVENTS);
+    LazyLock::force(&GAMMA_REQUESTS);
+    LazyLock::force(&GAMMA_RETRIES);
+    LazyLock::force(&GAMMA_CACHE);
+    LazyLock::force(&GAMMA_LATENCY);
+    LazyLock::force(&DB_WRITE_LATENCY);
+    LazyLock::force(&STATUS_TRANSITIONS);
+    LazyLock::force(&DATA_ANOMALIES);
+    LazyLock::force(&BLOCK_HEADERS);
+    LazyLock::force(&LAST_INDEXED_BLOCK);
+    LazyLock::force(&CHAIN_HEAD_BLOCK);
+    LazyLock::force(&HEAD_LAG_BLOCKS);
+}
+
+/// Render all registered metrics in the Prometheus text format
+pub fn render() -> String {
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&prometheus::gather(), &mut buffer)
+        .expect("text encoding of metrics cannot fail");
+    String::from_utf8(buffer).expect("metrics text is UTF-8")
+}
+
+async fn metrics_handler() -> impl IntoResponse {
+    ([(CONTENT_TYPE, prometheus::TEXT_FORMAT)], render())
+}
+
+/// Serve `/metrics` on the given address until the process exits
+pub async fn serve(addr: SocketAddr) -> Result<()> {
+    let app = Router::new().route("/metrics", get(metrics_handler));
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(
+        "Serving metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+/// Spawn the metrics server in the background if `[metrics]` enables it
+pub fn spawn_from_config(config: &MetricsConfig) {
+    let Some(addr) = config.listen_addr else {
+        return;
+    };
+    register_all();
+
+    tokio::spawn(async move {
+        if let Err(e) = serve(addr).await {
+            error!("Metrics server failed: {}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_includes_progress() {
+        register_all();
+        record_progress(1_000, 990);
+        record_decode::<(), ()>("TokenRegistered", &Err(()));
+
+        let text = render();
+        assert!(text.contains("indexer_head_lag_blocks 10"));
+        assert!(text.contains("indexer_last_indexed_block 990"));
+        assert!(text.contains("indexer_decode_failures_total{event=\"TokenRegistered\"} 1"));
+    }
+}
@@ -1,6 +1,6 @@
 // Polymarket Contract Addresses and Constants on Polygon
 
-use ethers::types::{H160, H256};
+use ethers::types::H160;
 use std::str::FromStr;
 
 /// CTFExchange contract address (Polygon mainnet) - raw string
@@ -11,6 +11,25 @@ pub const CTF_EXCHANGE_ADDRESS: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B898
 /// Conditional Tokens Framework contract address (Polygon mainnet) - raw string
 pub const CTF_CONTRACT_ADDRESS: &str = "0x4D97DCd97eC945f40cF65F87097ACe5EA0476045";
 
+/// NegRisk CTFExchange address (Polygon mainnet) - raw string
+///
+/// Same contract code as the CTFExchange, trading multi-outcome markets
+pub const NEG_RISK_EXCHANGE_ADDRESS: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+
+/// NegRiskAdapter address (Polygon mainnet) - raw string
+pub const NEG_RISK_ADAPTER_ADDRESS: &str = "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296";
+
+/// UMA CTF Adapter deployments (Polygon mainnet, v2 then v3) - raw strings
+///
+/// Oracles for binary markets: each emits the lifecycle events of the
+/// questions it initialized and reports their payouts to the Conditional
+/// Tokens contract. The NegRisk UMA adapter is left out: it reports to the
+/// NegRiskOperator rather than preparing conditions itself.
+pub const UMA_CTF_ADAPTER_ADDRESSES: &[&str] = &[
+    "0x6A9D222616C90FcA5754cd1333cFD9b7fb6a4F74",
+    "0x157Ce2d672854c848c9b79C49a8Cc6cc89176a49",
+];
+
 /// Get CTFExchange address as H160 (parsed)
 pub fn ctf_exchange_address() -> H160 {
     H160::from_str(CTF_EXCHANGE_ADDRESS).expect("Invalid CTF_EXCHANGE_ADDRESS constant")
@@ -21,8 +40,23 @@ pub fn ctf_contract_address() -> H160 {
     H160::from_str(CTF_CONTRACT_ADDRESS).expect("Invalid CTF_CONTRACT_ADDRESS constant")
 }
 
-/// TokenRegistered event signature: TokenRegistered(uint256,uint256,bytes32)
-pub fn token_registered_event_signature() -> H256 {
-    let signature = ethers::core::utils::keccak256(b"TokenRegistered(uint256,uint256,bytes32)");
-    H256::from(signature)
+/// USDC.e (bridged USDC) address (Polygon mainnet) - raw string
+///
+/// Collateral of the outcome tokens traded on the CTFExchange
+pub const USDC_E_ADDRESS: &str = "0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174";
+
+/// NegRisk WrappedCollateral address (Polygon mainnet) - raw string
+///
+/// USDC.e wrapper the NegRiskAdapter uses as collateral for its markets
+pub const NEG_RISK_WRAPPED_COLLATERAL_ADDRESS: &str = "0x3A3BD7bb9528E159577F7C2e685CC81A765002E2";
+
+/// Get USDC.e address as H160 (parsed)
+pub fn usdc_e_address() -> H160 {
+    H160::from_str(USDC_E_ADDRESS).expect("Invalid USDC_E_ADDRESS constant")
+}
+
+/// Get NegRisk WrappedCollateral address as H160 (parsed)
+pub fn neg_risk_wrapped_collateral_address() -> H160 {
+    H160::from_str(NEG_RISK_WRAPPED_COLLATERAL_ADDRESS)
+        .expect("Invalid NEG_RISK_WRAPPED_COLLATERAL_ADDRESS constant")
 }
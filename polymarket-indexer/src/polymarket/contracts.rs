@@ -0,0 +1,11 @@
+// Contract bindings generated from the ABIs in abi/
+//
+// The ABIs list the events the indexer decodes. The NegRisk exchange runs the
+// same code as the CTFExchange, so `ctf_exchange` covers both.
+
+use ethers::contract::abigen;
+
+abigen!(CTFExchange, "abi/CTFExchange.json");
+abigen!(NegRiskAdapter, "abi/NegRiskAdapter.json");
+abigen!(ConditionalTokens, "abi/ConditionalTokens.json");
+abigen!(UmaCtfAdapter, "abi/UmaCtfAdapter.json");
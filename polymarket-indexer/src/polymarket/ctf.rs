@@ -0,0 +1,303 @@
+// Conditional Tokens Framework ID derivation
+//
+// Mirrors CTHelpers from the Gnosis ConditionalTokens contract, so outcome
+// token IDs can be computed offline:
+// - conditionId  = keccak256(oracle, questionId, outcomeSlotCount)
+// - collectionId = a point on alt_bn128 hashed from (conditionId, indexSet),
+//   added to the parent collection's point, compressed to its x coordinate
+//   with the parity of y in bit 254
+// - positionId   = uint256(keccak256(collateralToken, collectionId))
+//
+// Polymarket outcome `i` of a condition is the position with index set
+// `1 << i` under the empty parent collection, so a market's token IDs follow
+// from its condition ID and collateral alone.
+
+use crate::error::{IndexerError, Result};
+use crate::polymarket::constants::{neg_risk_wrapped_collateral_address, usdc_e_address};
+use crate::polymarket::events::TokenRegistered;
+use ethers::types::{Address, U256, U512};
+use ethers::utils::keccak256;
+use std::sync::LazyLock;
+
+/// alt_bn128 field modulus
+static P: LazyLock<U256> = LazyLock::new(|| {
+    U256::from_dec_str(
+        "21888242871839275222246405745257275088696311157297823662689037894645226208583",
+    )
+    .expect("Invalid alt_bn128 modulus")
+});
+
+/// Curve constant: y^2 = x^3 + 3
+const B: u64 = 3;
+
+/// Bit set in a collection ID when the point's y coordinate is odd
+const ODD_FLAG_BIT: usize = 254;
+
+/// Condition ID for a question reported on by `oracle`
+pub fn condition_id(oracle: Address, question_id: [u8; 32], outcome_slot_count: u32) -> [u8; 32] {
+    let mut packed = Vec::with_capacity(20 + 32 + 32);
+    packed.extend_from_slice(oracle.as_bytes());
+    packed.extend_from_slice(&question_id);
+    packed.extend_from_slice(&word(U256::from(outcome_slot_count)));
+    keccak256(packed)
+}
+
+/// Collection ID for the outcomes in `index_set`, nested under `parent`
+///
+/// `parent` is all zeros for a top-level collection. Fails only when
+/// `parent` is not a valid collection ID.
+pub fn collection_id(
+    parent: [u8; 32],
+    condition_id: [u8; 32],
+    index_set: U256,
+) -> Result<[u8; 32]> {
+    let p = *P;
+    let mut packed = Vec::with_capacity(64);
+    packed.extend_from_slice(&condition_id);
+    packed.extend_from_slice(&word(index_set));
+    let hash = U256::from_big_endian(&keccak256(packed));
+
+    // Walk x forward from the hash until x^3 + 3 is a square
+    let odd = hash.bit(255);
+    let mut x1 = hash % p;
+    let mut y1;
+    loop {
+        x1 = add_mod(x1, U256::one());
+        let yy = curve_rhs(x1);
+        y1 = sqrt(yy);
+        if mul_mod(y1, y1) == yy {
+            break;
+        }
+    }
+    if odd != y1.bit(0) {
+        y1 = p - y1;
+    }
+
+    let x2 = U256::from_big_endian(&parent);
+    if !x2.is_zero() {
+        let odd = x2.bit(ODD_FLAG_BIT);
+        let x2 = (x2 << 2) >> 2;
+        let yy = curve_rhs(x2);
+        let mut y2 = sqrt(yy);
+        if odd != y2.bit(0) {
+            y2 = p - y2;
+        }
+        if mul_mod(y2, y2) != yy {
+            return Err(IndexerError::Decode(format!(
+                "invalid parent collection ID 0x{}",
+                hex::encode(parent)
+            )));
+        }
+        (x1, y1) = ec_add((x1, y1), (x2, y2));
+    }
+
+    if y1.bit(0) {
+        x1 ^= U256::one() << ODD_FLAG_BIT;
+    }
+    Ok(word(x1))
+}
+
+/// Position (ERC-1155 token) ID of a collection backed by `collateral`
+pub fn position_id(collateral: Address, collection_id: [u8; 32]) -> U256 {
+    let mut packed = Vec::with_capacity(20 + 32);
+    packed.extend_from_slice(collateral.as_bytes());
+    packed.extend_from_slice(&collection_id);
+    U256::from_big_endian(&keccak256(packed))
+}
+
+/// Token IDs of each outcome of a condition, in outcome order
+pub fn outcome_token_ids(
+    collateral: Address,
+    condition_id: [u8; 32],
+    outcome_slot_count: u32,
+) -> Vec<U256> {
+    (0..outcome_slot_count)
+        .map(|i| {
+            let collection = collection_id([0; 32], condition_id, U256::one() << i)
+                .expect("empty parent collection is always valid");
+            position_id(collateral, collection)
+        })
+        .collect()
+}
+
+/// Outcome tokens of a binary market, derived from its condition ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinaryPositions {
+    /// ERC-20 the positions are backed by
+    pub collateral: Address,
+    /// Token ID of outcome 0 and outcome 1
+    pub token_ids: [U256; 2],
+}
+
+impl BinaryPositions {
+    pub fn derive(collateral: Address, condition_id: [u8; 32]) -> Self {
+        let ids = outcome_token_ids(collateral, condition_id, 2);
+        Self {
+            collateral,
+            token_ids: [ids[0], ids[1]],
+        }
+    }
+
+    /// Outcome index of `token_id` (0 or 1), if it belongs to this market
+    pub fn outcome_index(&self, token_id: U256) -> Option<usize> {
+        self.token_ids.iter().position(|id| *id == token_id)
+    }
+
+    /// Whether `a` and `b` are exactly this market's two tokens, in any order
+    pub fn matches(&self, a: U256, b: U256) -> bool {
+        a != b && self.outcome_index(a).is_some() && self.outcome_index(b).is_some()
+    }
+}
+
+/// Check a registered token pair against the IDs derived from its condition
+///
+/// Polymarket markets use USDC.e as collateral, NegRisk markets the
+/// adapter's WrappedCollateral; the pair is tried against both. Returns the
+/// derived positions when the pair matches, `None` when it matches neither.
+pub fn verify_registration(event: &TokenRegistered) -> Option<BinaryPositions> {
+    [usdc_e_address(), neg_risk_wrapped_collateral_address()]
+        .into_iter()
+        .map(|collateral| BinaryPositions::derive(collateral, event.condition_id))
+        .find(|positions| positions.matches(event.token0, event.token1))
+}
+
+/// Label of outcome `index` from the market's outcome list (e.g. Gamma's
+/// `["Yes", "No"]`)
+pub fn outcome_label(outcomes: &[String], index: usize) -> Option<&str> {
+    outcomes.get(index).map(String::as_str)
+}
+
+/// Big-endian 32-byte word, as `abi.encodePacked` lays out a uint256
+fn word(value: U256) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    value.to_big_endian(&mut out);
+    out
+}
+
+fn add_mod(a: U256, b: U256) -> U256 {
+    // Operands are below P < 2^254, so the sum cannot overflow
+    (a + b) % *P
+}
+
+fn sub_mod(a: U256, b: U256) -> U256 {
+    (a + *P - b) % *P
+}
+
+fn mul_mod(a: U256, b: U256) -> U256 {
+    let product = a.full_mul(b) % U512::from(*P);
+    U256::try_from(product).expect("value reduced mod P fits in 256 bits")
+}
+
+fn pow_mod(mut base: U256, mut exponent: U256) -> U256 {
+    let mut result = U256::one();
+    while !exponent.is_zero() {
+        if exponent.bit(0) {
+            result = mul_mod(result, base);
+        }
+        base = mul_mod(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// x^3 + 3 mod P
+fn curve_rhs(x: U256) -> U256 {
+    add_mod(mul_mod(x, mul_mod(x, x)), U256::from(B))
+}
+
+/// Candidate square root; only a root when its square equals `a` (P = 3 mod 4)
+fn sqrt(a: U256) -> U256 {
+    pow_mod(a, (*P + 1) / 4)
+}
+
+fn inverse(a: U256) -> U256 {
+    pow_mod(a, *P - 2)
+}
+
+/// Point addition on alt_bn128, as the ecAdd precompile computes it
+///
+/// (0, 0) is the point at infinity.
+fn ec_add(a: (U256, U256), b: (U256, U256)) -> (U256, U256) {
+    let infinity = (U256::zero(), U256::zero());
+    if a == infinity {
+        return b;
+    }
+    if b == infinity {
+        return a;
+    }
+
+    let (x1, y1) = a;
+    let (x2, y2) = b;
+    let slope = if x1 == x2 {
+        if y1 != y2 || y1.is_zero() {
+            return infinity;
+        }
+        // Doubling: 3x^2 / 2y
+        mul_mod(
+            mul_mod(U256::from(3), mul_mod(x1, x1)),
+            inverse(add_mod(y1, y1)),
+        )
+    } else {
+        mul_mod(sub_mod(y2, y1), inverse(sub_mod(x2, x1)))
+    };
+
+    let x3 = sub_mod(sub_mod(mul_mod(slope, slope), x1), x2);
+    let y3 = sub_mod(mul_mod(slope, sub_mod(x1, x3)), y1);
+    (x3, y3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// "Presidential Election Winner 2024" - Donald Trump (NegRisk market)
+    const CONDITION_ID: &str = "dd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917";
+    const YES_TOKEN: &str =
+        "21742633143463906290569050155826241533067272736897614950488156847949938836455";
+    const NO_TOKEN: &str =
+        "48331043336612883890938759509493159234755048973500640148014422747788308965732";
+
+    fn condition() -> [u8; 32] {
+        hex::decode(CONDITION_ID).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_verifies_registered_tokens() {
+        let yes = U256::from_dec_str(YES_TOKEN).unwrap();
+        let no = U256::from_dec_str(NO_TOKEN).unwrap();
+        let mut event = TokenRegistered {
+            token0: no,
+            token1: yes,
+            condition_id: condition(),
+            block_number: 1,
+            tx_hash: String::new(),
+            transaction_index: 0,
+            log_index: 0,
+        };
+
+        let positions = verify_registration(&event).unwrap();
+        assert_eq!(positions.collateral, neg_risk_wrapped_collateral_address());
+        assert_eq!(positions.token_ids, [yes, no]);
+        assert_eq!(positions.outcome_index(event.token0), Some(1));
+        assert_eq!(event.outcome_token_ids(), [yes, no]);
+        let outcomes = ["Yes".to_string(), "No".to_string()];
+        assert_eq!(outcome_label(&outcomes, 1), Some("No"));
+
+        event.token1 = no;
+        assert_eq!(verify_registration(&event), None);
+    }
+
+    #[test]
+    fn test_nested_collections_commute() {
+        let a = condition();
+        let b = condition_id(usdc_e_address(), [7; 32], 2);
+        let one = U256::one();
+        let two = U256::from(2);
+
+        let a_then_b = collection_id(collection_id([0; 32], a, one).unwrap(), b, two).unwrap();
+        let b_then_a = collection_id(collection_id([0; 32], b, two).unwrap(), a, one).unwrap();
+        assert_eq!(a_then_b, b_then_a);
+        // x = 4 is not on the curve (4^3 + 3 has no square root mod P)
+        assert!(collection_id(word(U256::from(4)), a, one).is_err());
+    }
+}
@@ -1,19 +1,38 @@
-// TokenRegistered Event Definition and Processing
+// Polymarket Event Decoding and Filters
 //
-// Event: TokenRegistered(uint256 indexed token0, uint256 indexed token1, bytes32 indexed conditionId)
-// Emitted by: CTFExchange contract
+// Events are decoded with the bindings generated from abi/ (see `contracts`):
+// - `PolymarketEvent` decodes a log from any supported contract
+// - `TokenRegistered` is a decoded market registration plus where it was emitted;
+//   `TokenRegistered::pair` checks the two mirrored events the exchange emits
+//   per market and picks a canonical token order
+// - filter builders select events by their ABI signatures
 
-use ethers::types::{Log, U256};
-use eyre::{eyre, Result};
+use crate::error::{IndexerError, Result};
+use crate::polymarket::contracts::conditional_tokens::{
+    ConditionalTokensEvents, CONDITIONALTOKENS_ABI,
+};
+use crate::polymarket::contracts::ctf_exchange::{
+    CTFExchangeEvents, TokenRegisteredFilter, CTFEXCHANGE_ABI,
+};
+use crate::polymarket::contracts::neg_risk_adapter::{NegRiskAdapterEvents, NEGRISKADAPTER_ABI};
+use crate::polymarket::contracts::uma_ctf_adapter::{UmaCtfAdapterEvents, UMACTFADAPTER_ABI};
+use crate::polymarket::ctf;
+use ethers::abi::RawLog;
+use ethers::contract::{EthEvent, EthLogDecode};
+use ethers::types::{Address, Filter, Log, H256, U256};
+use std::fmt;
 
 /// TokenRegistered event structure
 ///
-/// Emitted when a new outcome token pair is registered for trading
+/// Emitted by the CTFExchange when a new outcome token pair is registered for
+/// trading: TokenRegistered(uint256 indexed token0, uint256 indexed token1,
+/// bytes32 indexed conditionId)
 #[derive(Debug, Clone)]
 pub struct TokenRegistered {
-    /// First outcome token ID (typically YES outcome)
+    /// One outcome token ID of the pair (see `ctf::verify_registration` for
+    /// which outcome it is)
     pub token0: U256,
-    /// Second outcome token ID (typically NO outcome)
+    /// The other outcome token ID of the pair
     pub token1: U256,
     /// Condition ID - unique identifier for the market
     pub condition_id: [u8; 32],
@@ -21,52 +40,61 @@ pub struct TokenRegistered {
     pub block_number: u64,
     /// Transaction hash
     pub tx_hash: String,
+    /// Position of the transaction in its block
+    pub transaction_index: u64,
+    /// Position of the log in its block
+    pub log_index: u64,
 }
 
 impl TokenRegistered {
     /// Parse a TokenRegistered event from a raw log
-    ///
-    /// Expected log structure:
-    /// - topics[0]: Event signature (keccak256 of "TokenRegistered(uint256,uint256,bytes32)")
-    /// - topics[1]: token0 (first indexed parameter)
-    /// - topics[2]: token1 (second indexed parameter)
-    /// - topics[3]: conditionId (third indexed parameter)
     pub fn from_log(log: &Log) -> Result<Self> {
-        // Validate we have exactly 4 topics (signature + 3 indexed params)
-        if log.topics.len() != 4 {
-            return Err(eyre!(
-                "Invalid TokenRegistered log: expected 4 topics, got {}",
-                log.topics.len()
-            ));
-        }
-
-        // Extract token0 from topics[1]
-        let token0 = U256::from(log.topics[1].as_bytes());
-
-        // Extract token1 from topics[2]
-        let token1 = U256::from(log.topics[2].as_bytes());
-
-        // Extract conditionId from topics[3] (convert H256 to [u8; 32])
-        let condition_id: [u8; 32] = log.topics[3].0;
+        let event = <TokenRegisteredFilter as EthLogDecode>::decode_log(&RawLog::from(log.clone()))
+            .map_err(|e| IndexerError::Decode(format!("Invalid TokenRegistered log: {}", e)))?;
+        Self::from_event(event, log)
+    }
 
-        // Extract block number
-        let block_number = log
-            .block_number
-            .ok_or_else(|| eyre!("Log missing block_number"))?
+    /// Attach the block, transaction and log position of `log` to a
+    /// decoded event
+    pub fn from_event(event: TokenRegisteredFilter, log: &Log) -> Result<Self> {
+        let (block_number, tx_hash, log_index) = log_position(log)?;
+        let transaction_index = log
+            .transaction_index
+            .ok_or_else(|| IndexerError::Decode("Log missing transaction_index".into()))?
             .as_u64();
 
-        // Extract transaction hash
-        let tx_hash = log
-            .transaction_hash
-            .ok_or_else(|| eyre!("Log missing transaction_hash"))?
-            .to_string();
-
         Ok(TokenRegistered {
-            token0,
-            token1,
-            condition_id,
+            token0: event.token_0,
+            token1: event.token_1,
+            condition_id: event.condition_id,
             block_number,
             tx_hash,
+            transaction_index,
+            log_index,
+        })
+    }
+
+    /// Rebuild the registration of a stored market from its condition ID
+    /// (hex, with 0x prefix) and token IDs (decimal strings); `None` when one
+    /// doesn't parse
+    ///
+    /// Markets don't keep the log position, so it is left at 0.
+    pub fn from_stored(
+        condition_id: &str,
+        token0: &str,
+        token1: &str,
+        block_number: u64,
+        tx_hash: &str,
+    ) -> Option<Self> {
+        let condition_id = hex::decode(condition_id.strip_prefix("0x")?).ok()?;
+        Some(TokenRegistered {
+            token0: U256::from_dec_str(token0).ok()?,
+            token1: U256::from_dec_str(token1).ok()?,
+            condition_id: condition_id.try_into().ok()?,
+            block_number,
+            tx_hash: tx_hash.to_string(),
+            transaction_index: 0,
+            log_index: 0,
         })
     }
 
@@ -77,11 +105,68 @@ impl TokenRegistered {
         println!("  Block: {}", self.block_number);
         println!("  TX: {}", self.tx_hash);
         println!("  Condition ID: 0x{}", hex::encode(self.condition_id));
-        println!("  Token 0 (YES): {}", self.token0);
-        println!("  Token 1 (NO):  {}", self.token1);
+        println!("  Token 0: {}", self.token0);
+        println!("  Token 1: {}", self.token1);
         println!("=================================");
     }
 
+    /// Token IDs in outcome order (outcome 0 first)
+    ///
+    /// The pair is ordered by the IDs derived from the condition; a pair
+    /// that doesn't verify is sorted ascending, so the order never depends
+    /// on which of the mirrored events was seen.
+    pub fn outcome_token_ids(&self) -> [U256; 2] {
+        match ctf::verify_registration(self) {
+            Some(positions) => positions.token_ids,
+            None => ascending(self.token0, self.token1),
+        }
+    }
+
+    /// Pair the TokenRegistered events of one condition
+    ///
+    /// The exchange registers each market with two events in one
+    /// transaction, the second swapping the first one's tokens. The earliest
+    /// event (by block, then transaction, then log order) is kept with its
+    /// tokens in canonical order (see `outcome_token_ids`); anything else
+    /// about the pair is reported as an anomaly. `None` when `events` is
+    /// empty.
+    pub fn pair(mut events: Vec<TokenRegistered>) -> Option<PairedRegistration> {
+        events.sort_by_key(|e| (e.block_number, e.transaction_index, e.log_index));
+        let mut event = events.first()?.clone();
+        let mut anomalies = Vec::new();
+
+        match events.get(1) {
+            None => anomalies.push(RegistrationAnomaly::Unpaired),
+            Some(second) => {
+                if (second.token0, second.token1) != (event.token1, event.token0) {
+                    anomalies.push(RegistrationAnomaly::NotMirrored {
+                        first: [event.token0, event.token1],
+                        second: [second.token0, second.token1],
+                    });
+                }
+                if second.tx_hash != event.tx_hash {
+                    anomalies.push(RegistrationAnomaly::SplitTransactions {
+                        first: event.tx_hash.clone(),
+                        second: second.tx_hash.clone(),
+                    });
+                }
+            }
+        }
+        if events.len() > 2 {
+            anomalies.push(RegistrationAnomaly::ExtraEvents(events.len()));
+        }
+
+        [event.token0, event.token1] = match ctf::verify_registration(&event) {
+            Some(positions) => positions.token_ids,
+            None => {
+                anomalies.push(RegistrationAnomaly::Unverified);
+                ascending(event.token0, event.token1)
+            }
+        };
+
+        Some(PairedRegistration { event, anomalies })
+    }
+
     /// Get the condition ID as a hex string (with 0x prefix)
     /// Used for querying the Gamma API
     pub fn condition_id_hex(&self) -> String {
@@ -89,6 +174,261 @@ impl TokenRegistered {
     }
 }
 
-// TODO: Add derive(EthEvent) once we implement event decoding
-// TODO: Add methods to decode from Log
-// TODO: Add filter creation for querying TokenRegistered events
+fn ascending(a: U256, b: U256) -> [U256; 2] {
+    if a <= b {
+        [a, b]
+    } else {
+        [b, a]
+    }
+}
+
+/// A market registration checked against its mirrored duplicate
+#[derive(Debug, Clone)]
+pub struct PairedRegistration {
+    /// Earliest event, with its tokens in canonical order
+    pub event: TokenRegistered,
+    pub anomalies: Vec<RegistrationAnomaly>,
+}
+
+/// Problem found while pairing a market's TokenRegistered events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistrationAnomaly {
+    /// Only one event was emitted
+    Unpaired,
+    /// The second event doesn't swap the first one's tokens
+    NotMirrored { first: [U256; 2], second: [U256; 2] },
+    /// The two events were emitted in different transactions
+    SplitTransactions { first: String, second: String },
+    /// More than two events were emitted (the count)
+    ExtraEvents(usize),
+    /// Neither collateral derives the token pair from the condition
+    Unverified,
+}
+
+impl RegistrationAnomaly {
+    /// Kind stored in `data_anomalies`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RegistrationAnomaly::Unpaired => "unpaired_registration",
+            RegistrationAnomaly::NotMirrored { .. } => "unmirrored_registration",
+            RegistrationAnomaly::SplitTransactions { .. } => "split_registration",
+            RegistrationAnomaly::ExtraEvents(_) => "extra_registrations",
+            RegistrationAnomaly::Unverified => "unverified_token_pair",
+        }
+    }
+}
+
+impl fmt::Display for RegistrationAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistrationAnomaly::Unpaired => {
+                write!(
+                    f,
+                    "TokenRegistered emitted once, without its mirrored event"
+                )
+            }
+            RegistrationAnomaly::NotMirrored { first, second } => write!(
+                f,
+                "tokens ({}, {}) are not mirrored by ({}, {})",
+                first[0], first[1], second[0], second[1]
+            ),
+            RegistrationAnomaly::SplitTransactions { first, second } => write!(
+                f,
+                "mirrored TokenRegistered events in different transactions {} and {}",
+                first, second
+            ),
+            RegistrationAnomaly::ExtraEvents(count) => {
+                write!(f, "TokenRegistered emitted {} times, expected 2", count)
+            }
+            RegistrationAnomaly::Unverified => write!(
+                f,
+                "token pair does not derive from the condition under USDC.e or WrappedCollateral"
+            ),
+        }
+    }
+}
+
+/// Any event emitted by a supported Polymarket contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolymarketEvent {
+    /// CTFExchange or NegRisk exchange
+    Exchange(CTFExchangeEvents),
+    NegRiskAdapter(NegRiskAdapterEvents),
+    ConditionalTokens(ConditionalTokensEvents),
+    UmaCtfAdapter(UmaCtfAdapterEvents),
+}
+
+impl PolymarketEvent {
+    /// Decode a log by its topics, trying each contract ABI in turn
+    ///
+    /// The ABIs share no event signatures, so the emitting address is not
+    /// needed to pick the right one.
+    pub fn decode(log: &Log) -> Result<Self> {
+        let raw = RawLog::from(log.clone());
+
+        if let Ok(event) = CTFExchangeEvents::decode_log(&raw) {
+            return Ok(PolymarketEvent::Exchange(event));
+        }
+        if let Ok(event) = NegRiskAdapterEvents::decode_log(&raw) {
+            return Ok(PolymarketEvent::NegRiskAdapter(event));
+        }
+        if let Ok(event) = ConditionalTokensEvents::decode_log(&raw) {
+            return Ok(PolymarketEvent::ConditionalTokens(event));
+        }
+        if let Ok(event) = UmaCtfAdapterEvents::decode_log(&raw) {
+            return Ok(PolymarketEvent::UmaCtfAdapter(event));
+        }
+
+        let message = match log.topics.first() {
+            Some(topic) => match event_name(*topic) {
+                Some(name) => format!("Invalid {} log", name),
+                None => format!("Unknown event topic {:?}", topic),
+            },
+            None => "Log has no topics".to_string(),
+        };
+        Err(IndexerError::Decode(message))
+    }
+}
+
+/// Block number, transaction hash (0x-prefixed hex) and log index of a
+/// mined log
+pub fn log_position(log: &Log) -> Result<(u64, String, u64)> {
+    let block_number = log
+        .block_number
+        .ok_or_else(|| IndexerError::Decode("Log missing block_number".into()))?
+        .as_u64();
+    let tx_hash = log
+        .transaction_hash
+        .ok_or_else(|| IndexerError::Decode("Log missing transaction_hash".into()))?;
+    let log_index = log
+        .log_index
+        .ok_or_else(|| IndexerError::Decode("Log missing log_index".into()))?
+        .as_u64();
+    Ok((block_number, format!("{:?}", tx_hash), log_index))
+}
+
+/// Event name for a topic0 signature from any of the contract ABIs
+pub fn event_name(topic0: H256) -> Option<&'static str> {
+    [
+        &*CTFEXCHANGE_ABI,
+        &*NEGRISKADAPTER_ABI,
+        &*CONDITIONALTOKENS_ABI,
+        &*UMACTFADAPTER_ABI,
+    ]
+    .into_iter()
+    .flat_map(|abi| abi.events())
+    .find(|event| event.signature() == topic0)
+    .map(|event| event.name.as_str())
+}
+
+/// Filter for one event type emitted by `address`
+pub fn event_filter<E: EthEvent>(address: Address) -> Filter {
+    Filter::new().address(address).topic0(E::signature())
+}
+
+/// Filter for TokenRegistered events from an exchange
+pub fn token_registered_filter(exchange: Address) -> Filter {
+    event_filter::<TokenRegisteredFilter>(exchange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::constants::ctf_exchange_address;
+    use ethers::types::U64;
+
+    fn token_registered_log() -> Log {
+        let word = |n: u64| H256::from_low_u64_be(n);
+        Log {
+            address: ctf_exchange_address(),
+            topics: vec![
+                TokenRegisteredFilter::signature(),
+                word(11),
+                word(22),
+                H256::repeat_byte(0xab),
+            ],
+            block_number: Some(U64::from(100)),
+            transaction_hash: Some(H256::repeat_byte(0x01)),
+            transaction_index: Some(U64::from(3)),
+            log_index: Some(U256::from(7)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_signature_matches_abi() {
+        let expected = ethers::utils::keccak256(b"TokenRegistered(uint256,uint256,bytes32)");
+        assert_eq!(TokenRegisteredFilter::signature(), H256::from(expected));
+        assert_eq!(
+            event_name(TokenRegisteredFilter::signature()),
+            Some("TokenRegistered")
+        );
+    }
+
+    #[test]
+    fn test_token_registered_from_log() {
+        let event = TokenRegistered::from_log(&token_registered_log()).unwrap();
+        assert_eq!(event.token0, U256::from(11));
+        assert_eq!(event.token1, U256::from(22));
+        assert_eq!(event.condition_id_hex(), format!("0x{}", "ab".repeat(32)));
+        assert_eq!(event.block_number, 100);
+        assert_eq!(event.tx_hash, format!("0x{}", "01".repeat(32)));
+        assert_eq!((event.transaction_index, event.log_index), (3, 7));
+    }
+
+    #[test]
+    fn test_pair_registrations() {
+        let event = TokenRegistered::from_log(&token_registered_log()).unwrap();
+        let mirrored = TokenRegistered {
+            token0: event.token1,
+            token1: event.token0,
+            log_index: event.log_index + 1,
+            ..event.clone()
+        };
+
+        // Either event first gives the same canonical pair
+        let paired = TokenRegistered::pair(vec![mirrored.clone(), event.clone()]).unwrap();
+        assert_eq!(
+            [paired.event.token0, paired.event.token1],
+            [U256::from(11), U256::from(22)]
+        );
+        assert_eq!(paired.anomalies, [RegistrationAnomaly::Unverified]);
+
+        // A later transaction sorts last, whatever its hash
+        let mut late = mirrored.clone();
+        late.tx_hash = "0x00".to_string();
+        late.transaction_index = event.transaction_index + 1;
+        let paired = TokenRegistered::pair(vec![late, event.clone(), event.clone()]).unwrap();
+        let kinds: Vec<_> = paired.anomalies.iter().map(|a| a.kind()).collect();
+        assert_eq!(
+            kinds,
+            [
+                "unmirrored_registration",
+                "extra_registrations",
+                "unverified_token_pair"
+            ]
+        );
+
+        let paired = TokenRegistered::pair(vec![event]).unwrap();
+        assert_eq!(paired.anomalies[0], RegistrationAnomaly::Unpaired);
+        assert!(TokenRegistered::pair(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn test_decode_any_event() {
+        let decoded = PolymarketEvent::decode(&token_registered_log()).unwrap();
+        assert!(matches!(
+            decoded,
+            PolymarketEvent::Exchange(CTFExchangeEvents::TokenRegisteredFilter(_))
+        ));
+
+        let mut truncated = token_registered_log();
+        truncated.topics.pop();
+        let err = PolymarketEvent::decode(&truncated).unwrap_err();
+        assert_eq!(err.to_string(), "decode error: Invalid TokenRegistered log");
+
+        let mut unknown = token_registered_log();
+        unknown.topics[0] = H256::zero();
+        assert!(PolymarketEvent::decode(&unknown).is_err());
+    }
+}
@@ -0,0 +1,207 @@
+// Market lifecycle - explicit status derived from on-chain events and Gamma
+//
+// A market moves forward through
+//   registered -> trading -> closed -> disputed -> resolved
+// and may skip states (a market indexed after it closed goes straight from
+// registered to closed). Signals come from two sources that disagree in
+// timing: Gamma flags lag the chain, so a Gamma signal behind the current
+// status is ignored (a cached "trading" for a market that already closed or
+// resolved), while a UMA signal that would move a market backwards (disputing
+// a resolved one) is an impossible transition and is reported as a data
+// anomaly instead of applied.
+
+use crate::polymarket::market::MarketMetadata;
+use crate::polymarket::uma::QuestionEventKind;
+use std::fmt;
+use std::str::FromStr;
+
+/// Data anomaly kind recorded for a rejected transition
+pub const INVALID_TRANSITION: &str = "invalid_status_transition";
+
+/// Where a market is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MarketStatus {
+    /// TokenRegistered seen; Gamma doesn't list it as trading (yet)
+    Registered,
+    /// Gamma lists the market as active and open
+    Trading,
+    /// Gamma closed the market; waiting for resolution
+    Closed,
+    /// A proposed outcome was disputed and the question reset
+    Disputed,
+    /// The adapter reported payouts
+    Resolved,
+}
+
+impl MarketStatus {
+    pub const ALL: [MarketStatus; 5] = [
+        MarketStatus::Registered,
+        MarketStatus::Trading,
+        MarketStatus::Closed,
+        MarketStatus::Disputed,
+        MarketStatus::Resolved,
+    ];
+
+    /// Name stored in the `status` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MarketStatus::Registered => "registered",
+            MarketStatus::Trading => "trading",
+            MarketStatus::Closed => "closed",
+            MarketStatus::Disputed => "disputed",
+            MarketStatus::Resolved => "resolved",
+        }
+    }
+
+    /// Status after `signal`
+    ///
+    /// `Ok(None)` leaves the status unchanged (already there, or a stale
+    /// Gamma flag); `Err` is a UMA signal no market can follow.
+    pub fn apply(self, signal: StatusSignal) -> Result<Option<MarketStatus>, InvalidTransition> {
+        let target = signal.target();
+        if target == self {
+            return Ok(None);
+        }
+        if target > self {
+            return Ok(Some(target));
+        }
+
+        match signal {
+            // Gamma lags the chain: a market can be disputed or resolved while
+            // Gamma still lists it as open or closed
+            StatusSignal::Trading | StatusSignal::Closed => Ok(None),
+            StatusSignal::Disputed | StatusSignal::Resolved => {
+                Err(InvalidTransition { from: self, signal })
+            }
+        }
+    }
+}
+
+impl fmt::Display for MarketStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MarketStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MarketStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| format!("unknown market status '{}'", s))
+    }
+}
+
+/// Something observed about a market that may change its status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusSignal {
+    /// Gamma: active and not closed
+    Trading,
+    /// Gamma: closed
+    Closed,
+    /// UMA CTF Adapter: QuestionReset
+    Disputed,
+    /// UMA CTF Adapter: QuestionResolved
+    Resolved,
+}
+
+impl StatusSignal {
+    /// Signal carried by Gamma's active/closed flags, if any
+    ///
+    /// Inactive, unclosed markets (not yet launched) carry none.
+    pub fn from_metadata(metadata: &MarketMetadata) -> Option<Self> {
+        match (metadata.active, metadata.closed) {
+            (_, Some(true)) => Some(StatusSignal::Closed),
+            (Some(true), _) => Some(StatusSignal::Trading),
+            _ => None,
+        }
+    }
+
+    /// Signal carried by a UMA question event, if any
+    pub fn from_question_event(kind: &QuestionEventKind) -> Option<Self> {
+        match kind {
+            QuestionEventKind::Reset => Some(StatusSignal::Disputed),
+            QuestionEventKind::Resolved { .. } => Some(StatusSignal::Resolved),
+            _ => None,
+        }
+    }
+
+    /// Signal carried by a stored question status ('reset' or 'resolved')
+    pub fn from_question_status(status: &str) -> Option<Self> {
+        match status {
+            "reset" => Some(StatusSignal::Disputed),
+            "resolved" => Some(StatusSignal::Resolved),
+            _ => None,
+        }
+    }
+
+    /// Status the signal moves a market to
+    pub fn target(self) -> MarketStatus {
+        match self {
+            StatusSignal::Trading => MarketStatus::Trading,
+            StatusSignal::Closed => MarketStatus::Closed,
+            StatusSignal::Disputed => MarketStatus::Disputed,
+            StatusSignal::Resolved => MarketStatus::Resolved,
+        }
+    }
+
+    /// Source recorded with the transition: 'gamma' or 'uma'
+    pub fn source(self) -> &'static str {
+        match self {
+            StatusSignal::Trading | StatusSignal::Closed => "gamma",
+            StatusSignal::Disputed | StatusSignal::Resolved => "uma",
+        }
+    }
+}
+
+/// A signal that would move a market to an impossible status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: MarketStatus,
+    pub signal: StatusSignal,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} market cannot become {} ({} signal)",
+            self.from,
+            self.signal.target(),
+            self.signal.source()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_transitions() {
+        use MarketStatus::*;
+        assert_eq!(Registered.apply(StatusSignal::Trading), Ok(Some(Trading)));
+        assert_eq!(Registered.apply(StatusSignal::Closed), Ok(Some(Closed)));
+        assert_eq!(Trading.apply(StatusSignal::Disputed), Ok(Some(Disputed)));
+        assert_eq!(Disputed.apply(StatusSignal::Resolved), Ok(Some(Resolved)));
+        assert_eq!(Trading.apply(StatusSignal::Trading), Ok(None));
+        assert_eq!("disputed".parse(), Ok(Disputed));
+    }
+
+    #[test]
+    fn test_stale_and_impossible_transitions() {
+        use MarketStatus::*;
+        assert_eq!(Resolved.apply(StatusSignal::Closed), Ok(None));
+        assert_eq!(Disputed.apply(StatusSignal::Trading), Ok(None));
+        assert_eq!(Closed.apply(StatusSignal::Trading), Ok(None));
+        assert_eq!(Resolved.apply(StatusSignal::Trading), Ok(None));
+
+        let redisputed = Resolved.apply(StatusSignal::Disputed).unwrap_err();
+        assert_eq!(
+            redisputed.to_string(),
+            "resolved market cannot become disputed (uma signal)"
+        );
+    }
+}
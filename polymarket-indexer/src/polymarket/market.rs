@@ -3,6 +3,7 @@
 // Structures for deserializing market information returned from
 // https://gamma-api.polymarket.com/markets
 
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{de, Deserialize, Deserializer};
 
 /// Market metadata from Gamma API
@@ -34,6 +35,28 @@ pub struct MarketMetadata {
     /// Market end date (ISO 8601 string)
     #[serde(default)]
     pub end_date: Option<String>,
+
+    /// Whether the market is live on Polymarket
+    #[serde(default)]
+    pub active: Option<bool>,
+
+    /// Whether trading has closed
+    #[serde(default)]
+    pub closed: Option<bool>,
+}
+
+impl MarketMetadata {
+    /// Start date as a timestamp; `None` when missing or not ISO 8601
+    ///
+    /// Accepts RFC 3339 timestamps and bare dates (taken as midnight UTC).
+    pub fn start_at(&self) -> Option<DateTime<Utc>> {
+        let start = self.start_date.as_deref()?.trim();
+        if let Ok(time) = DateTime::parse_from_rfc3339(start) {
+            return Some(time.with_timezone(&Utc));
+        }
+        let date = NaiveDate::parse_from_str(start, "%Y-%m-%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+    }
 }
 
 /// Custom deserializer for JSON-encoded string arrays
@@ -79,3 +102,40 @@ impl MarketMetadata {
         println!("=================================");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn metadata(start_date: Option<&str>) -> MarketMetadata {
+        MarketMetadata {
+            id: None,
+            question: "Will it rain?".to_string(),
+            slug: "will-it-rain".to_string(),
+            condition_id: "0x01".to_string(),
+            outcomes: vec!["Yes".to_string(), "No".to_string()],
+            start_date: start_date.map(str::to_string),
+            end_date: None,
+            active: None,
+            closed: None,
+        }
+    }
+
+    #[test]
+    fn test_start_at() {
+        let midnight = Utc.with_ymd_and_hms(2024, 11, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            metadata(Some("2024-11-01T00:00:00Z")).start_at(),
+            Some(midnight)
+        );
+        assert_eq!(
+            metadata(Some("2024-11-01T02:00:00.000+02:00")).start_at(),
+            Some(midnight)
+        );
+        assert_eq!(metadata(Some("2024-11-01")).start_at(), Some(midnight));
+        assert_eq!(metadata(Some("")).start_at(), None);
+        assert_eq!(metadata(Some("next week")).start_at(), None);
+        assert_eq!(metadata(None).start_at(), None);
+    }
+}
@@ -2,9 +2,22 @@
 //
 // Contains all Polymarket-specific functionality including:
 // - Contract addresses and constants
-// - Event definitions
+// - Contract bindings and event definitions
+// - Conditional Tokens ID derivation (condition, collection, position)
 // - Market metadata structures
+// - Market lifecycle status and its transitions
+// - UMA CTF Adapter questions and ancillary data
+// - Exchange fills and the trades they imply
+// - Split, merge and redeem events moving collateral in and out of markets
+// - Outcome token (ERC-1155) transfers
 
 pub mod constants;
+pub mod contracts;
+pub mod ctf;
 pub mod events;
+pub mod lifecycle;
 pub mod market;
+pub mod positions;
+pub mod trades;
+pub mod transfers;
+pub mod uma;
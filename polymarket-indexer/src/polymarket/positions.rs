@@ -0,0 +1,258 @@
+// Position events - collateral moving in and out of a market's outcome tokens
+//
+// Splitting collateral mints a full set of outcome tokens, merging a full set
+// burns it for the collateral back, and redeeming after resolution burns
+// tokens for their payout. The collateral held against a condition, its open
+// interest, is the running total of splits less merges and redemptions.
+//
+// Only the top level counts: splits and merges under a parent collection
+// trade one position for deeper ones, and redemptions there pay into the
+// parent position, so no collateral moves.
+//
+// NegRisk markets go through the NegRiskAdapter, which emits its own
+// PositionSplit, PositionsMerge and PayoutRedemption and performs the
+// Conditional Tokens call itself. Those inner events name the adapter as
+// stakeholder and are passthroughs of the adapter's; conversions between a
+// NegRisk market's questions are not tracked.
+
+use crate::error::Result;
+use crate::polymarket::contracts::conditional_tokens::ConditionalTokensEvents;
+use crate::polymarket::contracts::neg_risk_adapter::NegRiskAdapterEvents;
+use crate::polymarket::events::{self, PolymarketEvent};
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, Log, U256};
+use std::fmt;
+use std::str::FromStr;
+
+/// Which way collateral moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionEventKind {
+    Split,
+    Merge,
+    Redeem,
+}
+
+impl PositionEventKind {
+    pub const ALL: [PositionEventKind; 3] = [
+        PositionEventKind::Split,
+        PositionEventKind::Merge,
+        PositionEventKind::Redeem,
+    ];
+
+    /// Name stored in the `kind` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PositionEventKind::Split => "split",
+            PositionEventKind::Merge => "merge",
+            PositionEventKind::Redeem => "redeem",
+        }
+    }
+}
+
+impl fmt::Display for PositionEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PositionEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        PositionEventKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == s)
+            .ok_or_else(|| format!("unknown position event kind '{}'", s))
+    }
+}
+
+/// A top-level split, merge or redemption plus the block it was emitted in
+#[derive(Debug, Clone)]
+pub struct PositionEvent {
+    /// Contract that emitted the event
+    pub contract: Address,
+    pub kind: PositionEventKind,
+    pub condition_id: [u8; 32],
+    /// Address that split, merged or redeemed
+    pub stakeholder: Address,
+    /// Collateral moved: the amount split or merged, or the payout redeemed
+    pub amount: U256,
+    pub block_number: u64,
+    /// Block timestamp (see `blocks::BlockRecorder`)
+    pub block_time: DateTime<Utc>,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+impl PositionEvent {
+    /// Attach the contract, block and transaction of `log` to a decoded
+    /// event
+    ///
+    /// `None` for other events and for splits, merges and redemptions under
+    /// a parent collection.
+    pub fn from_event(
+        event: PolymarketEvent,
+        log: &Log,
+        block_time: DateTime<Utc>,
+    ) -> Result<Option<Self>> {
+        use ConditionalTokensEvents as Ctf;
+        use NegRiskAdapterEvents as Adapter;
+
+        let (kind, condition_id, stakeholder, amount) = match event {
+            PolymarketEvent::ConditionalTokens(Ctf::PositionSplitFilter(e))
+                if e.parent_collection_id == [0; 32] =>
+            {
+                (
+                    PositionEventKind::Split,
+                    e.condition_id,
+                    e.stakeholder,
+                    e.amount,
+                )
+            }
+            PolymarketEvent::ConditionalTokens(Ctf::PositionsMergeFilter(e))
+                if e.parent_collection_id == [0; 32] =>
+            {
+                (
+                    PositionEventKind::Merge,
+                    e.condition_id,
+                    e.stakeholder,
+                    e.amount,
+                )
+            }
+            PolymarketEvent::ConditionalTokens(Ctf::PayoutRedemptionFilter(e))
+                if e.parent_collection_id == [0; 32] =>
+            {
+                (
+                    PositionEventKind::Redeem,
+                    e.condition_id,
+                    e.redeemer,
+                    e.payout,
+                )
+            }
+            PolymarketEvent::NegRiskAdapter(Adapter::PositionSplitFilter(e)) => (
+                PositionEventKind::Split,
+                e.condition_id,
+                e.stakeholder,
+                e.amount,
+            ),
+            PolymarketEvent::NegRiskAdapter(Adapter::PositionsMergeFilter(e)) => (
+                PositionEventKind::Merge,
+                e.condition_id,
+                e.stakeholder,
+                e.amount,
+            ),
+            PolymarketEvent::NegRiskAdapter(Adapter::PayoutRedemptionFilter(e)) => (
+                PositionEventKind::Redeem,
+                e.condition_id,
+                e.redeemer,
+                e.payout,
+            ),
+            _ => return Ok(None),
+        };
+
+        let (block_number, tx_hash, log_index) = events::log_position(log)?;
+
+        Ok(Some(Self {
+            contract: log.address,
+            kind,
+            condition_id,
+            stakeholder,
+            amount,
+            block_number,
+            block_time,
+            tx_hash,
+            log_index,
+        }))
+    }
+
+    /// Condition ID as a hex string (with 0x prefix), as stored on markets
+    pub fn condition_id_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.condition_id))
+    }
+
+    /// Whether this is the Conditional Tokens side of a NegRiskAdapter call,
+    /// already counted through the adapter's own event
+    pub fn is_adapter_passthrough(&self, neg_risk_adapter: Address) -> bool {
+        self.stakeholder == neg_risk_adapter && self.contract != neg_risk_adapter
+    }
+
+    /// Collateral moved, if it fits in `i64` raw units
+    pub fn collateral(&self) -> Option<i64> {
+        i64::try_from(self.amount).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::contracts::conditional_tokens::PositionSplitFilter;
+    use crate::polymarket::contracts::neg_risk_adapter::PayoutRedemptionFilter;
+    use ethers::types::{H256, U64};
+
+    fn log(address: Address) -> Log {
+        Log {
+            address,
+            block_number: Some(U64::from(7)),
+            transaction_hash: Some(H256::repeat_byte(0xab)),
+            log_index: Some(U256::from(2)),
+            ..Default::default()
+        }
+    }
+
+    fn split(parent: [u8; 32], stakeholder: Address) -> PolymarketEvent {
+        PolymarketEvent::ConditionalTokens(ConditionalTokensEvents::PositionSplitFilter(
+            PositionSplitFilter {
+                stakeholder,
+                collateral_token: Address::repeat_byte(0xcc),
+                parent_collection_id: parent,
+                condition_id: [3; 32],
+                partition: vec![U256::from(1), U256::from(2)],
+                amount: U256::from(5_000_000),
+            },
+        ))
+    }
+
+    #[test]
+    fn test_top_level_events_only() {
+        let ctf = Address::repeat_byte(0xc7);
+        let user = Address::repeat_byte(1);
+
+        let event = PositionEvent::from_event(split([0; 32], user), &log(ctf), DateTime::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.kind, PositionEventKind::Split);
+        assert_eq!(event.collateral(), Some(5_000_000));
+        assert_eq!(event.condition_id_hex(), format!("0x{}", "03".repeat(32)));
+
+        let nested =
+            PositionEvent::from_event(split([9; 32], user), &log(ctf), DateTime::default());
+        assert!(nested.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_adapter_passthrough() {
+        let ctf = Address::repeat_byte(0xc7);
+        let adapter = Address::repeat_byte(0xad);
+
+        let inner =
+            PositionEvent::from_event(split([0; 32], adapter), &log(ctf), DateTime::default())
+                .unwrap()
+                .unwrap();
+        assert!(inner.is_adapter_passthrough(adapter));
+
+        let redemption = PolymarketEvent::NegRiskAdapter(
+            NegRiskAdapterEvents::PayoutRedemptionFilter(PayoutRedemptionFilter {
+                redeemer: Address::repeat_byte(1),
+                condition_id: [3; 32],
+                amounts: vec![U256::from(4), U256::zero()],
+                payout: U256::from(4),
+            }),
+        );
+        let outer = PositionEvent::from_event(redemption, &log(adapter), DateTime::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(outer.kind, PositionEventKind::Redeem);
+        assert!(!outer.is_adapter_passthrough(adapter));
+        assert_eq!("redeem".parse(), Ok(PositionEventKind::Redeem));
+    }
+}
@@ -0,0 +1,200 @@
+// Exchange trades - OrderFilled events and the prices they imply
+//
+// Both exchanges emit OrderFilled for every maker order a match fills, and
+// once more for the taker order with the exchange itself as `taker`. The
+// taker summary repeats volume already carried by the maker fills, so only
+// maker fills become trades.
+//
+// Asset ID 0 is the collateral (USDC). An order giving collateral buys the
+// outcome token it receives; an order giving an outcome token sells it.
+// Collateral and outcome tokens both use 6 decimals, so the price is the
+// ratio of the raw amounts.
+
+use crate::error::Result;
+use crate::polymarket::contracts::ctf_exchange::OrderFilledFilter;
+use crate::polymarket::events;
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, Log, U256};
+use std::fmt;
+
+/// One OrderFilled event plus the block it was emitted in
+#[derive(Debug, Clone)]
+pub struct Fill {
+    /// Exchange that emitted the event
+    pub exchange: Address,
+    pub order_hash: [u8; 32],
+    pub maker: Address,
+    pub taker: Address,
+    pub maker_asset_id: U256,
+    pub taker_asset_id: U256,
+    pub maker_amount_filled: U256,
+    pub taker_amount_filled: U256,
+    pub fee: U256,
+    pub block_number: u64,
+    /// Block timestamp (see `blocks::BlockRecorder`)
+    pub block_time: DateTime<Utc>,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+impl Fill {
+    /// Attach the exchange, block and transaction of `log` to a decoded
+    /// OrderFilled event
+    pub fn from_event(
+        event: OrderFilledFilter,
+        log: &Log,
+        block_time: DateTime<Utc>,
+    ) -> Result<Self> {
+        let (block_number, tx_hash, log_index) = events::log_position(log)?;
+
+        Ok(Self {
+            exchange: log.address,
+            order_hash: event.order_hash,
+            maker: event.maker,
+            taker: event.taker,
+            maker_asset_id: event.maker_asset_id,
+            taker_asset_id: event.taker_asset_id,
+            maker_amount_filled: event.maker_amount_filled,
+            taker_amount_filled: event.taker_amount_filled,
+            fee: event.fee,
+            block_number,
+            block_time,
+            tx_hash,
+            log_index,
+        })
+    }
+
+    /// Order hash as a hex string (with 0x prefix)
+    pub fn order_hash_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.order_hash))
+    }
+
+    /// Whether this is the taker order's fill, filled against the exchange
+    pub fn is_taker_summary(&self) -> bool {
+        self.taker == self.exchange
+    }
+
+    /// The trade this fill represents, if any
+    ///
+    /// `None` for taker summaries, fills that swap two outcome tokens or
+    /// collateral for collateral, empty fills and amounts beyond `i64`.
+    pub fn trade(&self) -> Option<Trade> {
+        if self.is_taker_summary() {
+            return None;
+        }
+
+        let (side, token_id, shares, collateral) =
+            match (self.maker_asset_id.is_zero(), self.taker_asset_id.is_zero()) {
+                (true, false) => (
+                    Side::Buy,
+                    self.taker_asset_id,
+                    self.taker_amount_filled,
+                    self.maker_amount_filled,
+                ),
+                (false, true) => (
+                    Side::Sell,
+                    self.maker_asset_id,
+                    self.maker_amount_filled,
+                    self.taker_amount_filled,
+                ),
+                _ => return None,
+            };
+
+        let size = i64::try_from(shares).ok().filter(|size| *size > 0)?;
+        let notional = i64::try_from(collateral).ok()?;
+        Some(Trade {
+            token_id,
+            side,
+            price: notional as f64 / size as f64,
+            size,
+            notional,
+        })
+    }
+}
+
+/// Which way the maker traded the outcome token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    /// Name stored in the `side` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An outcome token traded for collateral
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub token_id: U256,
+    /// Maker's side
+    pub side: Side,
+    /// Collateral per outcome token (0 to 1 for a valid market)
+    pub price: f64,
+    /// Outcome tokens traded, in raw 6-decimal units
+    pub size: i64,
+    /// Collateral paid, in raw 6-decimal units
+    pub notional: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(maker_asset: u64, taker_asset: u64, maker_amount: u64, taker_amount: u64) -> Fill {
+        Fill {
+            exchange: Address::repeat_byte(0xee),
+            order_hash: [1; 32],
+            maker: Address::repeat_byte(1),
+            taker: Address::repeat_byte(2),
+            maker_asset_id: U256::from(maker_asset),
+            taker_asset_id: U256::from(taker_asset),
+            maker_amount_filled: U256::from(maker_amount),
+            taker_amount_filled: U256::from(taker_amount),
+            fee: U256::zero(),
+            block_number: 100,
+            block_time: DateTime::default(),
+            tx_hash: "0xab".to_string(),
+            log_index: 0,
+        }
+    }
+
+    #[test]
+    fn test_trade_from_fill() {
+        // Maker pays 65 USDC for 100 shares of token 7
+        let buy = fill(0, 7, 65_000_000, 100_000_000).trade().unwrap();
+        assert_eq!(buy.side, Side::Buy);
+        assert_eq!(buy.token_id, U256::from(7));
+        assert_eq!(buy.size, 100_000_000);
+        assert!((buy.price - 0.65).abs() < 1e-12);
+
+        // Maker sells 40 shares of token 7 for 10 USDC
+        let sell = fill(7, 0, 40_000_000, 10_000_000).trade().unwrap();
+        assert_eq!(sell.side, Side::Sell);
+        assert_eq!(sell.notional, 10_000_000);
+        assert!((sell.price - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fills_without_trades() {
+        let mut summary = fill(0, 7, 65, 100);
+        summary.taker = summary.exchange;
+        assert!(summary.is_taker_summary());
+        assert_eq!(summary.trade(), None);
+
+        assert_eq!(fill(7, 8, 10, 10).trade(), None);
+        assert_eq!(fill(0, 7, 10, 0).trade(), None);
+    }
+}
@@ -0,0 +1,124 @@
+// Outcome token transfers - ERC-1155 TransferSingle and TransferBatch
+//
+// Every movement of an outcome token emits one of these: mints (from the
+// zero address) on splits, burns (to the zero address) on merges and
+// redemptions, the exchanges settling trades and plain transfers between
+// wallets. A batch is flattened into one transfer per token.
+
+use crate::error::{IndexerError, Result};
+use crate::polymarket::contracts::conditional_tokens::ConditionalTokensEvents;
+use crate::polymarket::events;
+use chrono::{DateTime, Utc};
+use ethers::types::{Address, Log, U256};
+
+/// One token moved by a TransferSingle or TransferBatch event
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenTransfer {
+    pub operator: Address,
+    pub from: Address,
+    pub to: Address,
+    pub token_id: U256,
+    pub amount: U256,
+    pub block_number: u64,
+    /// Block timestamp (see `blocks::BlockRecorder`)
+    pub block_time: DateTime<Utc>,
+    pub tx_hash: String,
+    pub log_index: u64,
+    /// Position within the batch (0 for TransferSingle)
+    pub batch_index: u32,
+}
+
+impl TokenTransfer {
+    /// Flatten a decoded transfer event, attaching the block and transaction
+    /// of `log`
+    ///
+    /// Empty for other Conditional Tokens events; fails for batches whose
+    /// ID and value lists differ in length.
+    pub fn from_event(
+        event: ConditionalTokensEvents,
+        log: &Log,
+        block_time: DateTime<Utc>,
+    ) -> Result<Vec<Self>> {
+        let (operator, from, to, moved) = match event {
+            ConditionalTokensEvents::TransferSingleFilter(e) => {
+                (e.operator, e.from, e.to, vec![(e.id, e.value)])
+            }
+            ConditionalTokensEvents::TransferBatchFilter(e) => {
+                if e.ids.len() != e.values.len() {
+                    return Err(IndexerError::Decode(format!(
+                        "TransferBatch with {} ids and {} values",
+                        e.ids.len(),
+                        e.values.len()
+                    )));
+                }
+                (
+                    e.operator,
+                    e.from,
+                    e.to,
+                    e.ids.into_iter().zip(e.values).collect(),
+                )
+            }
+            _ => return Ok(Vec::new()),
+        };
+
+        let (block_number, tx_hash, log_index) = events::log_position(log)?;
+
+        Ok(moved
+            .into_iter()
+            .enumerate()
+            .map(|(batch_index, (token_id, amount))| Self {
+                operator,
+                from,
+                to,
+                token_id,
+                amount,
+                block_number,
+                block_time,
+                tx_hash: tx_hash.clone(),
+                log_index,
+                batch_index: batch_index as u32,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::contracts::conditional_tokens::TransferBatchFilter;
+    use ethers::types::{H256, U64};
+
+    #[test]
+    fn test_batch_is_flattened() {
+        let log = Log {
+            block_number: Some(U64::from(7)),
+            transaction_hash: Some(H256::repeat_byte(0xab)),
+            log_index: Some(U256::from(2)),
+            ..Default::default()
+        };
+        let batch = |values: Vec<U256>| {
+            ConditionalTokensEvents::TransferBatchFilter(TransferBatchFilter {
+                operator: Address::repeat_byte(1),
+                from: Address::repeat_byte(1),
+                to: Address::repeat_byte(2),
+                ids: vec![U256::from(7), U256::from(8)],
+                values,
+            })
+        };
+
+        let transfers = TokenTransfer::from_event(
+            batch(vec![U256::from(10), U256::from(20)]),
+            &log,
+            DateTime::default(),
+        )
+        .unwrap();
+        assert_eq!(transfers.len(), 2);
+        assert_eq!(transfers[1].token_id, U256::from(8));
+        assert_eq!(transfers[1].amount, U256::from(20));
+        assert_eq!(transfers[1].batch_index, 1);
+
+        assert!(
+            TokenTransfer::from_event(batch(vec![U256::one()]), &log, DateTime::default()).is_err()
+        );
+    }
+}
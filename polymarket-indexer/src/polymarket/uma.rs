@@ -0,0 +1,306 @@
+// UMA CTF Adapter questions - the on-chain side of a market's question
+//
+// The adapter initializes a question with UMA ancillary data holding the
+// question text, asks the optimistic oracle for a price and reports the
+// payouts to the Conditional Tokens contract. A disputed proposal resets the
+// question with a new request, and an admin can pause it.
+//
+// Several adapter deployments are live; each is the oracle of the conditions
+// it prepares, so the condition ID follows from the emitting adapter's
+// address and question ID (see `ctf::condition_id`).
+//
+// NegRisk markets are not linked. Their UMA questions go through the NegRisk
+// UMA adapter and NegRiskOperator, while their conditions are prepared by the
+// NegRiskAdapter under its own question IDs (market ID plus outcome index);
+// mapping one to the other needs the operator's QuestionPrepared events,
+// which are not indexed. Such a question's derived condition matches no market.
+
+use crate::error::Result;
+use crate::polymarket::contracts::uma_ctf_adapter::UmaCtfAdapterEvents;
+use crate::polymarket::{ctf, events};
+use ethers::types::{Address, Log, I256, U256};
+
+/// Outcome slots of every question the adapter prepares (binary markets)
+const OUTCOME_SLOT_COUNT: u32 = 2;
+
+/// Marker the adapter appends to the creator's ancillary data
+const INITIALIZER_MARKER: &str = ",initializer:";
+
+/// Question text decoded from UMA ancillary data
+///
+/// Polymarket ancillary data reads
+/// `q: title: <title>, description: <text> res_data: <mapping>`, followed by
+/// `,initializer:<address>` added by the adapter. Sections that are missing
+/// are `None`; `raw` always keeps the full text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AncillaryData {
+    pub raw: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Price to outcome mapping, e.g. `p1: 0, p2: 1, p3: 0.5. Where p1 ...`
+    pub resolution_data: Option<String>,
+    /// Address that initialized the question (hex, without 0x)
+    pub initializer: Option<String>,
+}
+
+impl AncillaryData {
+    /// Decode ancillary data bytes; invalid UTF-8 is replaced, never rejected
+    pub fn parse(bytes: &[u8]) -> Self {
+        let raw = String::from_utf8_lossy(bytes).into_owned();
+
+        let (body, initializer) = match raw.rfind(INITIALIZER_MARKER) {
+            Some(at) => (&raw[..at], non_empty(&raw[at + INITIALIZER_MARKER.len()..])),
+            None => (raw.as_str(), None),
+        };
+        let body = body.trim_start();
+        let body = body.strip_prefix("q:").unwrap_or(body);
+
+        let title_at = body.find("title:");
+        let description_at = body.find("description:");
+        let res_data_at = body.find("res_data:");
+        let section = |start: Option<usize>, marker: &str, ends: &[Option<usize>]| {
+            let start = start? + marker.len();
+            let end = ends
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|&end| end >= start)
+                .min()
+                .unwrap_or(body.len());
+            non_empty(&body[start..end])
+        };
+
+        Self {
+            title: section(title_at, "title:", &[description_at, res_data_at]),
+            description: section(description_at, "description:", &[res_data_at]),
+            resolution_data: section(res_data_at, "res_data:", &[]),
+            initializer,
+            raw,
+        }
+    }
+}
+
+/// Trim whitespace and the separating comma; `None` when nothing is left
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim().trim_end_matches(',').trim_end();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// What happened to a question
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuestionEventKind {
+    Initialized {
+        request_timestamp: u64,
+        creator: Address,
+        ancillary_data: AncillaryData,
+        reward_token: Address,
+        reward: U256,
+        proposal_bond: U256,
+    },
+    Resolved {
+        settled_price: I256,
+        payouts: Vec<U256>,
+    },
+    /// A proposal was disputed and the price requested again
+    Reset,
+    Paused,
+    Unpaused,
+}
+
+impl QuestionEventKind {
+    /// Name stored in the event history
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuestionEventKind::Initialized { .. } => "initialized",
+            QuestionEventKind::Resolved { .. } => "resolved",
+            QuestionEventKind::Reset => "reset",
+            QuestionEventKind::Paused => "paused",
+            QuestionEventKind::Unpaused => "unpaused",
+        }
+    }
+
+    /// Question status after this event (`None` leaves it unchanged)
+    pub fn status(&self) -> Option<&'static str> {
+        match self {
+            QuestionEventKind::Resolved { .. } => Some("resolved"),
+            QuestionEventKind::Reset => Some("reset"),
+            _ => None,
+        }
+    }
+
+    /// Paused flag after this event (`None` leaves it unchanged)
+    pub fn paused(&self) -> Option<bool> {
+        match self {
+            QuestionEventKind::Paused => Some(true),
+            QuestionEventKind::Unpaused => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// A question lifecycle event plus the log it was emitted in
+#[derive(Debug, Clone)]
+pub struct QuestionEvent {
+    pub question_id: [u8; 32],
+    /// Adapter that emitted the event (the condition's oracle, except for
+    /// NegRisk questions)
+    pub adapter: Address,
+    pub kind: QuestionEventKind,
+    pub block_number: u64,
+    pub tx_hash: String,
+    pub log_index: u64,
+}
+
+impl QuestionEvent {
+    /// Attach the emitting adapter, block and transaction of `log` to a
+    /// decoded adapter event
+    pub fn from_event(event: UmaCtfAdapterEvents, log: &Log) -> Result<Self> {
+        let (question_id, kind) = match event {
+            UmaCtfAdapterEvents::QuestionInitializedFilter(e) => (
+                e.question_id,
+                QuestionEventKind::Initialized {
+                    request_timestamp: e.request_timestamp.low_u64(),
+                    creator: e.creator,
+                    ancillary_data: AncillaryData::parse(&e.ancillary_data),
+                    reward_token: e.reward_token,
+                    reward: e.reward,
+                    proposal_bond: e.proposal_bond,
+                },
+            ),
+            UmaCtfAdapterEvents::QuestionResolvedFilter(e) => (
+                e.question_id,
+                QuestionEventKind::Resolved {
+                    settled_price: e.settled_price,
+                    payouts: e.payouts,
+                },
+            ),
+            UmaCtfAdapterEvents::QuestionResetFilter(e) => {
+                (e.question_id, QuestionEventKind::Reset)
+            }
+            UmaCtfAdapterEvents::QuestionPausedFilter(e) => {
+                (e.question_id, QuestionEventKind::Paused)
+            }
+            UmaCtfAdapterEvents::QuestionUnpausedFilter(e) => {
+                (e.question_id, QuestionEventKind::Unpaused)
+            }
+        };
+
+        let (block_number, tx_hash, log_index) = events::log_position(log)?;
+
+        Ok(Self {
+            question_id,
+            adapter: log.address,
+            kind,
+            block_number,
+            tx_hash,
+            log_index,
+        })
+    }
+
+    /// Question ID as a hex string (with 0x prefix)
+    pub fn question_id_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.question_id))
+    }
+
+    /// Condition the adapter prepared for this question (not the market's
+    /// condition for NegRisk questions, see the module docs)
+    pub fn condition_id(&self) -> [u8; 32] {
+        ctf::condition_id(self.adapter, self.question_id, OUTCOME_SLOT_COUNT)
+    }
+
+    /// Condition ID as a hex string (with 0x prefix), as stored on markets
+    pub fn condition_id_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.condition_id()))
+    }
+
+    /// Creator, request timestamp and ancillary data of an initialization
+    pub fn initialization(&self) -> Option<(Address, u64, &AncillaryData)> {
+        match &self.kind {
+            QuestionEventKind::Initialized {
+                creator,
+                request_timestamp,
+                ancillary_data,
+                ..
+            } => Some((*creator, *request_timestamp, ancillary_data)),
+            _ => None,
+        }
+    }
+
+    /// Settled price and payouts (decimal strings) of a resolution
+    pub fn resolution(&self) -> Option<(String, Vec<String>)> {
+        match &self.kind {
+            QuestionEventKind::Resolved {
+                settled_price,
+                payouts,
+            } => Some((
+                settled_price.to_string(),
+                payouts.iter().map(U256::to_string).collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polymarket::constants::{NEG_RISK_ADAPTER_ADDRESS, UMA_CTF_ADAPTER_ADDRESSES};
+
+    #[test]
+    fn test_parse_polymarket_ancillary_data() {
+        let raw = "q: title: Will it rain in NYC on May 1?, description: This market will \
+                   resolve to \"Yes\" if it rains. res_data: p1: 0, p2: 1, p3: 0.5. Where p1 \
+                   corresponds to No, p2 to Yes, p3 to unknown/50-50.,\
+                   initializer:91430cad2d3975766499717fa0d66a78d814e5c5";
+        let data = AncillaryData::parse(raw.as_bytes());
+
+        assert_eq!(data.title.as_deref(), Some("Will it rain in NYC on May 1?"));
+        assert_eq!(
+            data.description.as_deref(),
+            Some("This market will resolve to \"Yes\" if it rains.")
+        );
+        assert_eq!(
+            data.resolution_data.as_deref(),
+            Some("p1: 0, p2: 1, p3: 0.5. Where p1 corresponds to No, p2 to Yes, p3 to unknown/50-50.")
+        );
+        assert_eq!(
+            data.initializer.as_deref(),
+            Some("91430cad2d3975766499717fa0d66a78d814e5c5")
+        );
+        assert_eq!(data.raw, raw);
+    }
+
+    #[test]
+    fn test_parse_free_form_ancillary_data() {
+        let data = AncillaryData::parse(b"Did it happen? \xff");
+        assert_eq!(data.title, None);
+        assert_eq!(data.description, None);
+        assert_eq!(data.raw, "Did it happen? \u{fffd}");
+    }
+
+    #[test]
+    fn test_condition_follows_oracle_and_question() {
+        // "Presidential Election Winner 2024" - Donald Trump: the condition
+        // the NegRiskAdapter prepared under its own question ID
+        let question_id = "e3b1bc389210504ebcb9cffe4b0ed06ccac50561e0f24abb6379984cec030f00";
+        let event = QuestionEvent {
+            question_id: hex::decode(question_id).unwrap().try_into().unwrap(),
+            adapter: NEG_RISK_ADAPTER_ADDRESS.parse().unwrap(),
+            kind: QuestionEventKind::Reset,
+            block_number: 1,
+            tx_hash: String::new(),
+            log_index: 0,
+        };
+        assert_eq!(
+            event.condition_id_hex(),
+            "0xdd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917"
+        );
+
+        // The same question ID from a UMA adapter is a different condition
+        let uma = QuestionEvent {
+            adapter: UMA_CTF_ADAPTER_ADDRESSES[0].parse().unwrap(),
+            ..event.clone()
+        };
+        assert_ne!(uma.condition_id(), event.condition_id());
+    }
+}
@@ -0,0 +1,321 @@
+// Event Registry - route logs from one eth_getLogs sweep to their handlers
+//
+// Each (contract address, topic0) pair is registered with the handler for
+// that event. The registry builds a single filter covering every registered
+// pair, decodes the returned logs with the ABI bindings and hands each
+// handler its events in log order. Logs matching no registration are counted
+// as unknown rather than failing the range.
+
+use crate::config::Config;
+use crate::ingest::{
+    FillIngester, IngestStats, MarketIngester, PositionIngester, QuestionIngester, TransferIngester,
+};
+use crate::metrics;
+use crate::polymarket::contracts::conditional_tokens;
+use crate::polymarket::contracts::ctf_exchange::{OrderFilledFilter, TokenRegisteredFilter};
+use crate::polymarket::contracts::neg_risk_adapter;
+use crate::polymarket::contracts::uma_ctf_adapter::{
+    QuestionInitializedFilter, QuestionPausedFilter, QuestionResetFilter, QuestionResolvedFilter,
+    QuestionUnpausedFilter,
+};
+use crate::polymarket::events::PolymarketEvent;
+use crate::store::MarketStore;
+use async_trait::async_trait;
+use ethers::contract::EthEvent;
+use ethers::types::{Address, Filter, Log, H256};
+use eyre::Result;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// A decoded event together with the log it came from
+#[derive(Debug, Clone)]
+pub struct DecodedLog {
+    pub log: Log,
+    pub event: PolymarketEvent,
+}
+
+/// Processes the events routed to it by an `EventRegistry`
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    /// Handle the events of one block range, in log order
+    async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats>;
+}
+
+struct Route {
+    name: String,
+    handler: usize,
+}
+
+/// Maps (address, topic0) to an event name and handler
+#[derive(Default)]
+pub struct EventRegistry {
+    routes: HashMap<(Address, H256), Route>,
+    handlers: Vec<Arc<dyn EventHandler>>,
+}
+
+impl EventRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The market indexing events: TokenRegistered on both exchanges and the
+    /// question lifecycle on every UMA CTF Adapter, plus OrderFilled on both exchanges
+    /// with `indexer.index_trades` and the Conditional Tokens and NegRiskAdapter
+    /// position events with `indexer.index_positions`, and Conditional Tokens
+    /// transfers with `indexer.index_transfers`
+    pub fn from_config(config: &Config, store: Arc<dyn MarketStore>) -> Self {
+        let markets: Arc<dyn EventHandler> =
+            Arc::new(MarketIngester::from_config(config, store.clone()));
+        let questions: Arc<dyn EventHandler> = Arc::new(QuestionIngester::new(store.clone()));
+
+        let mut registry = Self::new();
+        registry
+            .register::<TokenRegisteredFilter>(config.contracts.ctf_exchange, markets.clone())
+            .register::<TokenRegisteredFilter>(config.contracts.neg_risk_exchange, markets);
+        for &adapter in &config.contracts.uma_ctf_adapters {
+            registry
+                .register::<QuestionInitializedFilter>(adapter, questions.clone())
+                .register::<QuestionResolvedFilter>(adapter, questions.clone())
+                .register::<QuestionResetFilter>(adapter, questions.clone())
+                .register::<QuestionPausedFilter>(adapter, questions.clone())
+                .register::<QuestionUnpausedFilter>(adapter, questions.clone());
+        }
+
+        // Opt-in: fills outnumber every other event by orders of magnitude
+        if config.indexer.index_trades {
+            let fills: Arc<dyn EventHandler> = Arc::new(FillIngester::new(store.clone()));
+            registry
+                .register::<OrderFilledFilter>(config.contracts.ctf_exchange, fills.clone())
+                .register::<OrderFilledFilter>(config.contracts.neg_risk_exchange, fills);
+        }
+        if config.indexer.index_positions {
+            let ctf = config.contracts.ctf;
+            let adapter = config.contracts.neg_risk_adapter;
+            let positions: Arc<dyn EventHandler> =
+                Arc::new(PositionIngester::new(store.clone(), adapter));
+            registry
+                .register::<conditional_tokens::PositionSplitFilter>(ctf, positions.clone())
+                .register::<conditional_tokens::PositionsMergeFilter>(ctf, positions.clone())
+                .register::<conditional_tokens::PayoutRedemptionFilter>(ctf, positions.clone())
+                .register::<neg_risk_adapter::PositionSplitFilter>(adapter, positions.clone())
+                .register::<neg_risk_adapter::PositionsMergeFilter>(adapter, positions.clone())
+                .register::<neg_risk_adapter::PayoutRedemptionFilter>(adapter, positions);
+        }
+        if config.indexer.index_transfers {
+            let ctf = config.contracts.ctf;
+            let transfers: Arc<dyn EventHandler> = Arc::new(TransferIngester::new(store));
+            registry
+                .register::<conditional_tokens::TransferSingleFilter>(ctf, transfers.clone())
+                .register::<conditional_tokens::TransferBatchFilter>(ctf, transfers);
+        }
+        registry
+    }
+
+    /// Route event `E` emitted by `address` to `handler`
+    ///
+    /// Registering the same pair again replaces the earlier handler.
+    pub fn register<E: EthEvent>(
+        &mut self,
+        address: Address,
+        handler: Arc<dyn EventHandler>,
+    ) -> &mut Self {
+        let handler = match self.handlers.iter().position(|h| Arc::ptr_eq(h, &handler)) {
+            Some(index) => index,
+            None => {
+                self.handlers.push(handler);
+                self.handlers.len() - 1
+            }
+        };
+
+        self.routes.insert(
+            (address, E::signature()),
+            Route {
+                name: E::name().into_owned(),
+                handler,
+            },
+        );
+        self
+    }
+
+    /// Names of the registered events, sorted and deduplicated
+    pub fn event_names(&self) -> Vec<&str> {
+        let names: BTreeSet<&str> = self.routes.values().map(|r| r.name.as_str()).collect();
+        names.into_iter().collect()
+    }
+
+    /// Filter matching every registered address and topic0
+    ///
+    /// The filter is the cross product of addresses and topics, so it can
+    /// return pairs that were never registered; `dispatch` counts those as
+    /// unknown. An empty registry yields a filter matching every log.
+    pub fn filter(&self) -> Filter {
+        let addresses: Vec<Address> = self
+            .routes
+            .keys()
+            .map(|(address, _)| *address)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let topics: Vec<H256> = self
+            .routes
+            .keys()
+            .map(|(_, topic)| *topic)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let filter = match addresses.as_slice() {
+            [address] => Filter::new().address(*address),
+            _ => Filter::new().address(addresses),
+        };
+        match topics.as_slice() {
+            [topic] => filter.topic0(*topic),
+            _ => filter.topic0(topics),
+        }
+    }
+
+    /// Decode `logs` and pass each handler its events
+    ///
+    /// Handlers run one after another in registration order.
+    pub async fn dispatch(&self, logs: &[Log]) -> Result<IngestStats> {
+        let mut batches: Vec<Vec<DecodedLog>> = vec![Vec::new(); self.handlers.len()];
+
+        for log in logs {
+            let Some(&topic0) = log.topics.first() else {
+                continue;
+            };
+            let Some(route) = self.routes.get(&(log.address, topic0)) else {
+                debug!("No decoder for topic {:?} from {:?}", topic0, log.address);
+                metrics::UNKNOWN_EVENTS
+                    .with_label_values(&[&format!("{:?}", topic0)])
+                    .inc();
+                continue;
+            };
+
+            let decoded = PolymarketEvent::decode(log);
+            metrics::record_decode(&route.name, &decoded);
+            match decoded {
+                Ok(event) => batches[route.handler].push(DecodedLog {
+                    log: log.clone(),
+                    event,
+                }),
+                Err(e) => warn!("Failed to parse {} log: {}", route.name, e),
+            }
+        }
+
+        let mut stats = IngestStats::default();
+        for (handler, events) in self.handlers.iter().zip(batches) {
+            if !events.is_empty() {
+                stats += handler.handle(events).await?;
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use std::sync::Mutex;
+
+    /// Records the names of the events it receives
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<&'static str>>);
+
+    #[async_trait]
+    impl EventHandler for Recorder {
+        async fn handle(&self, events: Vec<DecodedLog>) -> Result<IngestStats> {
+            let mut seen = self.0.lock().unwrap();
+            for decoded in events {
+                seen.push(match decoded.event {
+                    PolymarketEvent::Exchange(_) => "exchange",
+                    PolymarketEvent::NegRiskAdapter(_) => "adapter",
+                    PolymarketEvent::ConditionalTokens(_) => "ctf",
+                    PolymarketEvent::UmaCtfAdapter(_) => "uma",
+                });
+            }
+            Ok(IngestStats::default())
+        }
+    }
+
+    fn token_registered(address: Address) -> Log {
+        Log {
+            address,
+            topics: vec![
+                TokenRegisteredFilter::signature(),
+                H256::from_low_u64_be(1),
+                H256::from_low_u64_be(2),
+                H256::repeat_byte(0xab),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_covers_registered_pairs() {
+        let handler: Arc<dyn EventHandler> = Arc::new(Recorder::default());
+        let mut registry = EventRegistry::new();
+        registry.register::<TokenRegisteredFilter>(Address::repeat_byte(1), handler.clone());
+
+        // A single pair keeps plain values so the eth_getLogs request is unchanged
+        let filter = serde_json::to_value(registry.filter()).unwrap();
+        assert!(filter["address"].is_string());
+        assert!(filter["topics"][0].is_string());
+
+        registry
+            .register::<TokenRegisteredFilter>(Address::repeat_byte(2), handler.clone())
+            .register::<OrderFilledFilter>(Address::repeat_byte(2), handler);
+        let filter = serde_json::to_value(registry.filter()).unwrap();
+        assert_eq!(filter["address"].as_array().unwrap().len(), 2);
+        assert_eq!(filter["topics"][0].as_array().unwrap().len(), 2);
+        assert_eq!(registry.event_names(), ["OrderFilled", "TokenRegistered"]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_routes_by_address_and_topic() {
+        let recorder = Arc::new(Recorder::default());
+        let exchange = Address::repeat_byte(1);
+        let mut registry = EventRegistry::new();
+        registry.register::<TokenRegisteredFilter>(exchange, recorder.clone());
+
+        let mut unknown_topic = token_registered(exchange);
+        unknown_topic.topics[0] = H256::repeat_byte(0xee);
+        let logs = [
+            token_registered(exchange),
+            token_registered(Address::repeat_byte(9)),
+            unknown_topic,
+        ];
+
+        let before = metrics::UNKNOWN_EVENTS
+            .with_label_values(&[&format!("{:?}", H256::repeat_byte(0xee))])
+            .get();
+        registry.dispatch(&logs).await.unwrap();
+
+        assert_eq!(*recorder.0.lock().unwrap(), ["exchange"]);
+        let after = metrics::UNKNOWN_EVENTS
+            .with_label_values(&[&format!("{:?}", H256::repeat_byte(0xee))])
+            .get();
+        assert_eq!(after - before, 1);
+    }
+
+    #[test]
+    fn test_trades_positions_and_transfers_are_opt_in() {
+        let mut config = Config::default();
+        let store = Arc::new(MemoryStore::new());
+        let opt_in = ["OrderFilled", "PositionSplit", "TransferBatch"];
+
+        let registry = EventRegistry::from_config(&config, store.clone());
+        let names = registry.event_names();
+        assert!(opt_in.iter().all(|name| !names.contains(name)));
+
+        config.indexer.index_trades = true;
+        config.indexer.index_positions = true;
+        config.indexer.index_transfers = true;
+        let registry = EventRegistry::from_config(&config, store);
+        let names = registry.event_names();
+        assert!(opt_in.iter().all(|name| names.contains(name)));
+    }
+}
@@ -0,0 +1,124 @@
+// Shutdown - stop long-running commands cleanly on SIGINT/SIGTERM
+//
+// Commands check `Shutdown` between units of work (a block range, a market)
+// and stop taking new work once a signal arrives. Work already in flight gets
+// the configured grace period to finish so its checkpoint can be saved;
+// anything still running after that is abandoned and redone on the next run.
+
+use crate::config::IndexerConfig;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::info;
+
+/// Shutdown request shared by the tasks of one command
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    requested: watch::Receiver<bool>,
+    grace: Duration,
+}
+
+impl Shutdown {
+    /// Request shutdown on SIGINT or SIGTERM
+    pub fn on_signals(grace: Duration) -> Self {
+        let (trigger, shutdown) = Self::manual(grace);
+        tokio::spawn(async move {
+            wait_for_signal().await;
+            info!(
+                "Shutdown requested, finishing in-flight work (up to {}s)",
+                grace.as_secs()
+            );
+            let _ = trigger.send(true);
+        });
+        shutdown
+    }
+
+    /// Request shutdown on signals, with the `[indexer]` grace period
+    pub fn from_config(config: &IndexerConfig) -> Self {
+        Self::on_signals(Duration::from_secs(config.shutdown_grace_secs))
+    }
+
+    /// Request shutdown by sending `true` on the returned sender
+    ///
+    /// Dropping the sender without sending leaves shutdown never requested.
+    pub fn manual(grace: Duration) -> (watch::Sender<bool>, Self) {
+        let (trigger, requested) = watch::channel(false);
+        (trigger, Self { requested, grace })
+    }
+
+    /// Whether shutdown has been requested
+    pub fn is_requested(&self) -> bool {
+        *self.requested.borrow()
+    }
+
+    /// Wait until shutdown is requested
+    pub async fn requested(&self) {
+        let mut requested = self.requested.clone();
+        if requested.wait_for(|&r| r).await.is_err() {
+            // Trigger dropped without a request: never resolve
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Run `work` to completion, unless shutdown is requested and the grace
+    /// period runs out first (`None`)
+    pub async fn drain<F: Future>(&self, work: F) -> Option<F::Output> {
+        tokio::pin!(work);
+        tokio::select! {
+            biased;
+            output = &mut work => Some(output),
+            _ = self.requested() => tokio::time::timeout(self.grace, work).await.ok(),
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn wait_for_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).expect("install SIGTERM handler");
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn sleep_then(ms: u64) -> u64 {
+        tokio::time::sleep(Duration::from_millis(ms)).await;
+        ms
+    }
+
+    #[tokio::test]
+    async fn test_drain_waits_for_work_within_grace() {
+        let (trigger, shutdown) = Shutdown::manual(Duration::from_secs(5));
+        assert_eq!(shutdown.drain(sleep_then(1)).await, Some(1));
+
+        trigger.send(true).unwrap();
+        assert!(shutdown.is_requested());
+        assert_eq!(shutdown.drain(sleep_then(20)).await, Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_drain_abandons_work_after_grace() {
+        let (trigger, shutdown) = Shutdown::manual(Duration::from_millis(10));
+        trigger.send(true).unwrap();
+        assert_eq!(shutdown.drain(sleep_then(5_000)).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_trigger_never_requests() {
+        let (trigger, shutdown) = Shutdown::manual(Duration::ZERO);
+        drop(trigger);
+        assert!(!shutdown.is_requested());
+        assert_eq!(shutdown.drain(sleep_then(1)).await, Some(1));
+    }
+}
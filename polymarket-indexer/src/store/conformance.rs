@@ -0,0 +1,340 @@
+// Store conformance - behaviour every `MarketStore` backend must share
+//
+// Each check takes an empty store and is called from the test module of
+// every backend, so the backends are held to one expectation instead of
+// copies that drift apart.
+
+use crate::candles::{self, Resolution};
+use crate::config::ContractsConfig;
+use crate::db::models::{WalletPosition, WalletStats};
+use crate::market_stats;
+use crate::polymarket::events::TokenRegistered;
+use crate::polymarket::positions::{PositionEvent, PositionEventKind};
+use crate::polymarket::trades::Fill;
+use crate::polymarket::transfers::TokenTransfer;
+use crate::polymarket::uma::{AncillaryData, QuestionEvent, QuestionEventKind};
+use crate::store::MarketStore;
+use crate::testing::time;
+use crate::wallets;
+use chrono::Duration;
+use ethers::types::{Address, U256};
+
+/// Market with outcome tokens 7 and 8
+fn market() -> TokenRegistered {
+    TokenRegistered {
+        token0: U256::from(7),
+        token1: U256::from(8),
+        condition_id: [1; 32],
+        block_number: 100,
+        tx_hash: "0xab".to_string(),
+        transaction_index: 0,
+        log_index: 0,
+    }
+}
+
+/// Maker buys `shares` of token 7 at `price` cents, `seconds` after a fixed
+/// time
+fn fill(log_index: u64, seconds: i64, price: u64, shares: u64) -> Fill {
+    Fill {
+        exchange: Address::repeat_byte(0xee),
+        order_hash: [log_index as u8; 32],
+        maker: Address::repeat_byte(1),
+        taker: Address::repeat_byte(2),
+        maker_asset_id: U256::zero(),
+        taker_asset_id: U256::from(7),
+        maker_amount_filled: U256::from(price * shares * 10_000),
+        taker_amount_filled: U256::from(shares * 1_000_000),
+        fee: U256::zero(),
+        block_number: 100 + log_index,
+        block_time: time(seconds),
+        tx_hash: "0xef".to_string(),
+        log_index,
+    }
+}
+
+fn position(log_index: u64, seconds: i64, kind: PositionEventKind, amount: u64) -> PositionEvent {
+    PositionEvent {
+        contract: Address::repeat_byte(0xc7),
+        kind,
+        condition_id: market().condition_id,
+        stakeholder: Address::repeat_byte(1),
+        amount: U256::from(amount),
+        block_number: 100 + log_index,
+        block_time: time(seconds),
+        tx_hash: "0xfa".to_string(),
+        log_index,
+    }
+}
+
+/// `amount` of `token_id` from wallet 1 to `to`
+fn transfer(to: u8, token_id: u64, amount: u64, log_index: u64, batch_index: u32) -> TokenTransfer {
+    TokenTransfer {
+        operator: Address::repeat_byte(1),
+        from: Address::repeat_byte(1),
+        to: Address::repeat_byte(to),
+        token_id: U256::from(token_id),
+        amount: U256::from(amount),
+        block_number: 110,
+        block_time: time(20),
+        tx_hash: "0xfa".to_string(),
+        log_index,
+        batch_index,
+    }
+}
+
+fn question_event(log_index: u64, kind: QuestionEventKind) -> QuestionEvent {
+    QuestionEvent {
+        question_id: [3; 32],
+        adapter: Address::repeat_byte(9),
+        kind,
+        block_number: 100 + log_index,
+        tx_hash: "0xcd".to_string(),
+        log_index,
+    }
+}
+
+pub async fn check_question_lifecycle(store: &dyn MarketStore) {
+    let initialized = question_event(
+        0,
+        QuestionEventKind::Initialized {
+            request_timestamp: 1_700_000_000,
+            creator: Address::repeat_byte(1),
+            ancillary_data: AncillaryData::parse(b"q: title: Will it rain?, description: Rain."),
+            reward_token: Address::zero(),
+            reward: U256::zero(),
+            proposal_bond: U256::zero(),
+        },
+    );
+    let condition_id = initialized.condition_id_hex();
+
+    assert!(store.record_question_event(&initialized).await.unwrap());
+    assert!(!store.record_question_event(&initialized).await.unwrap());
+    for (log_index, kind) in [
+        (1, QuestionEventKind::Reset),
+        (2, QuestionEventKind::Paused),
+        (3, QuestionEventKind::Unpaused),
+        (
+            4,
+            QuestionEventKind::Resolved {
+                settled_price: 1_000_000_000_000_000_000u64.into(),
+                payouts: vec![U256::one(), U256::zero()],
+            },
+        ),
+    ] {
+        store
+            .record_question_event(&question_event(log_index, kind))
+            .await
+            .unwrap();
+    }
+
+    let question = store.get_question(&condition_id).await.unwrap().unwrap();
+    assert_eq!(question.title.as_deref(), Some("Will it rain?"));
+    assert_eq!(question.status, "resolved");
+    assert_eq!(question.reset_count, 1);
+    assert!(!question.paused);
+    assert_eq!(question.payouts, Some(serde_json::json!(["1", "0"])));
+    assert_eq!(question.resolved_block, Some(104));
+}
+
+pub async fn check_fills_and_candles(store: &dyn MarketStore) {
+    let fills = [fill(0, 0, 50, 10), fill(1, 10, 60, 5), fill(2, 100, 40, 1)];
+
+    assert_eq!(store.record_fills(&fills[1..]).await.unwrap(), 2);
+    // Overlapping batch: only the new fill counts, out of order is fine
+    assert_eq!(store.record_fills(&fills[..2]).await.unwrap(), 1);
+
+    let minutes = store.get_candles("7", Resolution::Minute).await.unwrap();
+    assert_eq!(minutes.len(), 2);
+    assert_eq!(minutes[0].trades, 2);
+    assert_eq!((minutes[0].open, minutes[0].close), (0.5, 0.6));
+    assert_eq!(
+        (minutes[0].volume, minutes[0].notional),
+        (15_000_000, 8_000_000)
+    );
+    let hours = store.get_candles("7", Resolution::Hour).await.unwrap();
+    assert_eq!(
+        (hours[0].high, hours[0].low, hours[0].trades),
+        (0.6, 0.4, 3)
+    );
+
+    let (first, last) = store.get_fill_time_range(0, 200).await.unwrap().unwrap();
+    assert_eq!((first, last), (time(0), time(100)));
+    let stored = store
+        .get_fills_between(first, last + Duration::seconds(1))
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 3);
+    assert_eq!(stored[0].side.as_deref(), Some("buy"));
+    assert_eq!(stored[1].maker_amount_filled, "3000000");
+
+    // Rebuilding from the stored fills gives the same candles
+    assert_eq!(candles::rebuild(store, 0, 200).await.unwrap(), 4);
+    assert_eq!(
+        store.get_candles("7", Resolution::Hour).await.unwrap(),
+        hours
+    );
+    assert_eq!(
+        store.get_candles("7", Resolution::Minute).await.unwrap(),
+        minutes
+    );
+}
+
+pub async fn check_market_series(store: &dyn MarketStore) {
+    let market = market();
+    store.upsert_market(&market, None).await.unwrap();
+    let condition_id = market.condition_id_hex();
+
+    let day = 86_400;
+    let events = [
+        position(0, 0, PositionEventKind::Split, 100),
+        position(1, 10, PositionEventKind::Merge, 30),
+        position(2, day, PositionEventKind::Redeem, 50),
+    ];
+    assert_eq!(store.record_position_events(&events).await.unwrap(), 3);
+    assert_eq!(store.record_position_events(&events).await.unwrap(), 0);
+    store
+        .record_fills(&[fill(3, 20, 50, 10), fill(4, 30, 60, 5)])
+        .await
+        .unwrap();
+
+    let series = store.get_market_series(&condition_id).await.unwrap();
+    assert_eq!(series.len(), 2);
+    assert_eq!((series[0].open_interest, series[1].open_interest), (70, 20));
+    assert_eq!((series[0].volume, series[0].trades), (8_000_000, 2));
+
+    let stored = store
+        .get_position_events_between(series[0].day, series[1].day)
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 2);
+    assert_eq!(stored[0].kind, "split");
+    assert_eq!(stored[0].condition_id, condition_id);
+
+    // Rebuilding from the stored events and fills gives the same series
+    assert_eq!(market_stats::rebuild(store, 0, 200).await.unwrap(), 2);
+    assert_eq!(
+        store.get_market_series(&condition_id).await.unwrap(),
+        series
+    );
+}
+
+pub async fn check_transfers_and_wallets(store: &dyn MarketStore) {
+    let batch = [
+        transfer(2, 8, 5_000_000, 3, 1),
+        transfer(2, 7, 5_000_000, 3, 0),
+    ];
+    assert_eq!(store.record_transfers(&batch).await.unwrap(), 2);
+    assert_eq!(store.record_transfers(&batch).await.unwrap(), 0);
+    assert_eq!(
+        store.get_transfer_time_range(0, 200).await.unwrap(),
+        Some((time(20), time(20)))
+    );
+    let stored = store
+        .get_transfers_between(time(20), time(20) + Duration::days(1))
+        .await
+        .unwrap();
+    assert_eq!(stored[0].token_id, "7");
+    assert_eq!(stored[1].amount, "5000000");
+
+    let position = |token_id: &str| WalletPosition {
+        wallet: "0x01".to_string(),
+        token_id: token_id.to_string(),
+        condition_id: None,
+        shares: 10,
+        cost_basis: 4,
+        avg_price: Some(0.4),
+        realized_pnl: -1,
+        settled: false,
+        first_block: 100,
+        last_block: 101,
+    };
+    let stats = [WalletStats {
+        wallet: "0x01".to_string(),
+        markets: 0,
+        open_positions: 2,
+        resolved_markets: 0,
+        winning_markets: 0,
+        win_rate: None,
+        realized_pnl: -2,
+        volume: 8,
+        trades: 2,
+        first_block: 100,
+        last_block: 101,
+    }];
+    store
+        .replace_wallets(None, &[position("10"), position("9")], &stats)
+        .await
+        .unwrap();
+    let positions = store.get_wallet_positions("0x01").await.unwrap();
+    assert_eq!(positions, [position("9"), position("10")]);
+    assert_eq!(
+        store.get_wallet_stats("0x01").await.unwrap().as_ref(),
+        stats.first()
+    );
+
+    // Replacing other wallets keeps this one
+    store
+        .replace_wallets(Some(&["0x02".to_string()]), &[], &[])
+        .await
+        .unwrap();
+    assert_eq!(store.get_wallet_positions("0x01").await.unwrap().len(), 2);
+    assert!(store.get_wallet_stats("0x01").await.unwrap().is_some());
+
+    store.replace_wallets(None, &[], &[]).await.unwrap();
+    assert!(store.get_wallet_positions("0x01").await.unwrap().is_empty());
+    assert!(store.get_wallet_stats("0x01").await.unwrap().is_none());
+}
+
+pub async fn check_wallet_rebuild(store: &dyn MarketStore) {
+    let market = market();
+    store.upsert_market(&market, None).await.unwrap();
+
+    // Wallet 1 buys 10 shares at 50c and 5 at 60c, then sends 5 to wallet 3
+    store
+        .record_fills(&[fill(0, 0, 50, 10), fill(1, 10, 60, 5)])
+        .await
+        .unwrap();
+    store
+        .record_transfers(&[transfer(3, 7, 5_000_000, 0, 0)])
+        .await
+        .unwrap();
+
+    let contracts = ContractsConfig::default();
+    assert_eq!(
+        wallets::rebuild(store, &contracts, None).await.unwrap(),
+        (2, 2)
+    );
+    let sender = format!("{:?}", Address::repeat_byte(1));
+    let positions = store.get_wallet_positions(&sender).await.unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].condition_id, Some(market.condition_id_hex()));
+    // 8 USDC for 15 shares, a third of it moved with the transfer
+    assert_eq!(
+        (positions[0].shares, positions[0].cost_basis),
+        (10_000_000, 5_333_334)
+    );
+    let stats = store.get_wallet_stats(&sender).await.unwrap().unwrap();
+    assert_eq!((stats.volume, stats.trades), (8_000_000, 2));
+    assert_eq!((stats.open_positions, stats.win_rate), (1, None));
+
+    // A later buy of 5 shares at 40c only rewrites wallet 1, from its whole
+    // history; wallet 3 is kept
+    let recipient = format!("{:?}", Address::repeat_byte(3));
+    let kept = store.get_wallet_stats(&recipient).await.unwrap();
+    assert!(kept.is_some());
+    store.record_fills(&[fill(20, 30, 40, 5)]).await.unwrap();
+    assert_eq!(
+        wallets::rebuild(store, &contracts, Some((115, 200)))
+            .await
+            .unwrap(),
+        (1, 1)
+    );
+    let positions = store.get_wallet_positions(&sender).await.unwrap();
+    assert_eq!(
+        (positions[0].shares, positions[0].cost_basis),
+        (15_000_000, 7_333_334)
+    );
+    let stats = store.get_wallet_stats(&sender).await.unwrap().unwrap();
+    assert_eq!((stats.volume, stats.trades), (10_000_000, 3));
+    assert_eq!(store.get_wallet_stats(&recipient).await.unwrap(), kept);
+}
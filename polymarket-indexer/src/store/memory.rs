@@ -0,0 +1,991 @@
+// In-memory store - for tests and dry runs
+//
+// Mirrors the Postgres semantics: upserts keep existing metadata when none is
+// given, tags are deduplicated per market and tag inserts bump `updated_at`.
+// Nothing is persisted.
+
+use crate::blocks::{BlockHeader, ReplacedBlock};
+use crate::candles::{self, Resolution, Tick};
+use crate::db::models::{
+    Block, Candle, DataAnomaly, Fill as DbFill, Market, MarketDailyStats, MarketSeriesPoint,
+    MarketStatusTransition, OutcomeToken, PositionEvent as DbPositionEvent, Tag as DbTag,
+    TokenTransfer as DbTokenTransfer, UmaQuestion, WalletPosition, WalletStats,
+};
+use crate::market_stats;
+use crate::polymarket::events::TokenRegistered;
+use crate::polymarket::lifecycle::{MarketStatus, StatusSignal};
+use crate::polymarket::market::{MarketMetadata, Tag as ApiTag};
+use crate::polymarket::positions::PositionEvent;
+use crate::polymarket::trades::Fill;
+use crate::polymarket::transfers::TokenTransfer;
+use crate::polymarket::uma::{QuestionEvent, QuestionEventKind};
+use crate::store::MarketStore;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Debug, Default)]
+struct State {
+    markets: HashMap<String, Market>,
+    outcome_tokens: HashMap<String, OutcomeToken>,
+    tags: HashMap<String, DbTag>,
+    market_tags: HashMap<String, BTreeSet<String>>,
+    checkpoints: HashMap<String, u64>,
+    questions: HashMap<String, UmaQuestion>,
+    /// (tx_hash, log_index) of recorded question events
+    question_events: HashSet<(String, u64)>,
+    /// Status transitions in the order they were applied
+    transitions: Vec<MarketStatusTransition>,
+    anomalies: Vec<DataAnomaly>,
+    blocks: HashMap<u64, Block>,
+    /// Fills by (tx_hash, log_index)
+    fills: HashMap<(String, u64), DbFill>,
+    candles: BTreeMap<(String, String, DateTime<Utc>), Candle>,
+    /// Position events by (tx_hash, log_index)
+    position_events: HashMap<(String, u64), DbPositionEvent>,
+    market_stats: BTreeMap<(String, DateTime<Utc>), MarketDailyStats>,
+    /// Transfers by (tx_hash, log_index, batch_index)
+    transfers: HashMap<(String, u64, u32), DbTokenTransfer>,
+    wallet_positions: Vec<WalletPosition>,
+    wallet_stats: HashMap<String, WalletStats>,
+}
+
+impl State {
+    fn merge_market_stats(&mut self, stats: impl IntoIterator<Item = MarketDailyStats>) {
+        for row in stats {
+            self.market_stats
+                .entry(row.key())
+                .and_modify(|existing| existing.merge(&row))
+                .or_insert(row);
+        }
+    }
+}
+
+/// Market store that keeps everything in memory
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panic while holding the lock can't leave the maps half-updated
+        // in a way later reads care about, so ignore poisoning
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn fill_row(fill: &Fill, now: DateTime<Utc>) -> DbFill {
+    let trade = fill.trade();
+    DbFill {
+        tx_hash: fill.tx_hash.clone(),
+        log_index: fill.log_index as i64,
+        exchange: format!("{:?}", fill.exchange),
+        order_hash: fill.order_hash_hex(),
+        maker: format!("{:?}", fill.maker),
+        taker: format!("{:?}", fill.taker),
+        maker_asset_id: fill.maker_asset_id.to_string(),
+        taker_asset_id: fill.taker_asset_id.to_string(),
+        maker_amount_filled: fill.maker_amount_filled.to_string(),
+        taker_amount_filled: fill.taker_amount_filled.to_string(),
+        fee: fill.fee.to_string(),
+        token_id: trade.map(|t| t.token_id.to_string()),
+        side: trade.map(|t| t.side.to_string()),
+        price: trade.map(|t| t.price),
+        size: trade.map(|t| t.size),
+        notional: trade.map(|t| t.notional),
+        block_number: fill.block_number as i64,
+        block_time: fill.block_time,
+        created_at: now,
+    }
+}
+
+fn position_event_row(event: &PositionEvent, now: DateTime<Utc>) -> DbPositionEvent {
+    DbPositionEvent {
+        tx_hash: event.tx_hash.clone(),
+        log_index: event.log_index as i64,
+        contract: format!("{:?}", event.contract),
+        kind: event.kind.to_string(),
+        condition_id: event.condition_id_hex(),
+        stakeholder: format!("{:?}", event.stakeholder),
+        amount: event.amount.to_string(),
+        block_number: event.block_number as i64,
+        block_time: event.block_time,
+        created_at: now,
+    }
+}
+
+fn transfer_row(transfer: &TokenTransfer, now: DateTime<Utc>) -> DbTokenTransfer {
+    DbTokenTransfer {
+        tx_hash: transfer.tx_hash.clone(),
+        log_index: transfer.log_index as i64,
+        batch_index: transfer.batch_index as i32,
+        operator: format!("{:?}", transfer.operator),
+        from_address: format!("{:?}", transfer.from),
+        to_address: format!("{:?}", transfer.to),
+        token_id: transfer.token_id.to_string(),
+        amount: transfer.amount.to_string(),
+        block_number: transfer.block_number as i64,
+        block_time: transfer.block_time,
+        created_at: now,
+    }
+}
+
+fn apply_metadata(market: &mut Market, metadata: &MarketMetadata) {
+    market.question = Some(metadata.question.clone());
+    market.slug = Some(metadata.slug.clone());
+    if metadata.id.is_some() {
+        market.pm_market_id = metadata.id.clone();
+    }
+    if let Ok(outcomes) = serde_json::to_value(&metadata.outcomes) {
+        market.outcomes = Some(outcomes);
+    }
+    if metadata.start_date.is_some() {
+        market.start_date = metadata.start_date.clone();
+    }
+    if metadata.end_date.is_some() {
+        market.end_date = metadata.end_date.clone();
+    }
+    market.metadata_fetched_at = Some(Utc::now());
+}
+
+#[async_trait]
+impl MarketStore for MemoryStore {
+    async fn upsert_market(
+        &self,
+        event: &TokenRegistered,
+        metadata: Option<&MarketMetadata>,
+    ) -> Result<()> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let registered_at = state.blocks.get(&event.block_number).map(|b| b.timestamp);
+        let market = state
+            .markets
+            .entry(event.condition_id_hex())
+            .or_insert_with(|| Market {
+                condition_id: event.condition_id_hex(),
+                token0: event.token0.to_string(),
+                token1: event.token1.to_string(),
+                block_number: event.block_number as i64,
+                tx_hash: event.tx_hash.clone(),
+                question: None,
+                slug: None,
+                pm_market_id: None,
+                outcomes: None,
+                start_date: None,
+                end_date: None,
+                created_at: now,
+                updated_at: now,
+                metadata_fetched_at: None,
+                status: MarketStatus::Registered.to_string(),
+                status_updated_at: None,
+                registered_at,
+            });
+
+        market.registered_at = market.registered_at.or(registered_at);
+        if let Some(metadata) = metadata {
+            apply_metadata(market, metadata);
+        }
+        market.updated_at = now;
+
+        let token_ids = event.outcome_token_ids();
+        for (index, token_id) in token_ids.iter().enumerate() {
+            let token = state
+                .outcome_tokens
+                .entry(token_id.to_string())
+                .or_insert_with(|| OutcomeToken {
+                    token_id: token_id.to_string(),
+                    condition_id: event.condition_id_hex(),
+                    outcome_index: index as i16,
+                    outcome_label: None,
+                    complement_token_id: token_ids[1 - index].to_string(),
+                    created_at: now,
+                    updated_at: now,
+                });
+            if let Some(label) = metadata.and_then(|m| m.outcomes.get(index)) {
+                token.outcome_label = Some(label.clone());
+                token.updated_at = now;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn update_market_metadata(
+        &self,
+        condition_id: &str,
+        metadata: &MarketMetadata,
+    ) -> Result<()> {
+        let mut state = self.state();
+        if let Some(market) = state.markets.get_mut(condition_id) {
+            apply_metadata(market, metadata);
+        }
+        for token in state.outcome_tokens.values_mut() {
+            if token.condition_id != condition_id {
+                continue;
+            }
+            if let Some(label) = metadata.outcomes.get(token.outcome_index as usize) {
+                token.outcome_label = Some(label.clone());
+                token.updated_at = Utc::now();
+            }
+        }
+        Ok(())
+    }
+
+    async fn insert_market_tags(&self, condition_id: &str, tags: &[ApiTag]) -> Result<()> {
+        let mut state = self.state();
+
+        for tag in tags {
+            let stored = state.tags.entry(tag.id.clone()).or_insert_with(|| DbTag {
+                pm_tag_id: tag.id.clone(),
+                label: None,
+                slug: None,
+            });
+            if tag.label.is_some() {
+                stored.label = tag.label.clone();
+            }
+            if tag.slug.is_some() {
+                stored.slug = tag.slug.clone();
+            }
+
+            state
+                .market_tags
+                .entry(condition_id.to_string())
+                .or_default()
+                .insert(tag.id.clone());
+        }
+
+        if !tags.is_empty() {
+            if let Some(market) = state.markets.get_mut(condition_id) {
+                market.updated_at = Utc::now();
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_market(&self, condition_id: &str) -> Result<Option<Market>> {
+        Ok(self.state().markets.get(condition_id).cloned())
+    }
+
+    async fn get_outcome_token(&self, token_id: &str) -> Result<Option<OutcomeToken>> {
+        Ok(self.state().outcome_tokens.get(token_id).cloned())
+    }
+
+    async fn get_outcome_tokens(&self, condition_id: &str) -> Result<Vec<OutcomeToken>> {
+        let mut tokens: Vec<OutcomeToken> = self
+            .state()
+            .outcome_tokens
+            .values()
+            .filter(|t| t.condition_id == condition_id)
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|t| t.outcome_index);
+        Ok(tokens)
+    }
+
+    async fn get_tags_for_market(&self, condition_id: &str) -> Result<Vec<DbTag>> {
+        let state = self.state();
+        let mut tags: Vec<DbTag> = state
+            .market_tags
+            .get(condition_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| state.tags.get(id).cloned())
+            .collect();
+        // Postgres sorts NULL labels last
+        tags.sort_by(|a, b| (a.label.is_none(), &a.label).cmp(&(b.label.is_none(), &b.label)));
+
+        Ok(tags)
+    }
+
+    async fn get_markets_without_metadata(&self, limit: i64) -> Result<Vec<Market>> {
+        let mut markets: Vec<Market> = self
+            .state()
+            .markets
+            .values()
+            .filter(|m| m.metadata_fetched_at.is_none())
+            .cloned()
+            .collect();
+        markets.sort_by_key(|m| m.created_at);
+        markets.truncate(limit.max(0) as usize);
+
+        Ok(markets)
+    }
+
+    async fn count_markets(&self) -> Result<i64> {
+        Ok(self.state().markets.len() as i64)
+    }
+
+    async fn get_checkpoint(&self, name: &str) -> Result<Option<u64>> {
+        Ok(self.state().checkpoints.get(name).copied())
+    }
+
+    async fn set_checkpoint(&self, name: &str, block_number: u64) -> Result<()> {
+        self.state()
+            .checkpoints
+            .insert(name.to_string(), block_number);
+        Ok(())
+    }
+
+    async fn record_question_event(&self, event: &QuestionEvent) -> Result<bool> {
+        let now = Utc::now();
+        let mut state = self.state();
+        if !state
+            .question_events
+            .insert((event.tx_hash.clone(), event.log_index))
+        {
+            return Ok(false);
+        }
+
+        let question = state
+            .questions
+            .entry(event.question_id_hex())
+            .or_insert_with(|| UmaQuestion {
+                question_id: event.question_id_hex(),
+                condition_id: event.condition_id_hex(),
+                adapter: format!("{:?}", event.adapter),
+                creator: None,
+                request_timestamp: None,
+                ancillary_data: None,
+                title: None,
+                description: None,
+                resolution_data: None,
+                status: "initialized".to_string(),
+                paused: false,
+                reset_count: 0,
+                settled_price: None,
+                payouts: None,
+                initialized_block: None,
+                resolved_block: None,
+                created_at: now,
+                updated_at: now,
+            });
+
+        if let Some((creator, request_timestamp, data)) = event.initialization() {
+            question.creator = Some(format!("{:?}", creator));
+            question.request_timestamp = Some(request_timestamp as i64);
+            question.ancillary_data = Some(data.raw.clone());
+            question.title = data.title.clone();
+            question.description = data.description.clone();
+            question.resolution_data = data.resolution_data.clone();
+            question.initialized_block = Some(event.block_number as i64);
+        }
+        if let Some((settled_price, payouts)) = event.resolution() {
+            question.settled_price = Some(settled_price);
+            question.payouts = Some(serde_json::json!(payouts));
+            question.resolved_block = Some(event.block_number as i64);
+        }
+        if let Some(status) = event.kind.status() {
+            question.status = status.to_string();
+        }
+        if let Some(paused) = event.kind.paused() {
+            question.paused = paused;
+        }
+        if event.kind == QuestionEventKind::Reset {
+            question.reset_count += 1;
+        }
+        question.updated_at = now;
+
+        Ok(true)
+    }
+
+    async fn get_question(&self, condition_id: &str) -> Result<Option<UmaQuestion>> {
+        Ok(self
+            .state()
+            .questions
+            .values()
+            .filter(|q| q.condition_id == condition_id)
+            .max_by_key(|q| q.updated_at)
+            .cloned())
+    }
+
+    async fn set_market_status(
+        &self,
+        condition_id: &str,
+        from: MarketStatus,
+        to: MarketStatus,
+        signal: StatusSignal,
+        block_number: Option<u64>,
+    ) -> Result<bool> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let Some(market) = state.markets.get_mut(condition_id) else {
+            return Ok(false);
+        };
+        if market.status != from.as_str() {
+            return Ok(false);
+        }
+        market.status = to.to_string();
+        market.status_updated_at = Some(now);
+
+        state.transitions.push(MarketStatusTransition {
+            condition_id: condition_id.to_string(),
+            from_status: from.to_string(),
+            to_status: to.to_string(),
+            source: signal.source().to_string(),
+            block_number: block_number.map(|b| b as i64),
+            transitioned_at: now,
+        });
+
+        Ok(true)
+    }
+
+    async fn get_status_transitions(
+        &self,
+        condition_id: &str,
+    ) -> Result<Vec<MarketStatusTransition>> {
+        Ok(self
+            .state()
+            .transitions
+            .iter()
+            .filter(|t| t.condition_id == condition_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn record_anomaly(
+        &self,
+        condition_id: Option<&str>,
+        kind: &str,
+        detail: &str,
+        block_number: Option<u64>,
+    ) -> Result<()> {
+        let mut state = self.state();
+        let id = state.anomalies.len() as i64 + 1;
+        state.anomalies.push(DataAnomaly {
+            id,
+            condition_id: condition_id.map(str::to_string),
+            kind: kind.to_string(),
+            detail: detail.to_string(),
+            block_number: block_number.map(|b| b as i64),
+            created_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    async fn get_anomalies(&self, condition_id: &str) -> Result<Vec<DataAnomaly>> {
+        Ok(self
+            .state()
+            .anomalies
+            .iter()
+            .filter(|a| a.condition_id.as_deref() == Some(condition_id))
+            .cloned()
+            .collect())
+    }
+
+    async fn upsert_blocks(&self, headers: &[BlockHeader]) -> Result<Vec<ReplacedBlock>> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let mut replaced = Vec::new();
+        for header in headers {
+            let block = Block {
+                number: header.number as i64,
+                hash: header.hash_hex(),
+                parent_hash: header.parent_hash_hex(),
+                timestamp: header.time(),
+                created_at: now,
+            };
+            if let Some(previous) = state.blocks.get(&header.number) {
+                if previous.hash != block.hash {
+                    replaced.push(ReplacedBlock {
+                        number: header.number,
+                        hash: previous.hash.clone(),
+                        new_hash: block.hash.clone(),
+                    });
+                }
+            }
+            for market in state.markets.values_mut() {
+                if market.block_number == block.number {
+                    market.registered_at = Some(block.timestamp);
+                }
+            }
+            state.blocks.insert(header.number, block);
+        }
+        Ok(replaced)
+    }
+
+    async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        Ok(self.state().blocks.get(&number).cloned())
+    }
+
+    async fn record_fills(&self, fills: &[Fill]) -> Result<usize> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let mut ticks = Vec::new();
+        let mut inserted = 0;
+        for fill in fills {
+            let key = (fill.tx_hash.clone(), fill.log_index);
+            if state.fills.contains_key(&key) {
+                continue;
+            }
+            state.fills.insert(key, fill_row(fill, now));
+            inserted += 1;
+            ticks.extend(Tick::from_fill(fill));
+        }
+
+        let candles = candles::aggregate(&ticks);
+        let volume: Vec<MarketDailyStats> = candles
+            .iter()
+            .filter(|candle| candle.resolution == Resolution::Day.as_str())
+            .filter_map(|candle| {
+                let token = state.outcome_tokens.get(&candle.token_id)?;
+                Some(MarketDailyStats::from_candle(
+                    token.condition_id.clone(),
+                    candle,
+                ))
+            })
+            .collect();
+        state.merge_market_stats(volume);
+        for candle in candles {
+            state
+                .candles
+                .entry(candle.key())
+                .and_modify(|existing| existing.merge(&candle))
+                .or_insert(candle);
+        }
+        Ok(inserted)
+    }
+
+    async fn get_fill_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let state = self.state();
+        let times = state
+            .fills
+            .values()
+            .filter(|f| (from_block as i64..=to_block as i64).contains(&f.block_number))
+            .map(|f| f.block_time);
+        Ok(times.clone().min().zip(times.max()))
+    }
+
+    async fn get_fills_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbFill>> {
+        let mut fills: Vec<DbFill> = self
+            .state()
+            .fills
+            .values()
+            .filter(|f| f.block_time >= start && f.block_time < end)
+            .cloned()
+            .collect();
+        fills.sort_by_key(|f| (f.block_number, f.log_index));
+        Ok(fills)
+    }
+
+    async fn replace_candles(&self, candles: &[Candle]) -> Result<()> {
+        let mut state = self.state();
+        for candle in candles {
+            state.candles.insert(candle.key(), candle.clone());
+        }
+        Ok(())
+    }
+
+    async fn get_candles(&self, token_id: &str, resolution: Resolution) -> Result<Vec<Candle>> {
+        Ok(self
+            .state()
+            .candles
+            .values()
+            .filter(|c| c.token_id == token_id && c.resolution == resolution.as_str())
+            .cloned()
+            .collect())
+    }
+
+    async fn record_position_events(&self, events: &[PositionEvent]) -> Result<usize> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let mut rows = Vec::new();
+        let mut inserted = 0;
+        for event in events {
+            let key = (event.tx_hash.clone(), event.log_index);
+            if state.position_events.contains_key(&key) {
+                continue;
+            }
+            state
+                .position_events
+                .insert(key, position_event_row(event, now));
+            inserted += 1;
+            rows.extend(MarketDailyStats::from_position(event));
+        }
+        state.merge_market_stats(rows);
+        Ok(inserted)
+    }
+
+    async fn get_position_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let state = self.state();
+        let times = state
+            .position_events
+            .values()
+            .filter(|e| (from_block as i64..=to_block as i64).contains(&e.block_number))
+            .map(|e| e.block_time);
+        Ok(times.clone().min().zip(times.max()))
+    }
+
+    async fn get_position_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbPositionEvent>> {
+        let mut events: Vec<DbPositionEvent> = self
+            .state()
+            .position_events
+            .values()
+            .filter(|e| e.block_time >= start && e.block_time < end)
+            .cloned()
+            .collect();
+        events.sort_by_key(|e| (e.block_number, e.log_index));
+        Ok(events)
+    }
+
+    async fn replace_market_stats(
+        &self,
+        day: DateTime<Utc>,
+        stats: &[MarketDailyStats],
+    ) -> Result<()> {
+        let mut state = self.state();
+        state.market_stats.retain(|(_, row_day), _| *row_day != day);
+        state.merge_market_stats(stats.iter().cloned());
+        Ok(())
+    }
+
+    async fn get_market_series(&self, condition_id: &str) -> Result<Vec<MarketSeriesPoint>> {
+        let rows: Vec<MarketDailyStats> = self
+            .state()
+            .market_stats
+            .values()
+            .filter(|row| row.condition_id == condition_id)
+            .cloned()
+            .collect();
+        Ok(market_stats::series(&rows))
+    }
+
+    async fn record_transfers(&self, transfers: &[TokenTransfer]) -> Result<usize> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let mut inserted = 0;
+        for transfer in transfers {
+            let key = (
+                transfer.tx_hash.clone(),
+                transfer.log_index,
+                transfer.batch_index,
+            );
+            if state.transfers.contains_key(&key) {
+                continue;
+            }
+            state.transfers.insert(key, transfer_row(transfer, now));
+            inserted += 1;
+        }
+        Ok(inserted)
+    }
+
+    async fn get_transfer_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let state = self.state();
+        let times = state
+            .transfers
+            .values()
+            .filter(|t| (from_block as i64..=to_block as i64).contains(&t.block_number))
+            .map(|t| t.block_time);
+        Ok(times.clone().min().zip(times.max()))
+    }
+
+    async fn get_transfers_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbTokenTransfer>> {
+        let mut transfers: Vec<DbTokenTransfer> = self
+            .state()
+            .transfers
+            .values()
+            .filter(|t| t.block_time >= start && t.block_time < end)
+            .cloned()
+            .collect();
+        transfers.sort_by_key(|t| (t.block_number, t.log_index, t.batch_index));
+        Ok(transfers)
+    }
+
+    async fn replace_wallets(
+        &self,
+        wallets: Option<&[String]>,
+        positions: &[WalletPosition],
+        stats: &[WalletStats],
+    ) -> Result<()> {
+        let mut state = self.state();
+        let replaced = |wallet: &String| wallets.is_none_or(|w| w.contains(wallet));
+        state.wallet_positions.retain(|p| !replaced(&p.wallet));
+        state.wallet_positions.extend_from_slice(positions);
+        state.wallet_stats.retain(|wallet, _| !replaced(wallet));
+        state
+            .wallet_stats
+            .extend(stats.iter().map(|row| (row.wallet.clone(), row.clone())));
+        Ok(())
+    }
+
+    async fn get_wallet_positions(&self, wallet: &str) -> Result<Vec<WalletPosition>> {
+        let mut positions: Vec<WalletPosition> = self
+            .state()
+            .wallet_positions
+            .iter()
+            .filter(|p| p.wallet == wallet)
+            .cloned()
+            .collect();
+        // Decimal token IDs: shorter is smaller, as with Postgres NUMERIC
+        positions
+            .sort_by(|a, b| (a.token_id.len(), &a.token_id).cmp(&(b.token_id.len(), &b.token_id)));
+        Ok(positions)
+    }
+
+    async fn get_wallet_stats(&self, wallet: &str) -> Result<Option<WalletStats>> {
+        Ok(self.state().wallet_stats.get(wallet).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::conformance;
+    use crate::testing;
+    use ethers::types::U256;
+
+    fn event(id: u8) -> TokenRegistered {
+        TokenRegistered {
+            token0: U256::from(1),
+            token1: U256::from(2),
+            condition_id: [id; 32],
+            block_number: 100,
+            tx_hash: "0xab".to_string(),
+            transaction_index: 0,
+            log_index: 0,
+        }
+    }
+
+    fn metadata() -> MarketMetadata {
+        MarketMetadata {
+            id: Some("42".to_string()),
+            question: "Will it rain?".to_string(),
+            slug: "will-it-rain".to_string(),
+            condition_id: String::new(),
+            outcomes: vec!["Yes".to_string(), "No".to_string()],
+            start_date: None,
+            end_date: None,
+            active: Some(true),
+            closed: Some(false),
+        }
+    }
+
+    fn tag(id: &str, label: Option<&str>) -> ApiTag {
+        ApiTag {
+            id: id.to_string(),
+            label: label.map(str::to_string),
+            slug: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_keeps_metadata() {
+        let store = MemoryStore::new();
+        let event = event(1);
+
+        store
+            .upsert_market(&event, Some(&metadata()))
+            .await
+            .unwrap();
+        store.upsert_market(&event, None).await.unwrap();
+
+        let market = store
+            .get_market(&event.condition_id_hex())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(market.question.as_deref(), Some("Will it rain?"));
+        assert_eq!(market.pm_market_id.as_deref(), Some("42"));
+        assert_eq!(store.count_markets().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_outcome_tokens() {
+        let store = MemoryStore::new();
+        let condition_id = event(1).condition_id_hex();
+        store.upsert_market(&event(1), None).await.unwrap();
+
+        // Not derivable from the condition, so registration order is kept
+        let token = store.get_outcome_token("2").await.unwrap().unwrap();
+        assert_eq!(token.condition_id, condition_id);
+        assert_eq!(token.outcome_index, 1);
+        assert_eq!(token.complement_token_id, "1");
+        assert_eq!(token.outcome_label, None);
+
+        store
+            .update_market_metadata(&condition_id, &metadata())
+            .await
+            .unwrap();
+        let tokens = store.get_outcome_tokens(&condition_id).await.unwrap();
+        let labels: Vec<_> = tokens.iter().map(|t| t.outcome_label.as_deref()).collect();
+        assert_eq!(labels, [Some("Yes"), Some("No")]);
+        assert_eq!(
+            store
+                .get_outcome_token("3")
+                .await
+                .unwrap()
+                .map(|t| t.token_id),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_markets_without_metadata() {
+        let store = MemoryStore::new();
+        store.upsert_market(&event(1), None).await.unwrap();
+        store.upsert_market(&event(2), None).await.unwrap();
+
+        let pending = store.get_markets_without_metadata(10).await.unwrap();
+        assert_eq!(pending.len(), 2);
+
+        store
+            .update_market_metadata(&event(1).condition_id_hex(), &metadata())
+            .await
+            .unwrap();
+        let pending = store.get_markets_without_metadata(10).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].condition_id, event(2).condition_id_hex());
+    }
+
+    #[tokio::test]
+    async fn test_tags_are_deduplicated() {
+        let store = MemoryStore::new();
+        let condition_id = event(1).condition_id_hex();
+        store.upsert_market(&event(1), None).await.unwrap();
+
+        let tags = [tag("2", Some("Sports")), tag("1", Some("Politics"))];
+        store
+            .insert_market_tags(&condition_id, &tags)
+            .await
+            .unwrap();
+        store
+            .insert_market_tags(&condition_id, &[tag("1", None)])
+            .await
+            .unwrap();
+
+        let stored = store.get_tags_for_market(&condition_id).await.unwrap();
+        let labels: Vec<_> = stored.iter().map(|t| t.label.as_deref()).collect();
+        assert_eq!(labels, [Some("Politics"), Some("Sports")]);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints() {
+        let store = MemoryStore::new();
+        assert_eq!(store.get_checkpoint("stream").await.unwrap(), None);
+
+        store.set_checkpoint("stream", 10).await.unwrap();
+        store.set_checkpoint("stream", 12).await.unwrap();
+        assert_eq!(store.get_checkpoint("stream").await.unwrap(), Some(12));
+    }
+
+    #[tokio::test]
+    async fn test_market_status() {
+        let store = MemoryStore::new();
+        let condition_id = event(1).condition_id_hex();
+        store.upsert_market(&event(1), None).await.unwrap();
+
+        assert!(store
+            .set_market_status(
+                &condition_id,
+                MarketStatus::Registered,
+                MarketStatus::Closed,
+                StatusSignal::Closed,
+                None,
+            )
+            .await
+            .unwrap());
+        // Stale `from`: another writer already moved the market
+        assert!(!store
+            .set_market_status(
+                &condition_id,
+                MarketStatus::Registered,
+                MarketStatus::Trading,
+                StatusSignal::Trading,
+                None,
+            )
+            .await
+            .unwrap());
+        store
+            .record_anomaly(Some(&condition_id), "test", "reopened", Some(7))
+            .await
+            .unwrap();
+
+        let market = store.get_market(&condition_id).await.unwrap().unwrap();
+        assert_eq!(market.status, "closed");
+        assert!(market.status_updated_at.is_some());
+        let transitions = store.get_status_transitions(&condition_id).await.unwrap();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].to_status, "closed");
+        let anomalies = store.get_anomalies(&condition_id).await.unwrap();
+        assert_eq!(anomalies[0].block_number, Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_registered_at_from_blocks() {
+        let store = MemoryStore::new();
+        let header = testing::block(100);
+
+        // Header recorded after the market
+        store.upsert_market(&event(1), None).await.unwrap();
+        store.upsert_blocks(&[header]).await.unwrap();
+        // ... and before it
+        store.upsert_market(&event(2), None).await.unwrap();
+
+        for id in [1, 2] {
+            let market = store
+                .get_market(&event(id).condition_id_hex())
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(market.registered_at, Some(header.time()));
+        }
+        let block = store.get_block(100).await.unwrap().unwrap();
+        assert_eq!(block.parent_hash, header.parent_hash_hex());
+    }
+
+    #[tokio::test]
+    async fn test_question_lifecycle() {
+        conformance::check_question_lifecycle(&MemoryStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_fills_and_candles() {
+        conformance::check_fills_and_candles(&MemoryStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_market_series() {
+        conformance::check_market_series(&MemoryStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_transfers_and_wallets() {
+        conformance::check_transfers_and_wallets(&MemoryStore::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_wallet_rebuild() {
+        conformance::check_wallet_rebuild(&MemoryStore::new()).await;
+    }
+}
@@ -0,0 +1,239 @@
+// Market Store - storage backends for indexed markets
+//
+// The ingest path and the indexing commands write through `MarketStore`
+// instead of calling `db` directly, so they can run against Postgres or,
+// for tests and dry runs, an in-memory store. With the `sqlite` feature a
+// SQLite file can stand in for Postgres on small deployments.
+
+#[cfg(test)]
+mod conformance;
+pub mod memory;
+pub mod postgres;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+pub use memory::MemoryStore;
+pub use postgres::PgStore;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStore;
+
+use crate::blocks::{BlockHeader, ReplacedBlock};
+use crate::candles::Resolution;
+use crate::config::{DatabaseBackend, DatabaseConfig};
+use crate::db::create_pool;
+use crate::db::models::{
+    Block, Candle, DataAnomaly, Fill as DbFill, Market, MarketDailyStats, MarketSeriesPoint,
+    MarketStatusTransition, OutcomeToken, PositionEvent as DbPositionEvent, Tag as DbTag,
+    TokenTransfer as DbTokenTransfer, UmaQuestion, WalletPosition, WalletStats,
+};
+use crate::polymarket::events::TokenRegistered;
+use crate::polymarket::lifecycle::{MarketStatus, StatusSignal};
+use crate::polymarket::market::{MarketMetadata, Tag as ApiTag};
+use crate::polymarket::positions::PositionEvent;
+use crate::polymarket::trades::Fill;
+use crate::polymarket::transfers::TokenTransfer;
+use crate::polymarket::uma::QuestionEvent;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use std::sync::Arc;
+
+/// Storage operations used while indexing markets
+#[async_trait]
+pub trait MarketStore: Send + Sync {
+    /// Insert or update a market with on-chain data and optional metadata,
+    /// along with its outcome tokens
+    async fn upsert_market(
+        &self,
+        event: &TokenRegistered,
+        metadata: Option<&MarketMetadata>,
+    ) -> Result<()>;
+
+    /// Set Gamma metadata on an existing market
+    async fn update_market_metadata(
+        &self,
+        condition_id: &str,
+        metadata: &MarketMetadata,
+    ) -> Result<()>;
+
+    /// Attach tags to a market, creating tags that don't exist yet
+    async fn insert_market_tags(&self, condition_id: &str, tags: &[ApiTag]) -> Result<()>;
+
+    /// Get a market by condition ID
+    async fn get_market(&self, condition_id: &str) -> Result<Option<Market>>;
+
+    /// Get an outcome token by its ID (decimal string)
+    async fn get_outcome_token(&self, token_id: &str) -> Result<Option<OutcomeToken>>;
+
+    /// Get a market's outcome tokens in outcome order
+    async fn get_outcome_tokens(&self, condition_id: &str) -> Result<Vec<OutcomeToken>>;
+
+    /// Get the tags attached to a market
+    async fn get_tags_for_market(&self, condition_id: &str) -> Result<Vec<DbTag>>;
+
+    /// Get up to `limit` markets without metadata, oldest first
+    async fn get_markets_without_metadata(&self, limit: i64) -> Result<Vec<Market>>;
+
+    /// Count stored markets
+    async fn count_markets(&self) -> Result<i64>;
+
+    /// Get the last indexed block recorded under `name`
+    async fn get_checkpoint(&self, name: &str) -> Result<Option<u64>>;
+
+    /// Record `block_number` as the last indexed block under `name`
+    async fn set_checkpoint(&self, name: &str, block_number: u64) -> Result<()>;
+
+    /// Record a UMA question event and apply it to the question
+    ///
+    /// Returns `false` when the event was already recorded.
+    async fn record_question_event(&self, event: &QuestionEvent) -> Result<bool>;
+
+    /// Get the UMA question behind a market
+    async fn get_question(&self, condition_id: &str) -> Result<Option<UmaQuestion>>;
+
+    /// Move a market from `from` to `to`, recording the transition
+    ///
+    /// Returns `false` when the market is missing or no longer `from`.
+    async fn set_market_status(
+        &self,
+        condition_id: &str,
+        from: MarketStatus,
+        to: MarketStatus,
+        signal: StatusSignal,
+        block_number: Option<u64>,
+    ) -> Result<bool>;
+
+    /// Get a market's status transitions, oldest first
+    async fn get_status_transitions(
+        &self,
+        condition_id: &str,
+    ) -> Result<Vec<MarketStatusTransition>>;
+
+    /// Record indexed data that contradicts what is already stored
+    async fn record_anomaly(
+        &self,
+        condition_id: Option<&str>,
+        kind: &str,
+        detail: &str,
+        block_number: Option<u64>,
+    ) -> Result<()>;
+
+    /// Get the anomalies recorded for a market, oldest first
+    async fn get_anomalies(&self, condition_id: &str) -> Result<Vec<DataAnomaly>>;
+
+    /// Insert or replace block headers, setting `registered_at` on markets
+    /// registered in those blocks
+    ///
+    /// Returns the stored blocks whose hash changed (reorganized blocks).
+    async fn upsert_blocks(&self, headers: &[BlockHeader]) -> Result<Vec<ReplacedBlock>>;
+
+    /// Get a stored block header by number
+    async fn get_block(&self, number: u64) -> Result<Option<Block>>;
+
+    /// Record exchange fills and merge their trades into the candles and
+    /// the daily stats of markets with known outcome tokens
+    ///
+    /// Fills already recorded (a re-indexed range) are skipped and don't
+    /// count twice. Returns the number of new fills.
+    async fn record_fills(&self, fills: &[Fill]) -> Result<usize>;
+
+    /// Block times of the first and last stored fills in a block range
+    async fn get_fill_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>>;
+
+    /// Get the fills with a block time in `[start, end)`, in chain order
+    async fn get_fills_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbFill>>;
+
+    /// Insert candles, overwriting stored ones with the same key
+    async fn replace_candles(&self, candles: &[Candle]) -> Result<()>;
+
+    /// Get a token's candles at one resolution, oldest first
+    async fn get_candles(&self, token_id: &str, resolution: Resolution) -> Result<Vec<Candle>>;
+
+    /// Record position events and merge their collateral flows into the
+    /// markets' daily stats
+    ///
+    /// Events already recorded are skipped. Returns the number of new events.
+    async fn record_position_events(&self, events: &[PositionEvent]) -> Result<usize>;
+
+    /// Block times of the first and last stored position events in a block
+    /// range
+    async fn get_position_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>>;
+
+    /// Get the position events with a block time in `[start, end)`, in chain
+    /// order
+    async fn get_position_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbPositionEvent>>;
+
+    /// Replace every market's stats for the day starting at `day`
+    async fn replace_market_stats(
+        &self,
+        day: DateTime<Utc>,
+        stats: &[MarketDailyStats],
+    ) -> Result<()>;
+
+    /// Get a market's daily series with running open interest, oldest first
+    async fn get_market_series(&self, condition_id: &str) -> Result<Vec<MarketSeriesPoint>>;
+
+    /// Record outcome token transfers
+    ///
+    /// Transfers already recorded are skipped. Returns the number of new rows.
+    async fn record_transfers(&self, transfers: &[TokenTransfer]) -> Result<usize>;
+
+    /// Block times of the first and last stored transfers in a block range
+    async fn get_transfer_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>>;
+
+    /// Get the transfers with a block time in `[start, end)`, in chain order
+    async fn get_transfers_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbTokenTransfer>>;
+
+    /// Replace the stored positions and stats of `wallets`, or of every
+    /// wallet when `None`
+    async fn replace_wallets(
+        &self,
+        wallets: Option<&[String]>,
+        positions: &[WalletPosition],
+        stats: &[WalletStats],
+    ) -> Result<()>;
+
+    /// Get a wallet's positions, by token ID
+    async fn get_wallet_positions(&self, wallet: &str) -> Result<Vec<WalletPosition>>;
+
+    /// Get a wallet's stats
+    async fn get_wallet_stats(&self, wallet: &str) -> Result<Option<WalletStats>>;
+}
+
+/// Open the store configured in the `[database]` section
+///
+/// The backend is chosen by the `database.url` scheme.
+pub async fn open(config: &DatabaseConfig) -> Result<Arc<dyn MarketStore>> {
+    match config.backend()? {
+        DatabaseBackend::Postgres => {
+            let pool = create_pool(config).await?;
+            Ok(Arc::new(PgStore::new(pool)))
+        }
+        #[cfg(feature = "sqlite")]
+        DatabaseBackend::Sqlite => Ok(Arc::new(SqliteStore::connect(config).await?)),
+    }
+}
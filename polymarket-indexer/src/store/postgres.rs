@@ -0,0 +1,250 @@
+// PostgreSQL store - delegates to the `db` query functions
+
+use crate::blocks::{BlockHeader, ReplacedBlock};
+use crate::candles::Resolution;
+use crate::db::models::{
+    Block, Candle, DataAnomaly, Fill as DbFill, Market, MarketDailyStats, MarketSeriesPoint,
+    MarketStatusTransition, OutcomeToken, PositionEvent as DbPositionEvent, Tag as DbTag,
+    TokenTransfer as DbTokenTransfer, UmaQuestion, WalletPosition, WalletStats,
+};
+use crate::db::{
+    anomalies, blocks, candles, checkpoints, fills, market_stats, market_status, market_tags,
+    markets, positions, tokens, transfers, uma_questions, wallets,
+};
+use crate::polymarket::events::TokenRegistered;
+use crate::polymarket::lifecycle::{MarketStatus, StatusSignal};
+use crate::polymarket::market::{MarketMetadata, Tag as ApiTag};
+use crate::polymarket::positions::PositionEvent;
+use crate::polymarket::trades::Fill;
+use crate::polymarket::transfers::TokenTransfer;
+use crate::polymarket::uma::QuestionEvent;
+use crate::store::MarketStore;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use sqlx::PgPool;
+
+/// Market store backed by a PostgreSQL pool
+#[derive(Debug, Clone)]
+pub struct PgStore {
+    pool: PgPool,
+}
+
+impl PgStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Underlying connection pool, for queries outside `MarketStore`
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+#[async_trait]
+impl MarketStore for PgStore {
+    async fn upsert_market(
+        &self,
+        event: &TokenRegistered,
+        metadata: Option<&MarketMetadata>,
+    ) -> Result<()> {
+        Ok(markets::upsert_market(&self.pool, event, metadata).await?)
+    }
+
+    async fn update_market_metadata(
+        &self,
+        condition_id: &str,
+        metadata: &MarketMetadata,
+    ) -> Result<()> {
+        Ok(markets::update_market_metadata(&self.pool, condition_id, metadata).await?)
+    }
+
+    async fn insert_market_tags(&self, condition_id: &str, tags: &[ApiTag]) -> Result<()> {
+        Ok(market_tags::insert_market_tags(&self.pool, condition_id, tags).await?)
+    }
+
+    async fn get_market(&self, condition_id: &str) -> Result<Option<Market>> {
+        Ok(markets::get_market_by_condition_id(&self.pool, condition_id).await?)
+    }
+
+    async fn get_outcome_token(&self, token_id: &str) -> Result<Option<OutcomeToken>> {
+        Ok(tokens::get_by_token_id(&self.pool, token_id).await?)
+    }
+
+    async fn get_outcome_tokens(&self, condition_id: &str) -> Result<Vec<OutcomeToken>> {
+        Ok(tokens::get_tokens_for_market(&self.pool, condition_id).await?)
+    }
+
+    async fn get_tags_for_market(&self, condition_id: &str) -> Result<Vec<DbTag>> {
+        Ok(market_tags::get_tags_for_market(&self.pool, condition_id).await?)
+    }
+
+    async fn get_markets_without_metadata(&self, limit: i64) -> Result<Vec<Market>> {
+        Ok(markets::get_markets_without_metadata(&self.pool, limit).await?)
+    }
+
+    async fn count_markets(&self) -> Result<i64> {
+        Ok(markets::count_markets(&self.pool).await?)
+    }
+
+    async fn get_checkpoint(&self, name: &str) -> Result<Option<u64>> {
+        Ok(checkpoints::get_checkpoint(&self.pool, name).await?)
+    }
+
+    async fn set_checkpoint(&self, name: &str, block_number: u64) -> Result<()> {
+        Ok(checkpoints::set_checkpoint(&self.pool, name, block_number).await?)
+    }
+
+    async fn record_question_event(&self, event: &QuestionEvent) -> Result<bool> {
+        Ok(uma_questions::record_question_event(&self.pool, event).await?)
+    }
+
+    async fn get_question(&self, condition_id: &str) -> Result<Option<UmaQuestion>> {
+        Ok(uma_questions::get_question_by_condition_id(&self.pool, condition_id).await?)
+    }
+
+    async fn set_market_status(
+        &self,
+        condition_id: &str,
+        from: MarketStatus,
+        to: MarketStatus,
+        signal: StatusSignal,
+        block_number: Option<u64>,
+    ) -> Result<bool> {
+        Ok(market_status::set_market_status(
+            &self.pool,
+            condition_id,
+            from,
+            to,
+            signal,
+            block_number,
+        )
+        .await?)
+    }
+
+    async fn get_status_transitions(
+        &self,
+        condition_id: &str,
+    ) -> Result<Vec<MarketStatusTransition>> {
+        Ok(market_status::get_status_transitions(&self.pool, condition_id).await?)
+    }
+
+    async fn record_anomaly(
+        &self,
+        condition_id: Option<&str>,
+        kind: &str,
+        detail: &str,
+        block_number: Option<u64>,
+    ) -> Result<()> {
+        Ok(anomalies::record_anomaly(&self.pool, condition_id, kind, detail, block_number).await?)
+    }
+
+    async fn get_anomalies(&self, condition_id: &str) -> Result<Vec<DataAnomaly>> {
+        Ok(anomalies::get_anomalies_for_market(&self.pool, condition_id).await?)
+    }
+
+    async fn upsert_blocks(&self, headers: &[BlockHeader]) -> Result<Vec<ReplacedBlock>> {
+        Ok(blocks::upsert_blocks(&self.pool, headers).await?)
+    }
+
+    async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        Ok(blocks::get_block(&self.pool, number).await?)
+    }
+
+    async fn record_fills(&self, fills: &[Fill]) -> Result<usize> {
+        Ok(fills::record_fills(&self.pool, fills).await?)
+    }
+
+    async fn get_fill_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        Ok(fills::get_fill_time_range(&self.pool, from_block, to_block).await?)
+    }
+
+    async fn get_fills_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbFill>> {
+        Ok(fills::get_fills_between(&self.pool, start, end).await?)
+    }
+
+    async fn replace_candles(&self, candles: &[Candle]) -> Result<()> {
+        Ok(candles::replace_candles(&self.pool, candles).await?)
+    }
+
+    async fn get_candles(&self, token_id: &str, resolution: Resolution) -> Result<Vec<Candle>> {
+        Ok(candles::get_candles(&self.pool, token_id, resolution, None, None, i64::MAX).await?)
+    }
+
+    async fn record_position_events(&self, events: &[PositionEvent]) -> Result<usize> {
+        Ok(positions::record_position_events(&self.pool, events).await?)
+    }
+
+    async fn get_position_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        Ok(positions::get_position_time_range(&self.pool, from_block, to_block).await?)
+    }
+
+    async fn get_position_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbPositionEvent>> {
+        Ok(positions::get_position_events_between(&self.pool, start, end).await?)
+    }
+
+    async fn replace_market_stats(
+        &self,
+        day: DateTime<Utc>,
+        stats: &[MarketDailyStats],
+    ) -> Result<()> {
+        Ok(market_stats::replace_market_stats(&self.pool, day, stats).await?)
+    }
+
+    async fn get_market_series(&self, condition_id: &str) -> Result<Vec<MarketSeriesPoint>> {
+        Ok(market_stats::get_market_series(&self.pool, condition_id, None, None).await?)
+    }
+
+    async fn record_transfers(&self, transfers: &[TokenTransfer]) -> Result<usize> {
+        Ok(transfers::record_transfers(&self.pool, transfers).await?)
+    }
+
+    async fn get_transfer_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        Ok(transfers::get_transfer_time_range(&self.pool, from_block, to_block).await?)
+    }
+
+    async fn get_transfers_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbTokenTransfer>> {
+        Ok(transfers::get_transfers_between(&self.pool, start, end).await?)
+    }
+
+    async fn replace_wallets(
+        &self,
+        wallets: Option<&[String]>,
+        positions: &[WalletPosition],
+        stats: &[WalletStats],
+    ) -> Result<()> {
+        Ok(wallets::replace_wallets(&self.pool, wallets, positions, stats).await?)
+    }
+
+    async fn get_wallet_positions(&self, wallet: &str) -> Result<Vec<WalletPosition>> {
+        Ok(wallets::get_wallet_positions(&self.pool, wallet).await?)
+    }
+
+    async fn get_wallet_stats(&self, wallet: &str) -> Result<Option<WalletStats>> {
+        Ok(wallets::get_wallet_stats(&self.pool, wallet).await?)
+    }
+}
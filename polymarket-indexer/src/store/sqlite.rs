@@ -0,0 +1,1542 @@
+// SQLite store - single-file alternative to PostgreSQL (`sqlite` feature)
+//
+// Same semantics as the `db` query modules (markets, outcome tokens, tags,
+// UMA questions, market status, anomalies, block headers, fills, candles,
+// position events and daily market stats),
+// using runtime-checked queries so building doesn't need a SQLite database.
+// Outcomes and payouts are stored as JSON text and timestamps are set here
+// since SQLite has no updated_at trigger.
+
+use crate::blocks::{BlockHeader, ReplacedBlock};
+use crate::candles::{self, Resolution, Tick};
+use crate::config::DatabaseConfig;
+use crate::db::models::{
+    Block, Candle, DataAnomaly, Fill as DbFill, Market, MarketDailyStats, MarketSeriesPoint,
+    MarketStatusTransition, OutcomeToken, PositionEvent as DbPositionEvent, Tag as DbTag,
+    TokenTransfer as DbTokenTransfer, UmaQuestion, WalletPosition, WalletStats,
+};
+use crate::market_stats;
+use crate::metrics;
+use crate::polymarket::events::TokenRegistered;
+use crate::polymarket::lifecycle::{MarketStatus, StatusSignal};
+use crate::polymarket::market::{MarketMetadata, Tag as ApiTag};
+use crate::polymarket::positions::PositionEvent;
+use crate::polymarket::trades::Fill;
+use crate::polymarket::transfers::TokenTransfer;
+use crate::polymarket::uma::{QuestionEvent, QuestionEventKind};
+use crate::store::MarketStore;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use eyre::Result;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+};
+use std::str::FromStr;
+use tracing::{info, warn};
+
+const OUTCOME_TOKEN_COLUMNS: &str = "token_id, condition_id, outcome_index, outcome_label, \
+     complement_token_id, created_at, updated_at";
+
+const MARKET_COLUMNS: &str = "condition_id, token0, token1, block_number, tx_hash, \
+     question, slug, pm_market_id, outcomes, start_date, end_date, \
+     created_at, updated_at, metadata_fetched_at, status, status_updated_at, registered_at";
+
+const FILL_COLUMNS: &str = "tx_hash, log_index, exchange, order_hash, maker, taker, \
+     maker_asset_id, taker_asset_id, maker_amount_filled, taker_amount_filled, fee, \
+     token_id, side, price, size, notional, block_number, block_time, created_at";
+
+const POSITION_EVENT_COLUMNS: &str = "tx_hash, log_index, contract, kind, condition_id, \
+     stakeholder, amount, block_number, block_time, created_at";
+
+const MARKET_STATS_COLUMNS: &str = "condition_id, day, split_amount, merge_amount, \
+     redeem_amount, volume, trades";
+
+const TRANSFER_COLUMNS: &str = "tx_hash, log_index, batch_index, operator, from_address, \
+     to_address, token_id, amount, block_number, block_time, created_at";
+
+const WALLET_POSITION_COLUMNS: &str = "wallet, token_id, condition_id, shares, cost_basis, \
+     avg_price, realized_pnl, settled, first_block, last_block";
+
+const WALLET_STATS_COLUMNS: &str = "wallet, markets, open_positions, resolved_markets, \
+     winning_markets, win_rate, realized_pnl, volume, trades, first_block, last_block";
+
+const CANDLE_COLUMNS: &str = "token_id, resolution, bucket_start, open, high, low, close, \
+     volume, notional, trades, first_block, first_log_index, last_block, last_log_index";
+
+/// Market store backed by a SQLite database file
+#[derive(Debug, Clone)]
+pub struct SqliteStore {
+    pool: SqlitePool,
+}
+
+impl SqliteStore {
+    /// Open (creating if needed) the SQLite database at `database.url`
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(config.url()?)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(config.acquire_timeout())
+            .foreign_keys(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(config.acquire_timeout())
+            .connect_with(options)
+            .await?;
+
+        info!("✓ Connected to SQLite database");
+
+        Ok(Self::new(pool))
+    }
+
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Underlying connection pool
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Apply the SQLite migrations bundled with the crate
+    pub async fn migrate(&self) -> Result<()> {
+        sqlx::migrate!("./migrations_sqlite")
+            .run(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Insert the outcome tokens of markets stored before `outcome_tokens`
+    /// existed, as `db::tokens::backfill_outcome_tokens`
+    pub async fn backfill_outcome_tokens(&self) -> Result<usize> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["backfill_outcome_tokens"])
+            .start_timer();
+        let markets: Vec<(String, String, String, i64, String, Option<String>)> = sqlx::query_as(
+            r#"
+                SELECT condition_id, token0, token1, block_number, tx_hash, outcomes
+                FROM markets m
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM outcome_tokens t WHERE t.condition_id = m.condition_id
+                )
+                "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut filled = 0;
+        for (condition_id, token0, token1, block_number, tx_hash, outcomes) in markets {
+            let Some(event) = TokenRegistered::from_stored(
+                &condition_id,
+                &token0,
+                &token1,
+                block_number as u64,
+                &tx_hash,
+            ) else {
+                warn!(
+                    "Skipping outcome tokens of market {}: unparseable IDs",
+                    condition_id
+                );
+                continue;
+            };
+            let outcomes: Option<Vec<String>> =
+                outcomes.and_then(|o| serde_json::from_str(&o).ok());
+            upsert_outcome_tokens(&mut tx, &event, outcomes.as_deref()).await?;
+            filled += 1;
+        }
+        tx.commit().await?;
+
+        Ok(filled)
+    }
+}
+
+fn outcomes_json(metadata: &MarketMetadata) -> Option<String> {
+    serde_json::to_string(&metadata.outcomes).ok()
+}
+
+/// Add rows to the stored market stats, as `db::market_stats::merge_market_stats`
+async fn merge_market_stats(
+    conn: &mut SqliteConnection,
+    stats: &[MarketDailyStats],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    for row in stats {
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO market_daily_stats ({}, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (condition_id, day) DO UPDATE SET
+                split_amount = split_amount + excluded.split_amount,
+                merge_amount = merge_amount + excluded.merge_amount,
+                redeem_amount = redeem_amount + excluded.redeem_amount,
+                volume = volume + excluded.volume,
+                trades = trades + excluded.trades,
+                updated_at = excluded.updated_at
+            "#,
+            MARKET_STATS_COLUMNS
+        ))
+        .bind(&row.condition_id)
+        .bind(row.day)
+        .bind(row.split_amount)
+        .bind(row.merge_amount)
+        .bind(row.redeem_amount)
+        .bind(row.volume)
+        .bind(row.trades)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Add the volume of daily candles to their markets' stats, as
+/// `db::market_stats::merge_candle_volume`
+async fn merge_candle_volume(
+    conn: &mut SqliteConnection,
+    candles: &[Candle],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let mut stats = Vec::new();
+    for candle in candles {
+        if candle.resolution != Resolution::Day.as_str() {
+            continue;
+        }
+        let condition_id: Option<String> =
+            sqlx::query_scalar("SELECT condition_id FROM outcome_tokens WHERE token_id = ?1")
+                .bind(&candle.token_id)
+                .fetch_optional(&mut *conn)
+                .await?;
+        if let Some(condition_id) = condition_id {
+            stats.push(MarketDailyStats::from_candle(condition_id, candle));
+        }
+    }
+
+    merge_market_stats(conn, &stats, now).await
+}
+
+/// Write a candle, overwriting any stored one with the same key
+async fn write_candle(
+    conn: &mut SqliteConnection,
+    candle: &Candle,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(&format!(
+        r#"
+        INSERT OR REPLACE INTO candles ({}, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
+        "#,
+        CANDLE_COLUMNS
+    ))
+    .bind(&candle.token_id)
+    .bind(&candle.resolution)
+    .bind(candle.bucket_start)
+    .bind(candle.open)
+    .bind(candle.high)
+    .bind(candle.low)
+    .bind(candle.close)
+    .bind(candle.volume)
+    .bind(candle.notional)
+    .bind(candle.trades)
+    .bind(candle.first_block)
+    .bind(candle.first_log_index)
+    .bind(candle.last_block)
+    .bind(candle.last_log_index)
+    .bind(now)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Insert a market's outcome tokens, as `db::tokens::upsert_outcome_tokens`
+async fn upsert_outcome_tokens(
+    conn: &mut SqliteConnection,
+    event: &TokenRegistered,
+    outcomes: Option<&[String]>,
+) -> Result<()> {
+    let token_ids = event.outcome_token_ids();
+    let now = Utc::now();
+
+    for (index, token_id) in token_ids.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO outcome_tokens (
+                token_id, condition_id, outcome_index, outcome_label, complement_token_id,
+                created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+            ON CONFLICT (token_id) DO UPDATE SET
+                outcome_label = COALESCE(excluded.outcome_label, outcome_tokens.outcome_label),
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(token_id.to_string())
+        .bind(event.condition_id_hex())
+        .bind(index as i64)
+        .bind(outcomes.and_then(|o| o.get(index)).map(String::as_str))
+        .bind(token_ids[1 - index].to_string())
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+#[async_trait]
+impl MarketStore for SqliteStore {
+    async fn upsert_market(
+        &self,
+        event: &TokenRegistered,
+        metadata: Option<&MarketMetadata>,
+    ) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["upsert_market"])
+            .start_timer();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO markets (
+                condition_id, token0, token1, block_number, tx_hash,
+                question, slug, pm_market_id, outcomes, start_date, end_date,
+                created_at, updated_at, metadata_fetched_at, registered_at
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?12, ?13,
+                (SELECT timestamp FROM blocks WHERE number = ?4)
+            )
+            ON CONFLICT (condition_id) DO UPDATE SET
+                question = COALESCE(excluded.question, markets.question),
+                slug = COALESCE(excluded.slug, markets.slug),
+                pm_market_id = COALESCE(excluded.pm_market_id, markets.pm_market_id),
+                outcomes = COALESCE(excluded.outcomes, markets.outcomes),
+                start_date = COALESCE(excluded.start_date, markets.start_date),
+                end_date = COALESCE(excluded.end_date, markets.end_date),
+                metadata_fetched_at = COALESCE(excluded.metadata_fetched_at, markets.metadata_fetched_at),
+                registered_at = COALESCE(markets.registered_at, excluded.registered_at),
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(event.condition_id_hex())
+        .bind(event.token0.to_string())
+        .bind(event.token1.to_string())
+        .bind(event.block_number as i64)
+        .bind(&event.tx_hash)
+        .bind(metadata.map(|m| m.question.as_str()))
+        .bind(metadata.map(|m| m.slug.as_str()))
+        .bind(metadata.and_then(|m| m.id.as_deref()))
+        .bind(metadata.and_then(outcomes_json))
+        .bind(metadata.and_then(|m| m.start_date.as_deref()))
+        .bind(metadata.and_then(|m| m.end_date.as_deref()))
+        .bind(now)
+        .bind(metadata.map(|_| now))
+        .execute(&mut *tx)
+        .await?;
+
+        upsert_outcome_tokens(&mut tx, event, metadata.map(|m| m.outcomes.as_slice())).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn update_market_metadata(
+        &self,
+        condition_id: &str,
+        metadata: &MarketMetadata,
+    ) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["update_market_metadata"])
+            .start_timer();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE markets SET
+                question = ?2,
+                slug = ?3,
+                pm_market_id = COALESCE(?4, pm_market_id),
+                outcomes = COALESCE(?5, outcomes),
+                start_date = COALESCE(?6, start_date),
+                end_date = COALESCE(?7, end_date),
+                metadata_fetched_at = ?8,
+                updated_at = ?8
+            WHERE condition_id = ?1
+            "#,
+        )
+        .bind(condition_id)
+        .bind(&metadata.question)
+        .bind(&metadata.slug)
+        .bind(metadata.id.as_deref())
+        .bind(outcomes_json(metadata))
+        .bind(metadata.start_date.as_deref())
+        .bind(metadata.end_date.as_deref())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        for (index, label) in metadata.outcomes.iter().enumerate() {
+            sqlx::query(
+                r#"
+                UPDATE outcome_tokens SET outcome_label = ?3, updated_at = ?4
+                WHERE condition_id = ?1 AND outcome_index = ?2
+                "#,
+            )
+            .bind(condition_id)
+            .bind(index as i64)
+            .bind(label)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn insert_market_tags(&self, condition_id: &str, tags: &[ApiTag]) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["insert_market_tags"])
+            .start_timer();
+        let mut tx = self.pool.begin().await?;
+
+        for tag in tags {
+            sqlx::query(
+                r#"
+                INSERT INTO tags (pm_tag_id, label, slug)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT (pm_tag_id) DO UPDATE SET
+                    label = COALESCE(excluded.label, tags.label),
+                    slug = COALESCE(excluded.slug, tags.slug)
+                "#,
+            )
+            .bind(&tag.id)
+            .bind(tag.label.as_deref())
+            .bind(tag.slug.as_deref())
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO market_tags (condition_id, pm_tag_id)
+                VALUES (?1, ?2)
+                ON CONFLICT (condition_id, pm_tag_id) DO NOTHING
+                "#,
+            )
+            .bind(condition_id)
+            .bind(&tag.id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // Bump the market so incremental exports pick up tag changes
+        if !tags.is_empty() {
+            sqlx::query("UPDATE markets SET updated_at = ?2 WHERE condition_id = ?1")
+                .bind(condition_id)
+                .bind(Utc::now())
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn get_market(&self, condition_id: &str) -> Result<Option<Market>> {
+        let market = sqlx::query_as::<_, Market>(&format!(
+            "SELECT {} FROM markets WHERE condition_id = ?1",
+            MARKET_COLUMNS
+        ))
+        .bind(condition_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(market)
+    }
+
+    async fn get_outcome_token(&self, token_id: &str) -> Result<Option<OutcomeToken>> {
+        let token = sqlx::query_as::<_, OutcomeToken>(&format!(
+            "SELECT {} FROM outcome_tokens WHERE token_id = ?1",
+            OUTCOME_TOKEN_COLUMNS
+        ))
+        .bind(token_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn get_outcome_tokens(&self, condition_id: &str) -> Result<Vec<OutcomeToken>> {
+        let tokens = sqlx::query_as::<_, OutcomeToken>(&format!(
+            "SELECT {} FROM outcome_tokens WHERE condition_id = ?1 ORDER BY outcome_index ASC",
+            OUTCOME_TOKEN_COLUMNS
+        ))
+        .bind(condition_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tokens)
+    }
+
+    async fn get_tags_for_market(&self, condition_id: &str) -> Result<Vec<DbTag>> {
+        // Sort NULL labels last, as PostgreSQL does
+        let tags = sqlx::query_as::<_, DbTag>(
+            r#"
+            SELECT t.pm_tag_id, t.label, t.slug
+            FROM market_tags mt
+            JOIN tags t ON mt.pm_tag_id = t.pm_tag_id
+            WHERE mt.condition_id = ?1
+            ORDER BY t.label IS NULL, t.label
+            "#,
+        )
+        .bind(condition_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tags)
+    }
+
+    async fn get_markets_without_metadata(&self, limit: i64) -> Result<Vec<Market>> {
+        let markets = sqlx::query_as::<_, Market>(&format!(
+            "SELECT {} FROM markets WHERE metadata_fetched_at IS NULL \
+             ORDER BY created_at ASC LIMIT ?1",
+            MARKET_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(markets)
+    }
+
+    async fn count_markets(&self) -> Result<i64> {
+        let count = sqlx::query_scalar("SELECT COUNT(*) FROM markets")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn get_checkpoint(&self, name: &str) -> Result<Option<u64>> {
+        let block: Option<i64> =
+            sqlx::query_scalar("SELECT block_number FROM checkpoints WHERE name = ?1")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(block.map(|b| b as u64))
+    }
+
+    async fn set_checkpoint(&self, name: &str, block_number: u64) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["set_checkpoint"])
+            .start_timer();
+
+        sqlx::query(
+            r#"
+            INSERT INTO checkpoints (name, block_number, updated_at)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (name) DO UPDATE SET
+                block_number = excluded.block_number,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(name)
+        .bind(block_number as i64)
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_question_event(&self, event: &QuestionEvent) -> Result<bool> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["record_question_event"])
+            .start_timer();
+        let now = Utc::now();
+        let (settled_price, payouts) = event.resolution().unzip();
+        let payouts_json = payouts.and_then(|p| serde_json::to_string(&p).ok());
+        let initialization = event.initialization();
+        let mut tx = self.pool.begin().await?;
+
+        let recorded = sqlx::query(
+            r#"
+            INSERT INTO uma_question_events (
+                tx_hash, log_index, question_id, event, block_number,
+                settled_price, payouts, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (tx_hash, log_index) DO NOTHING
+            "#,
+        )
+        .bind(&event.tx_hash)
+        .bind(event.log_index as i64)
+        .bind(event.question_id_hex())
+        .bind(event.kind.name())
+        .bind(event.block_number as i64)
+        .bind(settled_price.as_deref())
+        .bind(payouts_json.as_deref())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if !recorded {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO uma_questions (
+                question_id, condition_id, adapter, creator, request_timestamp,
+                ancillary_data, title, description, resolution_data,
+                status, paused, reset_count, settled_price, payouts,
+                initialized_block, resolved_block, created_at, updated_at
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9,
+                COALESCE(?10, 'initialized'), COALESCE(?11, 0), ?12, ?13, ?14, ?15, ?16, ?17, ?17
+            )
+            ON CONFLICT (question_id) DO UPDATE SET
+                creator = COALESCE(excluded.creator, uma_questions.creator),
+                request_timestamp = COALESCE(excluded.request_timestamp, uma_questions.request_timestamp),
+                ancillary_data = COALESCE(excluded.ancillary_data, uma_questions.ancillary_data),
+                title = COALESCE(excluded.title, uma_questions.title),
+                description = COALESCE(excluded.description, uma_questions.description),
+                resolution_data = COALESCE(excluded.resolution_data, uma_questions.resolution_data),
+                status = COALESCE(?10, uma_questions.status),
+                paused = COALESCE(?11, uma_questions.paused),
+                reset_count = uma_questions.reset_count + excluded.reset_count,
+                settled_price = COALESCE(excluded.settled_price, uma_questions.settled_price),
+                payouts = COALESCE(excluded.payouts, uma_questions.payouts),
+                initialized_block = COALESCE(excluded.initialized_block, uma_questions.initialized_block),
+                resolved_block = COALESCE(excluded.resolved_block, uma_questions.resolved_block),
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(event.question_id_hex())
+        .bind(event.condition_id_hex())
+        .bind(format!("{:?}", event.adapter))
+        .bind(initialization.map(|(creator, _, _)| format!("{:?}", creator)))
+        .bind(initialization.map(|(_, timestamp, _)| timestamp as i64))
+        .bind(initialization.map(|(_, _, data)| data.raw.as_str()))
+        .bind(initialization.and_then(|(_, _, data)| data.title.as_deref()))
+        .bind(initialization.and_then(|(_, _, data)| data.description.as_deref()))
+        .bind(initialization.and_then(|(_, _, data)| data.resolution_data.as_deref()))
+        .bind(event.kind.status())
+        .bind(event.kind.paused())
+        .bind(i32::from(event.kind == QuestionEventKind::Reset))
+        .bind(settled_price.as_deref())
+        .bind(payouts_json.as_deref())
+        .bind(initialization.map(|_| event.block_number as i64))
+        .bind(settled_price.as_ref().map(|_| event.block_number as i64))
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn get_question(&self, condition_id: &str) -> Result<Option<UmaQuestion>> {
+        let question = sqlx::query_as::<_, UmaQuestion>(
+            r#"
+            SELECT question_id, condition_id, adapter, creator, request_timestamp,
+                   ancillary_data, title, description, resolution_data,
+                   status, paused, reset_count, settled_price, payouts,
+                   initialized_block, resolved_block, created_at, updated_at
+            FROM uma_questions
+            WHERE condition_id = ?1
+            ORDER BY updated_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(condition_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(question)
+    }
+
+    async fn set_market_status(
+        &self,
+        condition_id: &str,
+        from: MarketStatus,
+        to: MarketStatus,
+        signal: StatusSignal,
+        block_number: Option<u64>,
+    ) -> Result<bool> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["set_market_status"])
+            .start_timer();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE markets SET status = ?3, status_updated_at = ?4, updated_at = ?4
+            WHERE condition_id = ?1 AND status = ?2
+            "#,
+        )
+        .bind(condition_id)
+        .bind(from.as_str())
+        .bind(to.as_str())
+        .bind(now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+        if !updated {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO market_status_transitions (
+                condition_id, from_status, to_status, source, block_number, transitioned_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(condition_id)
+        .bind(from.as_str())
+        .bind(to.as_str())
+        .bind(signal.source())
+        .bind(block_number.map(|b| b as i64))
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn get_status_transitions(
+        &self,
+        condition_id: &str,
+    ) -> Result<Vec<MarketStatusTransition>> {
+        let transitions = sqlx::query_as::<_, MarketStatusTransition>(
+            r#"
+            SELECT condition_id, from_status, to_status, source, block_number, transitioned_at
+            FROM market_status_transitions
+            WHERE condition_id = ?1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(condition_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(transitions)
+    }
+
+    async fn record_anomaly(
+        &self,
+        condition_id: Option<&str>,
+        kind: &str,
+        detail: &str,
+        block_number: Option<u64>,
+    ) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["record_anomaly"])
+            .start_timer();
+
+        sqlx::query(
+            r#"
+            INSERT INTO data_anomalies (condition_id, kind, detail, block_number, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )
+        .bind(condition_id)
+        .bind(kind)
+        .bind(detail)
+        .bind(block_number.map(|b| b as i64))
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_anomalies(&self, condition_id: &str) -> Result<Vec<DataAnomaly>> {
+        let anomalies = sqlx::query_as::<_, DataAnomaly>(
+            r#"
+            SELECT id, condition_id, kind, detail, block_number, created_at
+            FROM data_anomalies
+            WHERE condition_id = ?1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(condition_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(anomalies)
+    }
+
+    async fn upsert_blocks(&self, headers: &[BlockHeader]) -> Result<Vec<ReplacedBlock>> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["upsert_blocks"])
+            .start_timer();
+        let now = Utc::now();
+        let mut replaced = Vec::new();
+        let mut tx = self.pool.begin().await?;
+
+        for header in headers {
+            let hash = header.hash_hex();
+            let previous: Option<String> =
+                sqlx::query_scalar("SELECT hash FROM blocks WHERE number = ?1")
+                    .bind(header.number as i64)
+                    .fetch_optional(&mut *tx)
+                    .await?;
+            if let Some(previous) = previous.filter(|previous| *previous != hash) {
+                replaced.push(ReplacedBlock {
+                    number: header.number,
+                    hash: previous,
+                    new_hash: hash.clone(),
+                });
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO blocks (number, hash, parent_hash, timestamp, created_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)
+                ON CONFLICT (number) DO UPDATE SET
+                    hash = excluded.hash,
+                    parent_hash = excluded.parent_hash,
+                    timestamp = excluded.timestamp
+                "#,
+            )
+            .bind(header.number as i64)
+            .bind(&hash)
+            .bind(header.parent_hash_hex())
+            .bind(header.time())
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE markets SET registered_at = ?2, updated_at = ?3
+                WHERE block_number = ?1 AND registered_at IS NOT ?2
+                "#,
+            )
+            .bind(header.number as i64)
+            .bind(header.time())
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(replaced)
+    }
+
+    async fn get_block(&self, number: u64) -> Result<Option<Block>> {
+        let block = sqlx::query_as::<_, Block>(
+            r#"
+            SELECT number, hash, parent_hash, timestamp, created_at
+            FROM blocks
+            WHERE number = ?1
+            "#,
+        )
+        .bind(number as i64)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(block)
+    }
+
+    async fn record_fills(&self, fills: &[Fill]) -> Result<usize> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["record_fills"])
+            .start_timer();
+        let now = Utc::now();
+        let mut ticks = Vec::new();
+        let mut inserted = 0;
+        let mut tx = self.pool.begin().await?;
+
+        for fill in fills {
+            let trade = fill.trade();
+            let result = sqlx::query(&format!(
+                r#"
+                INSERT INTO fills ({})
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                        ?17, ?18, ?19)
+                ON CONFLICT (tx_hash, log_index) DO NOTHING
+                "#,
+                FILL_COLUMNS
+            ))
+            .bind(&fill.tx_hash)
+            .bind(fill.log_index as i64)
+            .bind(format!("{:?}", fill.exchange))
+            .bind(fill.order_hash_hex())
+            .bind(format!("{:?}", fill.maker))
+            .bind(format!("{:?}", fill.taker))
+            .bind(fill.maker_asset_id.to_string())
+            .bind(fill.taker_asset_id.to_string())
+            .bind(fill.maker_amount_filled.to_string())
+            .bind(fill.taker_amount_filled.to_string())
+            .bind(fill.fee.to_string())
+            .bind(trade.map(|t| t.token_id.to_string()))
+            .bind(trade.map(|t| t.side.as_str()))
+            .bind(trade.map(|t| t.price))
+            .bind(trade.map(|t| t.size))
+            .bind(trade.map(|t| t.notional))
+            .bind(fill.block_number as i64)
+            .bind(fill.block_time)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() > 0 {
+                inserted += 1;
+                ticks.extend(Tick::from_fill(fill));
+            }
+        }
+
+        let candles = candles::aggregate(&ticks);
+        merge_candle_volume(&mut tx, &candles, now).await?;
+
+        // SQLite has no GREATEST/LEAST upsert shortcut worth the SQL; merge
+        // here as the memory store does
+        for mut candle in candles {
+            let stored = sqlx::query_as::<_, Candle>(&format!(
+                r#"
+                SELECT {} FROM candles
+                WHERE token_id = ?1 AND resolution = ?2 AND bucket_start = ?3
+                "#,
+                CANDLE_COLUMNS
+            ))
+            .bind(&candle.token_id)
+            .bind(&candle.resolution)
+            .bind(candle.bucket_start)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if let Some(mut stored) = stored {
+                stored.merge(&candle);
+                candle = stored;
+            }
+            write_candle(&mut tx, &candle, now).await?;
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn get_fill_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let (first, last): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT MIN(block_time), MAX(block_time)
+            FROM fills
+            WHERE block_number BETWEEN ?1 AND ?2
+            "#,
+        )
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(first.zip(last))
+    }
+
+    async fn get_fills_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbFill>> {
+        let fills = sqlx::query_as::<_, DbFill>(&format!(
+            r#"
+            SELECT {} FROM fills
+            WHERE block_time >= ?1 AND block_time < ?2
+            ORDER BY block_number ASC, log_index ASC
+            "#,
+            FILL_COLUMNS
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(fills)
+    }
+
+    async fn replace_candles(&self, candles: &[Candle]) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["replace_candles"])
+            .start_timer();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        for candle in candles {
+            write_candle(&mut tx, candle, now).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_candles(&self, token_id: &str, resolution: Resolution) -> Result<Vec<Candle>> {
+        let candles = sqlx::query_as::<_, Candle>(&format!(
+            r#"
+            SELECT {} FROM candles
+            WHERE token_id = ?1 AND resolution = ?2
+            ORDER BY bucket_start ASC
+            "#,
+            CANDLE_COLUMNS
+        ))
+        .bind(token_id)
+        .bind(resolution.as_str())
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(candles)
+    }
+
+    async fn record_position_events(&self, events: &[PositionEvent]) -> Result<usize> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["record_position_events"])
+            .start_timer();
+        let now = Utc::now();
+        let mut rows = Vec::new();
+        let mut inserted = 0;
+        let mut tx = self.pool.begin().await?;
+
+        for event in events {
+            let result = sqlx::query(&format!(
+                r#"
+                INSERT INTO position_events ({})
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                ON CONFLICT (tx_hash, log_index) DO NOTHING
+                "#,
+                POSITION_EVENT_COLUMNS
+            ))
+            .bind(&event.tx_hash)
+            .bind(event.log_index as i64)
+            .bind(format!("{:?}", event.contract))
+            .bind(event.kind.as_str())
+            .bind(event.condition_id_hex())
+            .bind(format!("{:?}", event.stakeholder))
+            .bind(event.amount.to_string())
+            .bind(event.block_number as i64)
+            .bind(event.block_time)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+            if result.rows_affected() > 0 {
+                inserted += 1;
+                match MarketDailyStats::from_position(event) {
+                    Some(row) => rows.push(row),
+                    None => warn!(
+                        "Position event {}:{} amount {} too large for market stats",
+                        event.tx_hash, event.log_index, event.amount
+                    ),
+                }
+            }
+        }
+
+        merge_market_stats(&mut tx, &market_stats::aggregate(rows), now).await?;
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn get_position_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let (first, last): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT MIN(block_time), MAX(block_time)
+            FROM position_events
+            WHERE block_number BETWEEN ?1 AND ?2
+            "#,
+        )
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(first.zip(last))
+    }
+
+    async fn get_position_events_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbPositionEvent>> {
+        let events = sqlx::query_as::<_, DbPositionEvent>(&format!(
+            r#"
+            SELECT {} FROM position_events
+            WHERE block_time >= ?1 AND block_time < ?2
+            ORDER BY block_number ASC, log_index ASC
+            "#,
+            POSITION_EVENT_COLUMNS
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    async fn replace_market_stats(
+        &self,
+        day: DateTime<Utc>,
+        stats: &[MarketDailyStats],
+    ) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["replace_market_stats"])
+            .start_timer();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM market_daily_stats WHERE day = ?1")
+            .bind(day)
+            .execute(&mut *tx)
+            .await?;
+        merge_market_stats(&mut tx, stats, Utc::now()).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_market_series(&self, condition_id: &str) -> Result<Vec<MarketSeriesPoint>> {
+        let rows = sqlx::query_as::<_, MarketDailyStats>(&format!(
+            r#"
+            SELECT {} FROM market_daily_stats
+            WHERE condition_id = ?1
+            ORDER BY day ASC
+            "#,
+            MARKET_STATS_COLUMNS
+        ))
+        .bind(condition_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(market_stats::series(&rows))
+    }
+
+    async fn record_transfers(&self, transfers: &[TokenTransfer]) -> Result<usize> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["record_transfers"])
+            .start_timer();
+        let now = Utc::now();
+        let mut inserted = 0;
+        let mut tx = self.pool.begin().await?;
+
+        for transfer in transfers {
+            let result = sqlx::query(&format!(
+                r#"
+                INSERT INTO token_transfers ({})
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                ON CONFLICT (tx_hash, log_index, batch_index) DO NOTHING
+                "#,
+                TRANSFER_COLUMNS
+            ))
+            .bind(&transfer.tx_hash)
+            .bind(transfer.log_index as i64)
+            .bind(transfer.batch_index as i32)
+            .bind(format!("{:?}", transfer.operator))
+            .bind(format!("{:?}", transfer.from))
+            .bind(format!("{:?}", transfer.to))
+            .bind(transfer.token_id.to_string())
+            .bind(transfer.amount.to_string())
+            .bind(transfer.block_number as i64)
+            .bind(transfer.block_time)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            inserted += result.rows_affected() as usize;
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn get_transfer_time_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Option<(DateTime<Utc>, DateTime<Utc>)>> {
+        let (first, last): (Option<DateTime<Utc>>, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT MIN(block_time), MAX(block_time)
+            FROM token_transfers
+            WHERE block_number BETWEEN ?1 AND ?2
+            "#,
+        )
+        .bind(from_block as i64)
+        .bind(to_block as i64)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(first.zip(last))
+    }
+
+    async fn get_transfers_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<DbTokenTransfer>> {
+        let transfers = sqlx::query_as::<_, DbTokenTransfer>(&format!(
+            r#"
+            SELECT {} FROM token_transfers
+            WHERE block_time >= ?1 AND block_time < ?2
+            ORDER BY block_number ASC, log_index ASC, batch_index ASC
+            "#,
+            TRANSFER_COLUMNS
+        ))
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(transfers)
+    }
+
+    async fn replace_wallets(
+        &self,
+        wallets: Option<&[String]>,
+        positions: &[WalletPosition],
+        stats: &[WalletStats],
+    ) -> Result<()> {
+        let _timer = metrics::DB_WRITE_LATENCY
+            .with_label_values(&["replace_wallets"])
+            .start_timer();
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        match wallets {
+            None => {
+                sqlx::query("DELETE FROM wallet_positions")
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("DELETE FROM wallet_stats")
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            Some(wallets) => {
+                for wallet in wallets {
+                    sqlx::query("DELETE FROM wallet_positions WHERE wallet = ?1")
+                        .bind(wallet)
+                        .execute(&mut *tx)
+                        .await?;
+                    sqlx::query("DELETE FROM wallet_stats WHERE wallet = ?1")
+                        .bind(wallet)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+
+        for position in positions {
+            sqlx::query(&format!(
+                r#"
+                INSERT INTO wallet_positions ({}, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                "#,
+                WALLET_POSITION_COLUMNS
+            ))
+            .bind(&position.wallet)
+            .bind(&position.token_id)
+            .bind(&position.condition_id)
+            .bind(position.shares)
+            .bind(position.cost_basis)
+            .bind(position.avg_price)
+            .bind(position.realized_pnl)
+            .bind(position.settled)
+            .bind(position.first_block)
+            .bind(position.last_block)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for row in stats {
+            sqlx::query(&format!(
+                r#"
+                INSERT INTO wallet_stats ({}, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                "#,
+                WALLET_STATS_COLUMNS
+            ))
+            .bind(&row.wallet)
+            .bind(row.markets)
+            .bind(row.open_positions)
+            .bind(row.resolved_markets)
+            .bind(row.winning_markets)
+            .bind(row.win_rate)
+            .bind(row.realized_pnl)
+            .bind(row.volume)
+            .bind(row.trades)
+            .bind(row.first_block)
+            .bind(row.last_block)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_wallet_positions(&self, wallet: &str) -> Result<Vec<WalletPosition>> {
+        // Token IDs are decimal text: order by length first to sort numerically
+        let positions = sqlx::query_as::<_, WalletPosition>(&format!(
+            r#"
+            SELECT {} FROM wallet_positions
+            WHERE wallet = ?1
+            ORDER BY LENGTH(token_id) ASC, token_id ASC
+            "#,
+            WALLET_POSITION_COLUMNS
+        ))
+        .bind(wallet)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(positions)
+    }
+
+    async fn get_wallet_stats(&self, wallet: &str) -> Result<Option<WalletStats>> {
+        let stats = sqlx::query_as::<_, WalletStats>(&format!(
+            "SELECT {} FROM wallet_stats WHERE wallet = ?1",
+            WALLET_STATS_COLUMNS
+        ))
+        .bind(wallet)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::conformance;
+    use crate::testing;
+    use ethers::types::U256;
+
+    /// In-memory databases are per connection, so keep a single one
+    async fn store() -> SqliteStore {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = SqliteStore::new(pool);
+        store.migrate().await.unwrap();
+        store
+    }
+
+    fn event() -> TokenRegistered {
+        TokenRegistered {
+            token0: U256::from(1),
+            token1: U256::from(2),
+            condition_id: [7; 32],
+            block_number: 100,
+            tx_hash: "0xab".to_string(),
+            transaction_index: 0,
+            log_index: 0,
+        }
+    }
+
+    fn metadata() -> MarketMetadata {
+        MarketMetadata {
+            id: Some("42".to_string()),
+            question: "Will it rain?".to_string(),
+            slug: "will-it-rain".to_string(),
+            condition_id: String::new(),
+            outcomes: vec!["Yes".to_string(), "No".to_string()],
+            start_date: None,
+            end_date: None,
+            active: Some(true),
+            closed: Some(false),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_market_round_trip() {
+        let store = store().await;
+        let event = event();
+        let condition_id = event.condition_id_hex();
+
+        store.upsert_market(&event, None).await.unwrap();
+        assert_eq!(
+            store.get_markets_without_metadata(10).await.unwrap().len(),
+            1
+        );
+
+        store
+            .update_market_metadata(&condition_id, &metadata())
+            .await
+            .unwrap();
+        store.upsert_market(&event, None).await.unwrap();
+
+        let market = store.get_market(&condition_id).await.unwrap().unwrap();
+        assert_eq!(market.question.as_deref(), Some("Will it rain?"));
+        assert_eq!(market.outcomes, Some(serde_json::json!(["Yes", "No"])));
+        let token = store.get_outcome_token("2").await.unwrap().unwrap();
+        assert_eq!(token.outcome_index, 1);
+        assert_eq!(token.outcome_label.as_deref(), Some("No"));
+        assert_eq!(token.complement_token_id, "1");
+        assert!(market.metadata_fetched_at.is_some());
+        assert_eq!(market.registered_at, None);
+        assert_eq!(store.count_markets().await.unwrap(), 1);
+
+        let header = testing::block(100);
+        assert!(store.upsert_blocks(&[header]).await.unwrap().is_empty());
+        let moved = BlockHeader {
+            hash: ethers::types::H256::repeat_byte(3),
+            ..header
+        };
+        assert_eq!(store.upsert_blocks(&[moved]).await.unwrap().len(), 1);
+        let market = store.get_market(&condition_id).await.unwrap().unwrap();
+        assert_eq!(market.registered_at, Some(header.time()));
+        let block = store.get_block(100).await.unwrap().unwrap();
+        assert_eq!(block.hash, moved.hash_hex());
+        assert!(store
+            .get_markets_without_metadata(10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backfill_outcome_tokens() {
+        let store = store().await;
+        // "Presidential Election Winner 2024" - Donald Trump, outcome 1 first
+        let event = TokenRegistered {
+            token0: U256::from_dec_str(
+                "48331043336612883890938759509493159234755048973500640148014422747788308965732",
+            )
+            .unwrap(),
+            token1: U256::from_dec_str(
+                "21742633143463906290569050155826241533067272736897614950488156847949938836455",
+            )
+            .unwrap(),
+            condition_id: hex::decode(
+                "dd22472e552920b8438158ea7238bfadfa4f736aa4cee91a6b86c39ead110917",
+            )
+            .unwrap()
+            .try_into()
+            .unwrap(),
+            ..event()
+        };
+        let condition_id = event.condition_id_hex();
+        store
+            .upsert_market(&event, Some(&metadata()))
+            .await
+            .unwrap();
+        // A market stored before the table existed
+        sqlx::query("DELETE FROM outcome_tokens")
+            .execute(store.pool())
+            .await
+            .unwrap();
+
+        assert_eq!(store.backfill_outcome_tokens().await.unwrap(), 1);
+        assert_eq!(store.backfill_outcome_tokens().await.unwrap(), 0);
+        let tokens = store.get_outcome_tokens(&condition_id).await.unwrap();
+        assert_eq!(tokens[0].token_id, event.token1.to_string());
+        assert_eq!(tokens[0].outcome_label.as_deref(), Some("Yes"));
+        assert_eq!(tokens[1].complement_token_id, event.token1.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_tags_and_checkpoints() {
+        let store = store().await;
+        let condition_id = event().condition_id_hex();
+        store.upsert_market(&event(), None).await.unwrap();
+
+        let tag = |id: &str, label: Option<&str>| ApiTag {
+            id: id.to_string(),
+            label: label.map(str::to_string),
+            slug: None,
+        };
+        store
+            .insert_market_tags(&condition_id, &[tag("1", Some("Politics")), tag("2", None)])
+            .await
+            .unwrap();
+        store
+            .insert_market_tags(&condition_id, &[tag("1", None)])
+            .await
+            .unwrap();
+
+        let tags = store.get_tags_for_market(&condition_id).await.unwrap();
+        let labels: Vec<_> = tags.iter().map(|t| t.label.as_deref()).collect();
+        assert_eq!(labels, [Some("Politics"), None]);
+
+        store.set_checkpoint("stream", 10).await.unwrap();
+        store.set_checkpoint("stream", 12).await.unwrap();
+        assert_eq!(store.get_checkpoint("stream").await.unwrap(), Some(12));
+    }
+
+    #[tokio::test]
+    async fn test_market_status() {
+        let store = store().await;
+        let condition_id = event().condition_id_hex();
+        store.upsert_market(&event(), None).await.unwrap();
+
+        assert!(store
+            .set_market_status(
+                &condition_id,
+                MarketStatus::Registered,
+                MarketStatus::Closed,
+                StatusSignal::Closed,
+                None,
+            )
+            .await
+            .unwrap());
+        // Stale `from`: another writer already moved the market
+        assert!(!store
+            .set_market_status(
+                &condition_id,
+                MarketStatus::Registered,
+                MarketStatus::Trading,
+                StatusSignal::Trading,
+                None,
+            )
+            .await
+            .unwrap());
+        store
+            .record_anomaly(Some(&condition_id), "test", "reopened", Some(7))
+            .await
+            .unwrap();
+
+        let market = store.get_market(&condition_id).await.unwrap().unwrap();
+        assert_eq!(market.status, "closed");
+        assert!(market.status_updated_at.is_some());
+        let transitions = store.get_status_transitions(&condition_id).await.unwrap();
+        assert_eq!(transitions.len(), 1);
+        assert_eq!(transitions[0].to_status, "closed");
+        let anomalies = store.get_anomalies(&condition_id).await.unwrap();
+        assert_eq!(anomalies[0].block_number, Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_question_lifecycle() {
+        conformance::check_question_lifecycle(&store().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_fills_and_candles() {
+        conformance::check_fills_and_candles(&store().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_market_series() {
+        conformance::check_market_series(&store().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_transfers_and_wallets() {
+        conformance::check_transfers_and_wallets(&store().await).await;
+    }
+
+    #[tokio::test]
+    async fn test_wallet_rebuild() {
+        conformance::check_wallet_rebuild(&store().await).await;
+    }
+}
@@ -0,0 +1,81 @@
+// Test fixtures shared across modules
+//
+// Handler tests decode logs emitted in `BLOCK` and run them against a
+// `MemoryStore` that already holds its header, as `BlockRecorder` would have
+// stored it before dispatch.
+
+use crate::blocks::BlockHeader;
+use crate::polymarket::contracts::ctf_exchange::OrderFilledFilter;
+use crate::polymarket::events::PolymarketEvent;
+use crate::registry::DecodedLog;
+use crate::store::{MarketStore, MemoryStore};
+use chrono::{DateTime, Utc};
+use ethers::abi::{encode, Token};
+use ethers::contract::EthEvent;
+use ethers::types::{Address, Log, H256, U256, U64};
+use std::sync::Arc;
+
+/// Block the fixture logs are emitted in
+pub const BLOCK: u64 = 5;
+
+/// A fixed time plus `seconds`
+pub fn time(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+}
+
+/// Header of block `number`: hashes follow the number and blocks are two
+/// seconds apart, starting from the fixed time
+pub fn block(number: u64) -> BlockHeader {
+    BlockHeader {
+        number,
+        hash: H256::from_low_u64_be(number * 256),
+        parent_hash: H256::from_low_u64_be(number.saturating_sub(1) * 256),
+        timestamp: time(number as i64 * 2).timestamp() as u64,
+    }
+}
+
+/// Memory store holding the header of `BLOCK`
+pub async fn store() -> Arc<MemoryStore> {
+    let store = Arc::new(MemoryStore::new());
+    store.upsert_blocks(&[block(BLOCK)]).await.unwrap();
+    store
+}
+
+/// Log emitted by `address` in `BLOCK`, with the ABI encoding of `data`
+pub fn log(address: Address, topics: Vec<H256>, data: &[Token], log_index: u64) -> Log {
+    Log {
+        address,
+        topics,
+        data: encode(data).into(),
+        block_number: Some(U64::from(BLOCK)),
+        transaction_hash: Some(H256::repeat_byte(0xcd)),
+        log_index: Some(U256::from(log_index)),
+        ..Default::default()
+    }
+}
+
+/// OrderFilled on `exchange`: wallet 1 pays 3 USDC for 10 shares of token 7
+pub fn order_filled(exchange: Address, log_index: u64) -> Log {
+    let amounts = [0u64, 7, 3_000_000, 10_000_000, 0];
+    log(
+        exchange,
+        vec![
+            OrderFilledFilter::signature(),
+            H256::repeat_byte(0xaa),
+            H256::from(Address::repeat_byte(1)),
+            H256::from(Address::repeat_byte(2)),
+        ],
+        &amounts.map(|a| Token::Uint(U256::from(a))),
+        log_index,
+    )
+}
+
+/// `logs` decoded as `EventRegistry::dispatch` hands them to a handler
+pub fn decoded(logs: &[Log]) -> Vec<DecodedLog> {
+    logs.iter()
+        .map(|log| DecodedLog {
+            log: log.clone(),
+            event: PolymarketEvent::decode(log).unwrap(),
+        })
+        .collect()
+}
@@ -0,0 +1,769 @@
+// Wallets - per-wallet positions, realized PnL and win rate
+//
+// Replays the stored fills, position events and outcome token transfers in
+// chain order, keeping for each wallet and outcome token the shares held and
+// the collateral paid for them:
+//
+// - fills: the order owner (`maker`) buys shares for collateral or sells
+//   them; the fee is charged in what the owner receives, shares on a buy
+//   and collateral on a sell. Token-for-token fills move no collateral and
+//   are skipped
+// - splits acquire a full set of the market's tokens for the collateral
+//   split, merges dispose of a full set for the collateral merged, and
+//   redemptions dispose of the redeemer's shares of the market for the payout
+// - transfers between wallets move shares at the sender's average cost.
+//   Mints, burns and transfers involving the exchanges or the NegRiskAdapter
+//   are the token side of a fill or position event and are skipped
+//
+// Disposals realize their proceeds less the average cost of the shares.
+// Once a market's question resolves, the shares still held are valued at
+// their payout, so a wallet that never redeems still shows its result. A
+// wallet wins a resolved market when its realized PnL over the market's
+// tokens is positive.
+//
+// Amounts are raw 6-decimal units, for collateral and shares alike.
+
+use crate::candles::Resolution;
+use crate::config::ContractsConfig;
+use crate::db::models::{
+    Fill as FillRow, OutcomeToken, PositionEvent as PositionEventRow,
+    TokenTransfer as TokenTransferRow, UmaQuestion, WalletPosition, WalletStats,
+};
+use crate::polymarket::positions::PositionEventKind;
+use crate::store::MarketStore;
+use ethers::types::Address;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use tracing::{info, warn};
+
+/// One wallet's shares of a token and what they cost
+#[derive(Debug, Clone, Default)]
+struct Holding {
+    shares: i64,
+    cost: i64,
+    realized: i64,
+    first_block: i64,
+    last_block: i64,
+}
+
+impl Holding {
+    fn acquire(&mut self, shares: i64, cost: i64) {
+        self.shares += shares;
+        self.cost += cost;
+    }
+
+    /// Sell or burn `shares` for `proceeds`, realizing the difference with
+    /// their average cost
+    ///
+    /// Only the shares held count: any others were acquired before the
+    /// replayed blocks, at an unknown cost.
+    fn dispose(&mut self, shares: i64, proceeds: i64) {
+        let (covered, cost) = self.remove(shares);
+        if covered > 0 {
+            self.realized += mul_div(proceeds, covered, shares) - cost;
+        }
+    }
+
+    /// Take up to `shares` out at average cost; returns the shares and cost
+    /// taken
+    fn remove(&mut self, shares: i64) -> (i64, i64) {
+        let covered = shares.min(self.shares).max(0);
+        if covered == 0 {
+            return (0, 0);
+        }
+        let cost = mul_div(self.cost, covered, self.shares);
+        self.shares -= covered;
+        self.cost -= cost;
+        (covered, cost)
+    }
+}
+
+/// `a * b / c` without intermediate overflow
+fn mul_div(a: i64, b: i64, c: i64) -> i64 {
+    (a as i128 * b as i128 / c as i128) as i64
+}
+
+/// Collateral `shares` of outcome `outcome_index` pay out at `payouts`
+fn payout_value(payouts: &[u64], outcome_index: i16, shares: i64) -> Option<i64> {
+    let total: u64 = payouts.iter().sum();
+    let payout = *payouts.get(usize::try_from(outcome_index).ok()?)?;
+    if total == 0 {
+        return None;
+    }
+    Some((shares as i128 * payout as i128 / total as i128) as i64)
+}
+
+/// Payout numerators of a resolved question
+fn question_payouts(question: &UmaQuestion) -> Option<Vec<u64>> {
+    question
+        .payouts
+        .as_ref()?
+        .as_array()?
+        .iter()
+        .map(|payout| payout.as_str()?.parse().ok())
+        .collect()
+}
+
+/// Wallet positions built up event by event
+struct Ledger {
+    /// Exchange and NegRiskAdapter addresses, and the zero address
+    contracts: HashSet<String>,
+    /// Holdings by (wallet, token ID)
+    holdings: BTreeMap<(String, String), Holding>,
+    /// Exchange volume and fills by wallet
+    trading: HashMap<String, (i64, i64)>,
+    /// (wallet, condition ID) of redemptions
+    redeemed: BTreeSet<(String, String)>,
+}
+
+impl Ledger {
+    fn new(contracts: &ContractsConfig) -> Self {
+        let contracts = [
+            contracts.ctf_exchange,
+            contracts.neg_risk_exchange,
+            contracts.neg_risk_adapter,
+            Address::zero(),
+        ];
+        Self {
+            contracts: contracts.iter().map(|a| format!("{:?}", a)).collect(),
+            holdings: BTreeMap::new(),
+            trading: HashMap::new(),
+            redeemed: BTreeSet::new(),
+        }
+    }
+
+    fn holding(&mut self, wallet: &str, token_id: &str, block: i64) -> &mut Holding {
+        let holding = self
+            .holdings
+            .entry((wallet.to_string(), token_id.to_string()))
+            .or_insert_with(|| Holding {
+                first_block: block,
+                ..Default::default()
+            });
+        holding.last_block = block;
+        holding
+    }
+
+    /// Apply the order owner's side of a fill
+    ///
+    /// The owner pays the fee: it adds to the cost of a buy and comes off the
+    /// proceeds of a sell. Volume counts the collateral traded, before fees.
+    fn apply_fill(&mut self, fill: &FillRow) {
+        let (Ok(maker_amount), Ok(taker_amount), Ok(fee)) = (
+            fill.maker_amount_filled.parse::<i64>(),
+            fill.taker_amount_filled.parse::<i64>(),
+            fill.fee.parse::<i64>(),
+        ) else {
+            warn!(
+                "Skipping fill {}:{} with amounts {} and {} and fee {}",
+                fill.tx_hash,
+                fill.log_index,
+                fill.maker_amount_filled,
+                fill.taker_amount_filled,
+                fill.fee
+            );
+            return;
+        };
+
+        let wallet = &fill.maker;
+        let block = fill.block_number;
+        let collateral = match (fill.maker_asset_id == "0", fill.taker_asset_id == "0") {
+            (true, false) => {
+                self.holding(wallet, &fill.taker_asset_id, block)
+                    .acquire(taker_amount - fee, maker_amount);
+                maker_amount
+            }
+            (false, true) => {
+                self.holding(wallet, &fill.maker_asset_id, block)
+                    .dispose(maker_amount, taker_amount - fee);
+                taker_amount
+            }
+            _ => return,
+        };
+
+        let (volume, trades) = self.trading.entry(wallet.clone()).or_default();
+        *volume += collateral;
+        *trades += 1;
+    }
+
+    /// Apply a split, merge or redemption; `tokens` are the market's tokens
+    /// in outcome order and `payouts` its payout numerators, if resolved
+    fn apply_position_event(
+        &mut self,
+        event: &PositionEventRow,
+        tokens: &[OutcomeToken],
+        payouts: Option<&[u64]>,
+    ) {
+        // The exchanges split and merge to match complementary orders; the
+        // fills already carry those trades
+        if self.contracts.contains(&event.stakeholder) || tokens.is_empty() {
+            return;
+        }
+        let (Ok(kind), Ok(amount)) = (
+            event.kind.parse::<PositionEventKind>(),
+            event.amount.parse::<i64>(),
+        ) else {
+            warn!(
+                "Skipping position event {}:{} with amount {}",
+                event.tx_hash, event.log_index, event.amount
+            );
+            return;
+        };
+
+        let wallet = &event.stakeholder;
+        let block = event.block_number;
+        let count = tokens.len() as i64;
+        // Collateral is shared evenly across the set, remainder on the first
+        let share = |i: usize| amount / count + if i == 0 { amount % count } else { 0 };
+        match kind {
+            PositionEventKind::Split => {
+                for (i, token) in tokens.iter().enumerate() {
+                    self.holding(wallet, &token.token_id, block)
+                        .acquire(amount, share(i));
+                }
+            }
+            PositionEventKind::Merge => {
+                for (i, token) in tokens.iter().enumerate() {
+                    self.holding(wallet, &token.token_id, block)
+                        .dispose(amount, share(i));
+                }
+            }
+            PositionEventKind::Redeem => self.redeem(event, tokens, payouts, amount),
+        }
+    }
+
+    /// Dispose of every share the redeemer holds in the market, splitting
+    /// the payout by what each token pays (by shares when that's unknown)
+    fn redeem(
+        &mut self,
+        event: &PositionEventRow,
+        tokens: &[OutcomeToken],
+        payouts: Option<&[u64]>,
+        payout: i64,
+    ) {
+        let wallet = &event.stakeholder;
+        let held: Vec<(&OutcomeToken, i64)> = tokens
+            .iter()
+            .filter_map(|token| {
+                let key = (wallet.clone(), token.token_id.clone());
+                let shares = self.holdings.get(&key)?.shares;
+                (shares > 0).then_some((token, shares))
+            })
+            .collect();
+        if held.is_empty() {
+            return;
+        }
+
+        let by_payout: Vec<i64> = held
+            .iter()
+            .map(|(token, shares)| {
+                payouts
+                    .and_then(|p| payout_value(p, token.outcome_index, *shares))
+                    .unwrap_or(0)
+            })
+            .collect();
+        let weights: Vec<i64> = if by_payout.iter().sum::<i64>() > 0 {
+            by_payout
+        } else {
+            held.iter().map(|(_, shares)| *shares).collect()
+        };
+        let total: i64 = weights.iter().sum();
+
+        let mut remaining = payout;
+        for (i, ((token, shares), weight)) in held.iter().zip(&weights).enumerate() {
+            let proceeds = if i + 1 == held.len() {
+                remaining
+            } else {
+                mul_div(payout, *weight, total)
+            };
+            remaining -= proceeds;
+            self.holding(wallet, &token.token_id, event.block_number)
+                .dispose(*shares, proceeds);
+        }
+        self.redeemed
+            .insert((wallet.clone(), event.condition_id.clone()));
+    }
+
+    /// Move shares between wallets at the sender's average cost
+    fn apply_transfer(&mut self, transfer: &TokenTransferRow) {
+        if transfer.from_address == transfer.to_address
+            || [
+                &transfer.operator,
+                &transfer.from_address,
+                &transfer.to_address,
+            ]
+            .iter()
+            .any(|address| self.contracts.contains(*address))
+        {
+            return;
+        }
+        let Ok(amount) = transfer.amount.parse::<i64>() else {
+            warn!(
+                "Skipping transfer {}:{}:{} with amount {}",
+                transfer.tx_hash, transfer.log_index, transfer.batch_index, transfer.amount
+            );
+            return;
+        };
+
+        let key = (transfer.from_address.clone(), transfer.token_id.clone());
+        let Some(sender) = self.holdings.get_mut(&key) else {
+            return;
+        };
+        sender.last_block = transfer.block_number;
+        let (shares, cost) = sender.remove(amount);
+        if shares > 0 {
+            self.holding(
+                &transfer.to_address,
+                &transfer.token_id,
+                transfer.block_number,
+            )
+            .acquire(shares, cost);
+        }
+    }
+
+    /// Token IDs held or traded by any wallet
+    fn token_ids(&self) -> BTreeSet<String> {
+        self.holdings
+            .keys()
+            .map(|(_, token)| token.clone())
+            .collect()
+    }
+
+    /// Settle positions in resolved markets and total them per wallet
+    ///
+    /// `tokens` maps token IDs to their market, `payouts` condition IDs of
+    /// resolved markets to their payout numerators.
+    fn finish(
+        self,
+        tokens: &HashMap<String, OutcomeToken>,
+        payouts: &HashMap<String, Vec<u64>>,
+    ) -> (Vec<WalletPosition>, Vec<WalletStats>) {
+        let mut positions = Vec::new();
+        for ((wallet, token_id), holding) in self.holdings {
+            let token = tokens.get(&token_id);
+            let value = token.and_then(|token| {
+                payout_value(
+                    payouts.get(&token.condition_id)?,
+                    token.outcome_index,
+                    holding.shares,
+                )
+            });
+            positions.push(WalletPosition {
+                wallet,
+                token_id,
+                condition_id: token.map(|token| token.condition_id.clone()),
+                shares: holding.shares,
+                cost_basis: holding.cost,
+                avg_price: (holding.shares > 0)
+                    .then(|| holding.cost as f64 / holding.shares as f64),
+                realized_pnl: holding.realized + value.map_or(0, |v| v - holding.cost),
+                settled: value.is_some(),
+                first_block: holding.first_block,
+                last_block: holding.last_block,
+            });
+        }
+
+        let mut stats = Vec::new();
+        for wallet_positions in positions.chunk_by(|a, b| a.wallet == b.wallet) {
+            let wallet = &wallet_positions[0].wallet;
+            let mut markets: BTreeMap<&str, (i64, bool)> = BTreeMap::new();
+            for position in wallet_positions {
+                let Some(condition_id) = &position.condition_id else {
+                    continue;
+                };
+                let (pnl, resolved) = markets.entry(condition_id).or_default();
+                *pnl += position.realized_pnl;
+                *resolved |= position.settled
+                    || self
+                        .redeemed
+                        .contains(&(wallet.clone(), condition_id.clone()));
+            }
+            let resolved = markets.values().filter(|(_, resolved)| *resolved).count() as i64;
+            let winning = markets
+                .values()
+                .filter(|(pnl, resolved)| *resolved && *pnl > 0)
+                .count() as i64;
+            let (volume, trades) = self.trading.get(wallet).copied().unwrap_or_default();
+
+            stats.push(WalletStats {
+                wallet: wallet.clone(),
+                markets: markets.len() as i64,
+                open_positions: wallet_positions
+                    .iter()
+                    .filter(|p| p.shares > 0 && !p.settled)
+                    .count() as i64,
+                resolved_markets: resolved,
+                winning_markets: winning,
+                win_rate: (resolved > 0).then(|| winning as f64 / resolved as f64),
+                realized_pnl: wallet_positions.iter().map(|p| p.realized_pnl).sum(),
+                volume,
+                trades,
+                first_block: wallet_positions
+                    .iter()
+                    .map(|p| p.first_block)
+                    .min()
+                    .unwrap_or(0),
+                last_block: wallet_positions
+                    .iter()
+                    .map(|p| p.last_block)
+                    .max()
+                    .unwrap_or(0),
+            });
+        }
+
+        (positions, stats)
+    }
+}
+
+/// A stored event to replay
+enum Entry<'a> {
+    Fill(&'a FillRow),
+    Position(&'a PositionEventRow),
+    Transfer(&'a TokenTransferRow),
+}
+
+impl Entry<'_> {
+    /// (block, log index, batch index)
+    fn chain_position(&self) -> (i64, i64, i32) {
+        match self {
+            Entry::Fill(f) => (f.block_number, f.log_index, 0),
+            Entry::Position(e) => (e.block_number, e.log_index, 0),
+            Entry::Transfer(t) => (t.block_number, t.log_index, t.batch_index),
+        }
+    }
+
+    /// Wallets whose holdings the entry can change
+    fn wallets(&self) -> Vec<&String> {
+        match self {
+            Entry::Fill(f) => vec![&f.maker],
+            Entry::Position(e) => vec![&e.stakeholder],
+            Entry::Transfer(t) => vec![&t.from_address, &t.to_address],
+        }
+    }
+}
+
+/// Markets' tokens and payouts, looked up once per condition
+struct Markets<'a> {
+    store: &'a dyn MarketStore,
+    tokens: HashMap<String, Vec<OutcomeToken>>,
+    payouts: HashMap<String, Option<Vec<u64>>>,
+}
+
+impl<'a> Markets<'a> {
+    fn new(store: &'a dyn MarketStore) -> Self {
+        Self {
+            store,
+            tokens: HashMap::new(),
+            payouts: HashMap::new(),
+        }
+    }
+
+    /// Look up a market's tokens and payouts unless already known
+    async fn load(&mut self, condition_id: &str) -> eyre::Result<()> {
+        if self.tokens.contains_key(condition_id) {
+            return Ok(());
+        }
+        let tokens = self.store.get_outcome_tokens(condition_id).await?;
+        let payouts = self
+            .store
+            .get_question(condition_id)
+            .await?
+            .and_then(|question| question_payouts(&question));
+        self.tokens.insert(condition_id.to_string(), tokens);
+        self.payouts.insert(condition_id.to_string(), payouts);
+        Ok(())
+    }
+
+    /// Tokens of a loaded market, in outcome order
+    fn tokens(&self, condition_id: &str) -> &[OutcomeToken] {
+        self.tokens.get(condition_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Payout numerators of a loaded market, if resolved
+    fn payouts(&self, condition_id: &str) -> Option<&[u64]> {
+        self.payouts.get(condition_id)?.as_deref()
+    }
+}
+
+/// Replay every stored fill, position event and transfer and write the
+/// wallet tables with the result
+///
+/// With `active_in`, a block range (inclusive), only the wallets with an
+/// entry in that range are rewritten and every other stored wallet is kept.
+/// Their figures still come from the whole replay, so they match a complete
+/// rebuild; without it the tables are replaced as a whole.
+///
+/// Shares acquired before the first stored block have no known cost and
+/// disposing of them realizes nothing, so complete figures need the history
+/// from the exchanges' deployment. Markets count as resolved once their UMA
+/// question resolved or the wallet redeemed. Returns the positions and
+/// wallets written.
+pub async fn rebuild(
+    store: &dyn MarketStore,
+    contracts: &ContractsConfig,
+    active_in: Option<(u64, u64)>,
+) -> eyre::Result<(usize, usize)> {
+    // Block numbers are stored as BIGINT
+    let last_block = i64::MAX as u64;
+    let ranges = [
+        store.get_fill_time_range(0, last_block).await?,
+        store.get_position_time_range(0, last_block).await?,
+        store.get_transfer_time_range(0, last_block).await?,
+    ];
+    let is_active =
+        |block: i64| active_in.is_some_and(|(from, to)| (from as i64..=to as i64).contains(&block));
+
+    let mut ledger = Ledger::new(contracts);
+    let mut markets = Markets::new(store);
+    let mut active = BTreeSet::new();
+    if let Some(first) = ranges.iter().flatten().map(|(first, _)| *first).min() {
+        let last = ranges
+            .iter()
+            .flatten()
+            .map(|(_, last)| *last)
+            .max()
+            .unwrap_or(first);
+
+        let mut day = Resolution::Day.bucket_start(first);
+        while day <= last {
+            let next = day + Resolution::Day.duration();
+
+            let fills = store.get_fills_between(day, next).await?;
+            let events = store.get_position_events_between(day, next).await?;
+            let transfers = store.get_transfers_between(day, next).await?;
+            let mut entries: Vec<Entry> = fills
+                .iter()
+                .map(Entry::Fill)
+                .chain(events.iter().map(Entry::Position))
+                .chain(transfers.iter().map(Entry::Transfer))
+                .collect();
+            entries.sort_by_key(Entry::chain_position);
+
+            for entry in &entries {
+                if is_active(entry.chain_position().0) {
+                    active.extend(entry.wallets().into_iter().cloned());
+                }
+                match entry {
+                    Entry::Fill(fill) => ledger.apply_fill(fill),
+                    Entry::Position(event) => {
+                        markets.load(&event.condition_id).await?;
+                        ledger.apply_position_event(
+                            event,
+                            markets.tokens(&event.condition_id),
+                            markets.payouts(&event.condition_id),
+                        );
+                    }
+                    Entry::Transfer(transfer) => ledger.apply_transfer(transfer),
+                }
+            }
+            info!(
+                "Replayed {} wallet events on {}",
+                entries.len(),
+                day.date_naive()
+            );
+            day = next;
+        }
+    }
+
+    let mut tokens = HashMap::new();
+    for token_id in ledger.token_ids() {
+        if let Some(token) = store.get_outcome_token(&token_id).await? {
+            tokens.insert(token_id, token);
+        }
+    }
+    let mut payouts = HashMap::new();
+    let conditions: BTreeSet<String> = tokens.values().map(|t| t.condition_id.clone()).collect();
+    for condition_id in conditions {
+        markets.load(&condition_id).await?;
+        if let Some(p) = markets.payouts(&condition_id) {
+            payouts.insert(condition_id.clone(), p.to_vec());
+        }
+    }
+
+    let (mut positions, mut stats) = ledger.finish(&tokens, &payouts);
+    let wallets: Option<Vec<String>> = active_in.map(|_| {
+        positions.retain(|p| active.contains(&p.wallet));
+        stats.retain(|s| active.contains(&s.wallet));
+        active.into_iter().collect()
+    });
+    store
+        .replace_wallets(wallets.as_deref(), &positions, &stats)
+        .await?;
+    Ok((positions.len(), stats.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    const ALICE: &str = "0x01";
+    const BOB: &str = "0x02";
+
+    fn token(token_id: &str, outcome_index: i16) -> OutcomeToken {
+        OutcomeToken {
+            token_id: token_id.to_string(),
+            condition_id: "0xaa".to_string(),
+            outcome_index,
+            outcome_label: None,
+            complement_token_id: String::new(),
+            created_at: DateTime::default(),
+            updated_at: DateTime::default(),
+        }
+    }
+
+    /// `wallet` buys (or sells) `shares` whole shares of token 7 at `cents`
+    fn fill(block: i64, wallet: &str, buy: bool, cents: i64, shares: i64) -> FillRow {
+        let (collateral, tokens) = (
+            (cents * shares * 10_000).to_string(),
+            (shares * 1_000_000).to_string(),
+        );
+        let (maker_asset_id, taker_asset_id, maker_amount, taker_amount) = if buy {
+            ("0", "7", collateral, tokens)
+        } else {
+            ("7", "0", tokens, collateral)
+        };
+        FillRow {
+            tx_hash: "0xef".to_string(),
+            log_index: 0,
+            exchange: "0xee".to_string(),
+            order_hash: String::new(),
+            maker: wallet.to_string(),
+            taker: "0xee".to_string(),
+            maker_asset_id: maker_asset_id.to_string(),
+            taker_asset_id: taker_asset_id.to_string(),
+            maker_amount_filled: maker_amount,
+            taker_amount_filled: taker_amount,
+            fee: "0".to_string(),
+            token_id: None,
+            side: None,
+            price: None,
+            size: None,
+            notional: None,
+            block_number: block,
+            block_time: DateTime::default(),
+            created_at: DateTime::default(),
+        }
+    }
+
+    fn position(block: i64, kind: PositionEventKind, amount: i64) -> PositionEventRow {
+        PositionEventRow {
+            tx_hash: "0xef".to_string(),
+            log_index: 0,
+            contract: "0xc7".to_string(),
+            kind: kind.to_string(),
+            condition_id: "0xaa".to_string(),
+            stakeholder: ALICE.to_string(),
+            amount: amount.to_string(),
+            block_number: block,
+            block_time: DateTime::default(),
+            created_at: DateTime::default(),
+        }
+    }
+
+    fn transfer(block: i64, from: &str, to: &str, shares: i64) -> TokenTransferRow {
+        TokenTransferRow {
+            tx_hash: "0xef".to_string(),
+            log_index: 0,
+            batch_index: 0,
+            operator: from.to_string(),
+            from_address: from.to_string(),
+            to_address: to.to_string(),
+            token_id: "7".to_string(),
+            amount: (shares * 1_000_000).to_string(),
+            block_number: block,
+            block_time: DateTime::default(),
+            created_at: DateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_trades_and_settlement() {
+        let mut ledger = Ledger::new(&ContractsConfig::default());
+        ledger.apply_fill(&fill(1, ALICE, true, 40, 100));
+        ledger.apply_fill(&fill(2, ALICE, true, 60, 100));
+        // Average cost 50c: selling at 70c realizes 20c a share
+        ledger.apply_fill(&fill(3, ALICE, false, 70, 100));
+        ledger.apply_transfer(&transfer(4, ALICE, BOB, 50));
+        // Minted and exchange-settled transfers are part of other events
+        ledger.apply_transfer(&transfer(
+            5,
+            "0x0000000000000000000000000000000000000000",
+            BOB,
+            10,
+        ));
+
+        let tokens = HashMap::from([("7".to_string(), token("7", 0))]);
+        let payouts = HashMap::from([("0xaa".to_string(), vec![1, 0])]);
+        let (positions, stats) = ledger.finish(&tokens, &payouts);
+
+        assert_eq!(positions.len(), 2);
+        let alice = &positions[0];
+        assert_eq!((alice.shares, alice.cost_basis), (50_000_000, 25_000_000));
+        assert_eq!(alice.avg_price, Some(0.5));
+        // 20 on the sale, 25 on the 50 shares paying out 1 each
+        assert_eq!(alice.realized_pnl, 45_000_000);
+        assert!(alice.settled);
+        assert_eq!((alice.first_block, alice.last_block), (1, 4));
+
+        let bob = &positions[1];
+        assert_eq!((bob.shares, bob.cost_basis), (50_000_000, 25_000_000));
+        assert_eq!(bob.realized_pnl, 25_000_000);
+
+        assert_eq!(stats[0].volume, 170_000_000);
+        assert_eq!(stats[0].trades, 3);
+        assert_eq!(stats[0].open_positions, 0);
+        assert_eq!(stats[0].win_rate, Some(1.0));
+        assert_eq!(stats[1].trades, 0);
+    }
+
+    #[test]
+    fn test_fees_count_against_the_trader() {
+        let mut ledger = Ledger::new(&ContractsConfig::default());
+        let mut buy = fill(1, ALICE, true, 50, 100);
+        buy.fee = "20000000".to_string();
+        let mut sell = fill(2, ALICE, false, 70, 40);
+        sell.fee = "2000000".to_string();
+        ledger.apply_fill(&buy);
+        ledger.apply_fill(&sell);
+
+        let (positions, stats) = ledger.finish(&HashMap::new(), &HashMap::new());
+        // 50 for 100 shares less 20 in fee; half sold for 28 less the 2 fee
+        assert_eq!(
+            (positions[0].shares, positions[0].cost_basis),
+            (40_000_000, 25_000_000)
+        );
+        assert_eq!(positions[0].realized_pnl, 1_000_000);
+        assert_eq!(stats[0].volume, 78_000_000);
+    }
+
+    #[test]
+    fn test_split_sell_and_redeem() {
+        let tokens = [token("7", 0), token("8", 1)];
+        let mut ledger = Ledger::new(&ContractsConfig::default());
+        ledger.apply_position_event(
+            &position(1, PositionEventKind::Split, 10_000_000),
+            &tokens,
+            None,
+        );
+        // The other side sells for less than the 5 it cost
+        let mut sale = fill(2, ALICE, false, 30, 10);
+        sale.maker_asset_id = "8".to_string();
+        ledger.apply_fill(&sale);
+        ledger.apply_position_event(
+            &position(3, PositionEventKind::Redeem, 10_000_000),
+            &tokens,
+            Some(&[1, 0]),
+        );
+
+        // Resolved through the redemption alone: no question payouts
+        let tokens = tokens.map(|t| (t.token_id.clone(), t)).into();
+        let (positions, stats) = ledger.finish(&tokens, &HashMap::new());
+        let pnl: Vec<i64> = positions.iter().map(|p| p.realized_pnl).collect();
+        assert_eq!(pnl, [5_000_000, -2_000_000]);
+        assert!(positions.iter().all(|p| p.shares == 0 && !p.settled));
+        assert_eq!(stats[0].realized_pnl, 3_000_000);
+        assert_eq!((stats[0].open_positions, stats[0].markets), (0, 1));
+        assert_eq!(stats[0].resolved_markets, 1);
+        assert_eq!(stats[0].win_rate, Some(1.0));
+    }
+}
@@ -0,0 +1,385 @@
+// End-to-end backfill tests
+//
+// Both tests run the real backfill path (RPC logs -> decode -> Gamma -> store)
+// into an in-memory store:
+// - against responses captured in tests/fixtures/backfill, fully offline
+// - against a local anvil chain with a log-emitting contract and a mock Gamma
+//   server (ignored by default: run with `--ignored` where `anvil` is on PATH)
+
+use axum::extract::{Path, Query};
+use axum::routing::get;
+use axum::{Json, Router};
+use ethers::contract::EthEvent;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, Bytes, TransactionRequest, H256, U256};
+use ethers::utils::Anvil;
+use polymarket_indexer::cli::backfill::{self, BackfillArgs};
+use polymarket_indexer::cli::StoreArgs;
+use polymarket_indexer::config::{Config, FixtureMode, ProviderKind};
+use polymarket_indexer::polymarket::contracts::ctf_exchange::TokenRegisteredFilter;
+use polymarket_indexer::polymarket::ctf;
+use polymarket_indexer::shutdown::Shutdown;
+use polymarket_indexer::store::{MarketStore, MemoryStore};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Creation code for a contract whose runtime emits LOG4 with the four
+/// 32-byte words of its calldata as topics and no data:
+///
+///   PUSH1 0x60 CALLDATALOAD  PUSH1 0x40 CALLDATALOAD
+///   PUSH1 0x20 CALLDATALOAD  PUSH1 0x00 CALLDATALOAD
+///   PUSH1 0x00 PUSH1 0x00 LOG4 STOP
+///
+/// prefixed with a constructor that copies those 18 bytes and returns them.
+const EMITTER_CREATION_CODE: &str = "6012 80 600b 6000 39 6000 f3 \
+     6060 35 6040 35 6020 35 6000 35 6000 6000 a4 00";
+
+/// Condition ID of the market with Gamma metadata in the fixtures
+const FIXTURE_MARKET: &str = "0x5f65177b394277fd294cd75650044e32ba009a95022d88a0c1d565897d72f8f1";
+/// Condition ID of the market Gamma doesn't know in the fixtures
+const FIXTURE_UNKNOWN_MARKET: &str =
+    "0x9c0c5b2e8a8a0b47f2b1e0b3d5e1c7a4f6a8d9e0b1c2d3e4f5a6b7c8d9e0f1a2";
+/// Question ID of the QuestionInitialized event in the fixtures
+const FIXTURE_QUESTION: [u8; 32] = [0xc3; 32];
+
+fn range(from_block: u64, to_block: u64) -> BackfillArgs {
+    BackfillArgs {
+        days: None,
+        hours: None,
+        minutes: None,
+        from_block: Some(from_block),
+        to_block: Some(to_block),
+        resume: false,
+        store: StoreArgs { dry_run: true },
+    }
+}
+
+fn test_config() -> Config {
+    let mut config = Config::default();
+    config.gamma.max_retries = 0;
+    config
+}
+
+fn replay_config() -> Config {
+    let mut config = test_config();
+    config.fixtures.mode = FixtureMode::Replay;
+    config.fixtures.dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/backfill").into();
+    // The log filter, and so the fixture key, depends on the adapters; the
+    // fixtures were recorded with the v2 adapter only
+    config.contracts.uma_ctf_adapters.truncate(1);
+    config
+}
+
+/// A shutdown that is never requested
+fn no_shutdown() -> Shutdown {
+    Shutdown::manual(Duration::ZERO).1
+}
+
+async fn tag_labels(store: &MemoryStore, condition_id: &str) -> Vec<String> {
+    store
+        .get_tags_for_market(condition_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter_map(|t| t.label)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_backfill_replays_recorded_range() {
+    let store = Arc::new(MemoryStore::new());
+    let config = replay_config();
+    let stats = backfill::backfill(
+        &range(60_000_000, 60_000_010),
+        &config,
+        store.clone(),
+        &no_shutdown(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(stats.inserted, 2);
+    assert_eq!(stats.failed, 0);
+    assert_eq!(stats.anomalies, 0);
+    assert_eq!(stats.tags_inserted, 2);
+    assert_eq!(store.count_markets().await.unwrap(), 2);
+    assert_eq!(
+        store.get_checkpoint(backfill::CHECKPOINT).await.unwrap(),
+        Some(60_000_010)
+    );
+
+    let market = store.get_market(FIXTURE_MARKET).await.unwrap().unwrap();
+    assert_eq!(market.block_number, 60_000_002);
+    assert_eq!(
+        market.question.as_deref(),
+        Some("Will the Fed cut rates in December?")
+    );
+    assert_eq!(market.pm_market_id.as_deref(), Some("512340"));
+    assert_eq!(market.outcomes, Some(json!(["Yes", "No"])));
+    assert_eq!(
+        tag_labels(&store, FIXTURE_MARKET).await,
+        ["Economy", "Politics"]
+    );
+    let tokens = store.get_outcome_tokens(FIXTURE_MARKET).await.unwrap();
+    let labels: Vec<_> = tokens.iter().map(|t| t.outcome_label.as_deref()).collect();
+    assert_eq!(labels, [Some("Yes"), Some("No")]);
+    assert_eq!(tokens[0].complement_token_id, tokens[1].token_id);
+
+    // Gamma lists it as active and open
+    assert_eq!(market.status, "trading");
+    let transitions = store.get_status_transitions(FIXTURE_MARKET).await.unwrap();
+    assert_eq!(transitions.len(), 1);
+    assert_eq!(transitions[0].from_status, "registered");
+    assert_eq!(transitions[0].source, "gamma");
+    assert_eq!(stats.status_changes, 1);
+
+    // Headers of the three blocks holding logs were recorded
+    assert_eq!(stats.blocks, 3);
+    let block = store.get_block(60_000_002).await.unwrap().unwrap();
+    assert_eq!(market.registered_at, Some(block.timestamp));
+    assert_eq!(block.timestamp.timestamp(), 1_722_470_404);
+    assert_eq!(
+        block.hash,
+        "0x000000000000000000000000000000000000000000000000000000001908b10e"
+    );
+
+    // Unknown to Gamma: stored without metadata for `enrich` to retry
+    let unknown = store
+        .get_market(FIXTURE_UNKNOWN_MARKET)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(unknown.metadata_fetched_at.is_none());
+    assert_eq!(unknown.status, "registered");
+    // Registered with the outcome 1 token first; stored in outcome order
+    assert_eq!(
+        unknown.token0,
+        "78402469549025342833534473781364085523451592521993098074180553384256839360811"
+    );
+    assert_eq!(
+        store.get_markets_without_metadata(10).await.unwrap().len(),
+        1
+    );
+
+    // The UMA question is linked through the condition the adapter prepared
+    assert_eq!(stats.question_events, 1);
+    let adapter = config.contracts.uma_ctf_adapters[0];
+    let condition_id = ctf::condition_id(adapter, FIXTURE_QUESTION, 2);
+    let question = store
+        .get_question(&format!("0x{}", hex::encode(condition_id)))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        question.title.as_deref(),
+        Some("Will the Fed cut rates in December?")
+    );
+    assert_eq!(question.status, "initialized");
+    assert_eq!(question.request_timestamp, Some(1_733_011_200));
+}
+
+#[tokio::test]
+async fn test_replay_fails_outside_recorded_range() {
+    let store = Arc::new(MemoryStore::new());
+    let err = backfill::backfill(&range(1, 5), &replay_config(), store, &no_shutdown())
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("no recorded response"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_backfill_stops_when_shutdown_requested() {
+    let (trigger, shutdown) = Shutdown::manual(Duration::ZERO);
+    trigger.send(true).unwrap();
+
+    let store = Arc::new(MemoryStore::new());
+    let stats = backfill::backfill(
+        &range(60_000_000, 60_000_010),
+        &replay_config(),
+        store.clone(),
+        &shutdown,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(stats.inserted, 0);
+    assert_eq!(store.count_markets().await.unwrap(), 0);
+    assert_eq!(
+        store.get_checkpoint(backfill::CHECKPOINT).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn test_resume_starts_after_checkpoint() {
+    let args = BackfillArgs {
+        resume: true,
+        ..range(60_000_000, 60_000_010)
+    };
+
+    // Checkpoint before the range: the whole range is indexed
+    let store = Arc::new(MemoryStore::new());
+    store
+        .set_checkpoint(backfill::CHECKPOINT, 59_000_000)
+        .await
+        .unwrap();
+    let stats = backfill::backfill(&args, &replay_config(), store.clone(), &no_shutdown())
+        .await
+        .unwrap();
+    assert_eq!(stats.inserted, 2);
+
+    // Checkpoint at the end of the range: nothing is left to fetch
+    let store = Arc::new(MemoryStore::new());
+    store
+        .set_checkpoint(backfill::CHECKPOINT, 60_000_010)
+        .await
+        .unwrap();
+    let stats = backfill::backfill(&args, &replay_config(), store.clone(), &no_shutdown())
+        .await
+        .unwrap();
+    assert_eq!(stats.inserted, 0);
+    assert_eq!(store.count_markets().await.unwrap(), 0);
+}
+
+/// Serve `/markets?condition_ids=` and `/markets/:id/tags` from fixed data
+async fn spawn_mock_gamma(markets: Vec<Value>, tags: HashMap<String, Value>) -> String {
+    let app = Router::new()
+        .route(
+            "/markets",
+            get(move |Query(query): Query<HashMap<String, String>>| {
+                let wanted = query.get("condition_ids").cloned().unwrap_or_default();
+                let found: Vec<Value> = markets
+                    .iter()
+                    .filter(|m| m["conditionId"] == wanted.as_str())
+                    .cloned()
+                    .collect();
+                async move { Json(found) }
+            }),
+        )
+        .route(
+            "/markets/:id/tags",
+            get(move |Path(id): Path<String>| {
+                let found = tags.get(&id).cloned().unwrap_or_else(|| json!([]));
+                async move { Json(found) }
+            }),
+        );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    format!("http://{}", addr)
+}
+
+/// Send a transaction to the emitter that logs `topics`
+async fn emit(provider: &Provider<Http>, from: Address, emitter: Address, topics: [H256; 4]) {
+    let data: Vec<u8> = topics.iter().flat_map(|t| t.0).collect();
+    let tx = TransactionRequest::new().from(from).to(emitter).data(data);
+    provider
+        .send_transaction(tx, None)
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .expect("emit transaction receipt");
+}
+
+fn word(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256(bytes)
+}
+
+#[tokio::test]
+#[ignore = "requires anvil on PATH"]
+async fn test_backfill_against_anvil() {
+    let anvil = Anvil::new().spawn();
+    let provider = Provider::<Http>::try_from(anvil.endpoint())
+        .unwrap()
+        .interval(Duration::from_millis(10));
+    let from = anvil.addresses()[0];
+
+    let code: Bytes = EMITTER_CREATION_CODE.replace(' ', "").parse().unwrap();
+    let receipt = provider
+        .send_transaction(TransactionRequest::new().from(from).data(code), None)
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .expect("deploy receipt");
+    let emitter = receipt.contract_address.expect("deployed address");
+
+    // One market with metadata, registered twice with swapped tokens as the
+    // exchange does, and one unrelated event from the same contract
+    let condition_id = H256::repeat_byte(0x42);
+    let (token0, token1) = (word(U256::from(1001)), word(U256::from(1002)));
+    let signature = TokenRegisteredFilter::signature();
+    emit(
+        &provider,
+        from,
+        emitter,
+        [signature, token0, token1, condition_id],
+    )
+    .await;
+    emit(
+        &provider,
+        from,
+        emitter,
+        [signature, token1, token0, condition_id],
+    )
+    .await;
+    emit(
+        &provider,
+        from,
+        emitter,
+        [H256::repeat_byte(0xee), token0, token1, condition_id],
+    )
+    .await;
+
+    let condition_hex = format!("{:?}", condition_id);
+    let gamma_url = spawn_mock_gamma(
+        vec![json!({
+            "id": "7",
+            "question": "Will anvil mine this block?",
+            "slug": "anvil-block",
+            "conditionId": condition_hex,
+            "outcomes": "[\"Yes\", \"No\"]",
+        })],
+        HashMap::from([(
+            "7".to_string(),
+            json!([{"id": "1", "label": "Testing", "slug": "testing"}]),
+        )]),
+    )
+    .await;
+
+    let mut config = test_config();
+    config.rpc.provider = ProviderKind::Custom;
+    config.rpc.http_url = Some(anvil.endpoint());
+    config.contracts.ctf_exchange = emitter;
+    config.gamma.base_url = gamma_url;
+
+    let head = provider.get_block_number().await.unwrap().as_u64();
+    let store = Arc::new(MemoryStore::new());
+    let stats = backfill::backfill(&range(0, head), &config, store.clone(), &no_shutdown())
+        .await
+        .unwrap();
+
+    assert_eq!(stats.inserted, 1);
+    assert_eq!(stats.tags_inserted, 1);
+    // Emitted in separate transactions, with tokens no collateral derives
+    let anomalies = store.get_anomalies(&condition_hex).await.unwrap();
+    let kinds: Vec<_> = anomalies.iter().map(|a| a.kind.as_str()).collect();
+    assert_eq!(kinds, ["split_registration", "unverified_token_pair"]);
+    assert_eq!(store.count_markets().await.unwrap(), 1);
+
+    let market = store.get_market(&condition_hex).await.unwrap().unwrap();
+    assert_eq!(
+        market.question.as_deref(),
+        Some("Will anvil mine this block?")
+    );
+    assert_eq!([market.token0, market.token1], ["1001", "1002"]);
+    assert_eq!(tag_labels(&store, &condition_hex).await, ["Testing"]);
+}